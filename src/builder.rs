@@ -0,0 +1,152 @@
+use std::fmt::Display;
+use std::mem;
+
+use crate::{ExecutionUnit, ProgressManager, StatusHandle, TaskGroup};
+
+/// Builds a [`ProgressManager`] and its groups in one expression, returned
+/// by [`ProgressManager::builder`].
+///
+/// The manager is only built, never run: [`build`](Self::build) hands it
+/// back to be configured further or started. Groups built here and groups
+/// added with [`add_group`](Self::add_group) run in the order they were added.
+///
+/// ```rust
+/// # use hflow::{ExecutionUnit, ProgressManager, TaskGroup};
+/// let mut manager = ProgressManager::builder()
+///     .group("Database", |group| {
+///         group
+///             .task("Create schema", |_status| Ok::<(), String>(()))
+///             .task("Seed data", |status| {
+///                 status.set_message("42 rows");
+///                 Ok::<(), String>(())
+///             })
+///             .continue_on_error(true);
+///     })
+///     .group("App", |group| {
+///         group.task("Start server", |_status| Ok::<(), String>(()));
+///     })
+///     .add_group(TaskGroup::builder().task("Smoke test", |_status| Ok::<(), String>(())).build())
+///     .configure(|manager| manager.writer(std::io::sink()))
+///     .build();
+///
+/// let mut cleanup = TaskGroup::new();
+/// cleanup.add_unit(ExecutionUnit::new("Clean up".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+/// manager.add_group(cleanup);
+///
+/// let names: Vec<_> = manager.groups().iter().map(|group| group.name()).collect();
+/// assert_eq!(names, [Some("Database"), Some("App"), None, None]);
+/// assert_eq!(manager.groups()[0].units().len(), 2);
+/// manager.start().unwrap();
+/// assert!(manager.groups()[0].units()[1].is_completed());
+/// ```
+pub struct ProgressManagerBuilder {
+    manager: ProgressManager,
+}
+
+impl ProgressManagerBuilder {
+    pub(crate) fn new() -> Self {
+        Self { manager: ProgressManager::new() }
+    }
+
+    /// Adds a group printed under the header `name`, see
+    /// [`TaskGroup::new_named`], whose units are added by `build`.
+    pub fn group<F>(self, name: impl Into<String>, build: F) -> Self
+    where
+        F: FnOnce(&mut TaskGroupBuilder),
+    {
+        let mut group = TaskGroupBuilder::new(TaskGroup::new_named(name));
+        build(&mut group);
+        self.add_group(group.build())
+    }
+
+    /// Adds a group built elsewhere, see [`ProgressManager::add_group`].
+    pub fn add_group(mut self, group: TaskGroup) -> Self {
+        self.manager.add_group(group);
+        self
+    }
+
+    /// Calls `configure` with the manager, for the settings without a
+    /// builder method of their own.
+    pub fn configure<F>(mut self, configure: F) -> Self
+    where
+        F: FnOnce(&mut ProgressManager),
+    {
+        configure(&mut self.manager);
+        self
+    }
+
+    /// Returns the manager with its groups, ready to be started.
+    pub fn build(self) -> ProgressManager {
+        self.manager
+    }
+}
+
+/// Adds units to a [`TaskGroup`] in a chain of calls, returned by
+/// [`TaskGroup::builder`] and handed to the closures of
+/// [`ProgressManagerBuilder::group`].
+///
+/// ```rust
+/// # use hflow::{ExecutionStatus, ExecutionUnit, TaskGroup};
+/// let mut group = TaskGroup::builder()
+///     .task("Fetch", |_status| Ok::<(), String>(()))
+///     .task("Verify", |_status| Err("checksum mismatch"))
+///     .add_unit(ExecutionUnit::new("Install".to_string()).on_try_execute(|_status| Ok::<(), String>(())))
+///     .continue_on_error(true)
+///     .build();
+///
+/// assert!(group.run(1, 1).is_err());
+/// let statuses: Vec<_> = group.units().iter().map(|unit| unit.status()).collect();
+/// assert_eq!(statuses, [ExecutionStatus::Completed, ExecutionStatus::Failed, ExecutionStatus::Completed]);
+/// ```
+pub struct TaskGroupBuilder {
+    group: TaskGroup,
+}
+
+impl TaskGroupBuilder {
+    pub(crate) fn new(group: TaskGroup) -> Self {
+        Self { group }
+    }
+
+    /// Adds a unit described by `description` running `callback`, see
+    /// [`ExecutionUnit::on_try_execute`].
+    pub fn task<F, E>(&mut self, description: impl Into<String>, callback: F) -> &mut Self
+    where
+        F: Fn(StatusHandle) -> Result<(), E> + Send + 'static,
+        E: Display + Send + Sync + 'static,
+    {
+        self.add_unit(ExecutionUnit::new(description.into()).on_try_execute(callback))
+    }
+
+    /// Adds a unit built elsewhere, see [`TaskGroup::add_unit`].
+    pub fn add_unit(&mut self, unit: ExecutionUnit) -> &mut Self {
+        self.group.add_unit(unit);
+        self
+    }
+
+    /// See [`TaskGroup::continue_on_error`].
+    pub fn continue_on_error(&mut self, enabled: bool) -> &mut Self {
+        self.group.continue_on_error(enabled);
+        self
+    }
+
+    /// See [`TaskGroup::parallel`].
+    pub fn parallel(&mut self, max_concurrency: usize) -> &mut Self {
+        self.group.parallel(max_concurrency);
+        self
+    }
+
+    /// Calls `configure` with the group, for the settings without a
+    /// builder method of their own.
+    pub fn configure<F>(&mut self, configure: F) -> &mut Self
+    where
+        F: FnOnce(&mut TaskGroup),
+    {
+        configure(&mut self.group);
+        self
+    }
+
+    /// Returns the group with its units, leaving an empty one behind.
+    pub fn build(&mut self) -> TaskGroup {
+        mem::take(&mut self.group)
+    }
+}
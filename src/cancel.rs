@@ -0,0 +1,45 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// A cheap, cloneable flag used to stop work from outside.
+///
+/// Tokens form a tree: a token created with [`child_token`](Self::child_token)
+/// is cancelled when its parent is, but cancelling the child leaves the parent
+/// untouched. `ProgressManager` owns the root token of a run, each group and
+/// unit receives a child of it.
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<TokenInner>,
+}
+
+#[derive(Default)]
+struct TokenInner {
+    cancelled: AtomicBool,
+    parent: Option<CancellationToken>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a token that is cancelled together with this one.
+    pub fn child_token(&self) -> Self {
+        Self {
+            inner: Arc::new(TokenInner {
+                cancelled: AtomicBool::new(false),
+                parent: Some(self.clone()),
+            }),
+        }
+    }
+
+    /// Requests cancellation of this token and all of its children.
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+            || self.inner.parent.as_ref().is_some_and(|parent| parent.is_cancelled())
+    }
+}
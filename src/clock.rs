@@ -0,0 +1,138 @@
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// How the timestamps enabled by [`ProgressManager::timestamps`](crate::ProgressManager::timestamps) are written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TimestampFormat {
+    /// RFC 3339 in UTC, `2024-05-01T13:45:07.120Z`.
+    #[default]
+    Utc,
+    /// RFC 3339 with the local offset, `2024-05-01T15:45:07.120+02:00`.
+    /// Falls back to UTC where the offset cannot be determined.
+    Local,
+    /// Seconds since the run started, `+12.480s`.
+    Elapsed,
+}
+
+impl TimestampFormat {
+    /// Formats `at`, `started` being the start of the run.
+    pub(crate) fn format(self, at: SystemTime, now: Instant, started: Instant) -> String {
+        match self {
+            TimestampFormat::Utc => rfc3339(at, 0),
+            TimestampFormat::Local => rfc3339(at, sys::local_offset(at).unwrap_or(0)),
+            TimestampFormat::Elapsed => format!("+{:.3}s", now.saturating_duration_since(started).as_secs_f64()),
+        }
+    }
+}
+
+/// `at` as RFC 3339 with millisecond precision, shifted by `offset` seconds east of UTC.
+fn rfc3339(at: SystemTime, offset: i64) -> String {
+    let since_epoch = at.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let seconds = since_epoch.as_secs() as i64 + offset;
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+    let time = seconds.rem_euclid(86_400);
+    let zone = if offset == 0 {
+        "Z".to_string()
+    } else {
+        let sign = if offset < 0 { '-' } else { '+' };
+        format!("{}{:02}:{:02}", sign, offset.abs() / 3600, offset.abs() % 3600 / 60)
+    };
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}{}",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60,
+        since_epoch.subsec_millis(),
+        zone
+    )
+}
+
+/// Year, month and day of the `days`-th day since 1970-01-01, from
+/// Howard Hinnant's `civil_from_days`.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "dragonfly"
+))]
+mod sys {
+    use std::os::raw::{c_char, c_int, c_long};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[repr(C)]
+    struct Tm {
+        tm_sec: c_int,
+        tm_min: c_int,
+        tm_hour: c_int,
+        tm_mday: c_int,
+        tm_mon: c_int,
+        tm_year: c_int,
+        tm_wday: c_int,
+        tm_yday: c_int,
+        tm_isdst: c_int,
+        tm_gmtoff: c_long,
+        tm_zone: *const c_char,
+    }
+
+    unsafe extern "C" {
+        fn localtime_r(time: *const c_long, result: *mut Tm) -> *mut Tm;
+    }
+
+    /// Offset of the local time zone from UTC at `at`, in seconds.
+    pub(super) fn local_offset(at: SystemTime) -> Option<i64> {
+        let seconds = at.duration_since(UNIX_EPOCH).ok()?.as_secs() as c_long;
+        let mut tm = Tm {
+            tm_sec: 0,
+            tm_min: 0,
+            tm_hour: 0,
+            tm_mday: 0,
+            tm_mon: 0,
+            tm_year: 0,
+            tm_wday: 0,
+            tm_yday: 0,
+            tm_isdst: 0,
+            tm_gmtoff: 0,
+            tm_zone: std::ptr::null(),
+        };
+        // SAFETY: both pointers are valid for the call, localtime_r is thread safe.
+        let result = unsafe { localtime_r(&seconds, &mut tm) };
+        (!result.is_null()).then_some(tm.tm_gmtoff as i64)
+    }
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "dragonfly"
+)))]
+mod sys {
+    use std::time::SystemTime;
+
+    pub(super) fn local_offset(_at: SystemTime) -> Option<i64> {
+        None
+    }
+}
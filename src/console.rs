@@ -0,0 +1,55 @@
+use std::sync::OnceLock;
+
+/// Whether escape sequences written to stdout are interpreted.
+///
+/// On Windows this turns on virtual terminal processing for the console,
+/// once per process; consoles that do not support it print escape
+/// sequences as garbage. Always true elsewhere, and when stdout is not a
+/// console at all.
+pub(crate) fn supports_ansi() -> bool {
+    static SUPPORTED: OnceLock<bool> = OnceLock::new();
+    *SUPPORTED.get_or_init(sys::enable_virtual_terminal)
+}
+
+#[cfg(windows)]
+mod sys {
+    use std::os::raw::c_void;
+
+    type Handle = *mut c_void;
+
+    const STD_OUTPUT_HANDLE: u32 = -11i32 as u32;
+    const INVALID_HANDLE_VALUE: Handle = -1isize as Handle;
+    const ENABLE_VIRTUAL_TERMINAL_PROCESSING: u32 = 0x0004;
+
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        fn GetStdHandle(std_handle: u32) -> Handle;
+        fn GetConsoleMode(console: Handle, mode: *mut u32) -> i32;
+        fn SetConsoleMode(console: Handle, mode: u32) -> i32;
+    }
+
+    pub(super) fn enable_virtual_terminal() -> bool {
+        // SAFETY: the handle comes from GetStdHandle and is checked before
+        // use, `mode` outlives the call that writes it.
+        unsafe {
+            let handle = GetStdHandle(STD_OUTPUT_HANDLE);
+            if handle.is_null() || handle == INVALID_HANDLE_VALUE {
+                return true;
+            }
+            let mut mode = 0;
+            if GetConsoleMode(handle, &mut mode) == 0 {
+                // Redirected to a file or a pipe, nothing interprets the output.
+                return true;
+            }
+            mode & ENABLE_VIRTUAL_TERMINAL_PROCESSING != 0
+                || SetConsoleMode(handle, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING) != 0
+        }
+    }
+}
+
+#[cfg(not(windows))]
+mod sys {
+    pub(super) fn enable_virtual_terminal() -> bool {
+        true
+    }
+}
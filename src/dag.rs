@@ -0,0 +1,34 @@
+/// Orders the nodes of a graph so that every node comes after the ones it
+/// depends on, `dependencies[node]` listing them. Nodes that do not depend
+/// on each other keep their relative order.
+///
+/// Fails with the nodes of a dependency cycle, in order, the first one repeated at the end.
+pub(crate) fn order(dependencies: &[Vec<usize>]) -> Result<Vec<usize>, Vec<usize>> {
+    let mut placed = vec![false; dependencies.len()];
+    let mut order = Vec::with_capacity(dependencies.len());
+    while order.len() < dependencies.len() {
+        let next = (0..dependencies.len())
+            .find(|&node| !placed[node] && dependencies[node].iter().all(|&dependency| placed[dependency]));
+        let Some(node) = next else {
+            return Err(cycle(dependencies, &placed));
+        };
+        placed[node] = true;
+        order.push(node);
+    }
+    Ok(order)
+}
+
+/// Follows the dependencies of the nodes left unplaced, each of which
+/// depends on another unplaced node, until one comes up again.
+fn cycle(dependencies: &[Vec<usize>], placed: &[bool]) -> Vec<usize> {
+    let mut path = Vec::new();
+    let mut node = placed.iter().position(|&placed| !placed).unwrap_or_default();
+    while !path.contains(&node) {
+        path.push(node);
+        node = dependencies[node].iter().copied().find(|&dependency| !placed[dependency]).unwrap_or(node);
+    }
+    let start = path.iter().position(|&visited| visited == node).unwrap_or_default();
+    let mut cycle = path.split_off(start);
+    cycle.push(node);
+    cycle
+}
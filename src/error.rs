@@ -0,0 +1,182 @@
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
+
+use crate::RunSummary;
+
+/// Errors returned by the execution entry points instead of terminating the process.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HflowError {
+    /// A unit finished with [`ExecutionStatus::Failed`](crate::ExecutionStatus::Failed).
+    UnitFailed {
+        description: String,
+        reason: Option<String>,
+        exit_code: Option<u8>,
+    },
+    /// A unit was still running when its timeout expired.
+    TimedOut { description: String, timeout: Duration },
+    /// A unit of a group depends on an id no unit of the group has, see
+    /// [`ExecutionUnit::depends_on`](crate::ExecutionUnit::depends_on).
+    UnknownDependency { description: String, dependency: String },
+    /// Units of a group depend on each other in a cycle, listed by id, or by
+    /// description for units without one; the first is repeated at the end.
+    DependencyCycle { cycle: Vec<String> },
+    /// Every unit that failed or timed out in a run under
+    /// [`FailureMode::RunAll`](crate::FailureMode::RunAll), group by group.
+    Failures(Vec<HflowError>),
+    /// The whole run went past its
+    /// [`total_timeout`](crate::ProgressManager::total_timeout), after
+    /// `completed` of its `total` units completed.
+    RunTimedOut { timeout: Duration, completed: usize, total: usize },
+    /// A group stopped after `failures` of its units failed, see
+    /// [`TaskGroup::max_failures`](crate::TaskGroup::max_failures).
+    CircuitBreakerTripped { failures: usize },
+    /// [`ProgressManager::start_from`](crate::ProgressManager::start_from)
+    /// was given an index past the last of the `groups` of the run.
+    GroupOutOfRange { index: usize, groups: usize },
+    /// No group of the run has the name given to
+    /// [`ProgressManager::start_from_group`](crate::ProgressManager::start_from_group).
+    UnknownGroup { name: String },
+    /// A unit was executed without a closure, see
+    /// [`ExecutionUnit::on_execute`](crate::ExecutionUnit::on_execute).
+    MissingCallback { description: String },
+}
+
+impl HflowError {
+    /// Exit code the process should end with for this error, 1 unless the unit chose one.
+    ///
+    /// ```rust
+    /// # use hflow::ExecutionUnit;
+    /// let mut unit = ExecutionUnit::new("Validate input".to_string())
+    ///     .on_execute(|status| status.fail_with_code(2));
+    ///
+    /// assert_eq!(unit.execute().unwrap_err().exit_code(), 2);
+    /// ```
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            HflowError::UnitFailed { exit_code: Some(code), .. } => i32::from(*code),
+            HflowError::Failures(errors) => errors.first().map_or(1, HflowError::exit_code),
+            _ => 1,
+        }
+    }
+}
+
+impl fmt::Display for HflowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HflowError::UnitFailed { description, reason: None, .. } => write!(f, "unit failed: {}", description),
+            HflowError::UnitFailed { description, reason: Some(reason), .. } => {
+                write!(f, "unit failed: {}: {}", description, reason)
+            }
+            HflowError::TimedOut { description, timeout } => {
+                write!(f, "unit timed out after {:?}: {}", timeout, description)
+            }
+            HflowError::UnknownDependency { description, dependency } => {
+                write!(f, "unit depends on unknown id {:?}: {}", dependency, description)
+            }
+            HflowError::DependencyCycle { cycle } => write!(f, "dependency cycle: {}", cycle.join(" -> ")),
+            HflowError::CircuitBreakerTripped { failures } => {
+                write!(f, "circuit breaker tripped after {} failures", failures)
+            }
+            HflowError::GroupOutOfRange { index, groups } => {
+                write!(f, "cannot resume from group index {}: the run has {} groups", index, groups)
+            }
+            HflowError::UnknownGroup { name } => write!(f, "cannot resume from group {:?}: no group has this name", name),
+            HflowError::MissingCallback { description } => {
+                write!(f, "unit has no closure to execute, set one with on_execute: {}", description)
+            }
+            HflowError::RunTimedOut { timeout, completed, total } => {
+                write!(f, "run timed out after {:?}: {} of {} units completed", timeout, completed, total)
+            }
+            HflowError::Failures(errors) => {
+                write!(f, "{} units failed", errors.len())?;
+                for (idx, error) in errors.iter().enumerate() {
+                    write!(f, "{} {}", if idx == 0 { ":" } else { ";" }, error)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Error for HflowError {}
+
+/// Returned by [`ProgressManager::start`](crate::ProgressManager::start)
+/// when the run failed, with how every unit of it ended.
+///
+/// Converts into the [`HflowError`] it carries, so `?` works in functions
+/// returning one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RunError {
+    /// Why the run failed.
+    pub error: HflowError,
+    /// The same summary [`ProgressManager::on_finish`](crate::ProgressManager::on_finish)
+    /// receives, boxed to keep the error small.
+    pub summary: Box<RunSummary>,
+}
+
+impl RunError {
+    /// Exit code the process should end with, see [`HflowError::exit_code`].
+    ///
+    /// When several units fail, the first of them to fail sets the code.
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionStatus, ExecutionUnit, ProgressManager, TaskGroup};
+    /// let mut group = TaskGroup::new();
+    /// group.continue_on_error(true);
+    /// group.add_unit(ExecutionUnit::new("Validate".to_string()).on_execute(|status| status.fail_with_code(3)));
+    /// group.add_unit(ExecutionUnit::new("Upload".to_string()).on_execute(|status| status.fail_with_code(7)));
+    /// let mut manager = ProgressManager::new();
+    /// manager.add_group(group);
+    ///
+    /// let err = manager.start().unwrap_err();
+    /// assert_eq!(err.summary.count(ExecutionStatus::Failed), 2);
+    /// assert_eq!(err.exit_code(), 3);
+    /// ```
+    pub fn exit_code(&self) -> i32 {
+        self.error.exit_code()
+    }
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl Error for RunError {}
+
+impl From<RunError> for HflowError {
+    fn from(err: RunError) -> Self {
+        err.error
+    }
+}
+
+/// Returned when parsing an [`ExecutionStatus`](crate::ExecutionStatus) from an unknown name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseStatusError {
+    pub(crate) input: String,
+}
+
+impl fmt::Display for ParseStatusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown execution status: {:?}", self.input)
+    }
+}
+
+impl Error for ParseStatusError {}
+
+/// Returned when a [`LineTemplate`](crate::LineTemplate) cannot be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseTemplateError {
+    pub(crate) template: String,
+    pub(crate) reason: String,
+}
+
+impl fmt::Display for ParseTemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid line template {:?}: {}", self.template, self.reason)
+    }
+}
+
+impl Error for ParseTemplateError {}
@@ -0,0 +1,114 @@
+use std::fmt::{Display, Write};
+
+use crate::github;
+
+/// How a run reports its progress.
+///
+/// Set on [`ProgressManager::format`](crate::ProgressManager::format). By
+/// default [`GithubActions`](Self::GithubActions) is used when the
+/// `GITHUB_ACTIONS` environment variable is `true` and the output is stdout
+/// or stderr, [`Human`](Self::Human) otherwise.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Spinners and status lines meant to be read by a person.
+    #[default]
+    Human,
+    /// One JSON object per line for every event of the run, meant to be
+    /// read by another program. Nothing else is printed.
+    JsonLines,
+    /// Status lines for the log of a GitHub Actions step: only appended,
+    /// each group folded between `::group::` and `::endgroup::`, and an
+    /// `::error::` annotation for every failed unit.
+    GithubActions,
+}
+
+impl OutputFormat {
+    /// The format used when none is set, see [`OutputFormat`].
+    pub(crate) fn detect() -> Self {
+        if github::is_detected() { OutputFormat::GithubActions } else { OutputFormat::Human }
+    }
+}
+
+/// One event of a run, written as a single line of JSON.
+///
+/// Fields keep the order they were added in; the `event` field always comes first.
+pub(crate) struct Event {
+    json: String,
+}
+
+impl Event {
+    pub(crate) fn new(name: &str) -> Self {
+        let mut json = String::from("{\"event\":");
+        push_string(&mut json, name);
+        Self { json }
+    }
+
+    /// Adds a string field.
+    pub(crate) fn text(mut self, key: &str, value: &str) -> Self {
+        self.push_key(key);
+        push_string(&mut self.json, value);
+        self
+    }
+
+    /// Adds a string field when there is a `value`.
+    pub(crate) fn optional_text(self, key: &str, value: Option<&str>) -> Self {
+        match value {
+            Some(value) => self.text(key, value),
+            None => self,
+        }
+    }
+
+    /// Adds a field holding an array of strings, unless `values` is empty.
+    pub(crate) fn list(mut self, key: &str, values: &[String]) -> Self {
+        if values.is_empty() {
+            return self;
+        }
+        self.push_key(key);
+        self.json.push('[');
+        for (idx, value) in values.iter().enumerate() {
+            if idx > 0 {
+                self.json.push(',');
+            }
+            push_string(&mut self.json, value);
+        }
+        self.json.push(']');
+        self
+    }
+
+    /// Adds a numeric or boolean field, written as is.
+    pub(crate) fn value(mut self, key: &str, value: impl Display) -> Self {
+        self.push_key(key);
+        let _ = write!(self.json, "{}", value);
+        self
+    }
+
+    fn push_key(&mut self, key: &str) {
+        self.json.push(',');
+        push_string(&mut self.json, key);
+        self.json.push(':');
+    }
+
+    pub(crate) fn into_line(mut self) -> String {
+        self.json.push('}');
+        self.json
+    }
+}
+
+/// Appends `value` as a quoted JSON string.
+fn push_string(json: &mut String, value: &str) {
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
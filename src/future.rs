@@ -0,0 +1,224 @@
+use std::future::Future;
+use std::mem;
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+use std::time::Duration;
+
+use crate::CancellationToken;
+
+/// How often a unit waiting for its future checks whether it was cancelled.
+const CANCEL_POLL: Duration = Duration::from_millis(50);
+
+pub(crate) type BoxFuture = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+/// Hands the futures of units over from their worker threads to the task
+/// awaiting [`Drive`], so they are polled on the runtime of the caller.
+#[derive(Clone, Default)]
+pub(crate) struct Driver {
+    shared: Arc<Mutex<Shared>>,
+}
+
+#[derive(Default)]
+struct Shared {
+    submitted: Vec<Job>,
+    waker: Option<Waker>,
+    /// The awaiting task is gone, futures can no longer run.
+    closed: bool,
+}
+
+struct Job {
+    future: BoxFuture,
+    cancellation: CancellationToken,
+    done: Arc<Done>,
+}
+
+#[derive(Default)]
+struct Done {
+    finished: Mutex<bool>,
+    changed: Condvar,
+}
+
+impl Done {
+    fn finish(&self) {
+        *lock(&self.finished) = true;
+        self.changed.notify_all();
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl Driver {
+    /// Has `future` polled by the awaiting task and blocks until it completes,
+    /// or until `cancellation` fires and the future is dropped.
+    fn run(&self, future: BoxFuture, cancellation: CancellationToken) {
+        let done = Arc::new(Done::default());
+        {
+            let mut shared = lock(&self.shared);
+            if shared.closed {
+                return;
+            }
+            shared.submitted.push(Job {
+                future,
+                cancellation: cancellation.clone(),
+                done: done.clone(),
+            });
+        }
+        self.wake();
+        let mut finished = lock(&done.finished);
+        while !*finished {
+            finished = done
+                .changed
+                .wait_timeout(finished, CANCEL_POLL)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+            if !*finished && cancellation.is_cancelled() {
+                // The future is dropped the next time the task is polled.
+                self.wake();
+            }
+        }
+    }
+
+    fn wake(&self) {
+        let waker = lock(&self.shared).waker.clone();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    /// Drops every future, the units waiting for them return at once.
+    fn close(&self, jobs: &mut Vec<Job>) {
+        let mut shared = lock(&self.shared);
+        shared.closed = true;
+        jobs.append(&mut shared.submitted);
+        drop(shared);
+        for job in jobs.drain(..) {
+            let Job { future, done, .. } = job;
+            drop(future);
+            done.finish();
+        }
+    }
+}
+
+/// Runs `future` to completion on the current thread, dropping it once
+/// `cancellation` fires. `driver`, when set, runs it on the awaiting task instead.
+pub(crate) fn run(driver: Option<&Driver>, future: BoxFuture, cancellation: CancellationToken) {
+    if let Some(driver) = driver {
+        return driver.run(future, cancellation);
+    }
+    struct Unpark(Thread);
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+    let waker = Waker::from(Arc::new(Unpark(thread::current())));
+    let mut context = Context::from_waker(&waker);
+    let mut future = future;
+    while !cancellation.is_cancelled() {
+        if future.as_mut().poll(&mut context).is_ready() {
+            return;
+        }
+        thread::park_timeout(CANCEL_POLL);
+    }
+}
+
+/// Blocking call run by [`Drive`] on the value it moved out.
+type Run<T, R> = Box<dyn FnOnce(&mut T, Driver) -> R + Send>;
+
+enum Stage<T, R> {
+    Idle { blank: T, run: Run<T, R> },
+    Running,
+    Finished,
+}
+
+/// Future running a blocking call on a thread of its own, with `target`
+/// moved out and replaced by a blank value until it returns, while polling
+/// the futures of the units on the task that awaits it.
+///
+/// Dropping it before it is done cancels the run; `target` is then left blank.
+pub(crate) struct Drive<'a, T, R> {
+    target: &'a mut T,
+    stage: Stage<T, R>,
+    driver: Driver,
+    jobs: Vec<Job>,
+    outcome: Arc<Mutex<Option<(T, R)>>>,
+    cancellation: CancellationToken,
+}
+
+/// Runs `run` on `target` once the returned future is first polled.
+pub(crate) fn drive<T, R>(
+    target: &mut T,
+    blank: T,
+    cancellation: CancellationToken,
+    run: impl FnOnce(&mut T, Driver) -> R + Send + 'static,
+) -> Drive<'_, T, R> {
+    Drive {
+        target,
+        stage: Stage::Idle { blank, run: Box::new(run) },
+        driver: Driver::default(),
+        jobs: Vec::new(),
+        outcome: Arc::new(Mutex::new(None)),
+        cancellation,
+    }
+}
+
+impl<T, R> Future for Drive<'_, T, R>
+where
+    T: Send + Unpin + 'static,
+    R: Send + 'static,
+{
+    type Output = R;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<R> {
+        let this = self.get_mut();
+        lock(&this.driver.shared).waker = Some(cx.waker().clone());
+        if let Stage::Idle { .. } = this.stage {
+            let Stage::Idle { blank, run } = mem::replace(&mut this.stage, Stage::Running) else {
+                unreachable!()
+            };
+            let mut target = mem::replace(this.target, blank);
+            let driver = this.driver.clone();
+            let outcome = this.outcome.clone();
+            thread::spawn(move || {
+                let result = run(&mut target, driver.clone());
+                *lock(&outcome) = Some((target, result));
+                driver.wake();
+            });
+        }
+
+        this.jobs.append(&mut lock(&this.driver.shared).submitted);
+        let mut idx = 0;
+        while idx < this.jobs.len() {
+            let job = &mut this.jobs[idx];
+            if job.cancellation.is_cancelled() || job.future.as_mut().poll(cx).is_ready() {
+                // The future is dropped before its unit goes on.
+                let done = this.jobs.swap_remove(idx).done;
+                done.finish();
+            } else {
+                idx += 1;
+            }
+        }
+
+        match lock(&this.outcome).take() {
+            Some((target, result)) => {
+                *this.target = target;
+                this.stage = Stage::Finished;
+                Poll::Ready(result)
+            }
+            None => Poll::Pending,
+        }
+    }
+}
+
+impl<T, R> Drop for Drive<'_, T, R> {
+    fn drop(&mut self) {
+        if let Stage::Running = self.stage {
+            self.cancellation.cancel();
+            self.driver.close(&mut self.jobs);
+        }
+    }
+}
@@ -0,0 +1,13 @@
+use std::env;
+
+/// Whether the process runs as a GitHub Actions step.
+pub(crate) fn is_detected() -> bool {
+    env::var("GITHUB_ACTIONS").is_ok_and(|value| value == "true")
+}
+
+/// A workflow command such as `::group::Build`, with `value` escaped so
+/// line breaks stay part of the message.
+pub(crate) fn command(name: &str, value: &str) -> String {
+    let value = value.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A");
+    format!("::{}::{}", name, value)
+}
@@ -0,0 +1,81 @@
+use std::panic;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::{CancellationToken, ExecutionStatus, ExecutionUnit, HflowError, UnitView};
+
+/// How often [`UnitHandle::wait_timeout`] checks whether the unit is done.
+const WAIT_INTERVAL: Duration = Duration::from_millis(10);
+
+type Worker = JoinHandle<(ExecutionUnit, Result<ExecutionStatus, HflowError>)>;
+
+/// A unit running on a thread of its own, returned by [`ExecutionUnit::spawn`].
+///
+/// Dropping the handle leaves the unit running to the end, unseen.
+pub struct UnitHandle {
+    view: UnitView,
+    cancellation: CancellationToken,
+    worker: Worker,
+}
+
+impl UnitHandle {
+    pub(crate) fn spawn(mut unit: ExecutionUnit) -> Self {
+        let view = unit.view();
+        let cancellation = unit.cancellation_token();
+        let worker = thread::spawn(move || {
+            let result = unit.execute();
+            (unit, result)
+        });
+        Self { view, cancellation, worker }
+    }
+
+    pub fn status(&self) -> ExecutionStatus {
+        self.view.status()
+    }
+
+    /// Read-only access to the unit, which can outlive the handle.
+    pub fn view(&self) -> UnitView {
+        self.view.clone()
+    }
+
+    /// Whether [`execute`](ExecutionUnit::execute) returned, callbacks included.
+    pub fn is_finished(&self) -> bool {
+        self.worker.is_finished()
+    }
+
+    /// Cancels the unit, see [`ExecutionUnit::execute`] for how it stops.
+    pub fn cancel(&self) {
+        self.cancellation.cancel();
+    }
+
+    /// Replaces the message shown on the line of the unit, see [`StatusHandle::set_message`](crate::StatusHandle::set_message).
+    pub fn set_message(&self, message: impl Into<String>) {
+        self.view.status.set_message(Some(message.into()));
+    }
+
+    /// Blocks until the unit is done, returning what
+    /// [`execute`](ExecutionUnit::execute) returned.
+    pub fn wait(self) -> Result<ExecutionStatus, HflowError> {
+        self.join().1
+    }
+
+    /// Blocks until the unit is done or `timeout` elapsed, returning
+    /// whether it is done; [`wait`](Self::wait) then returns at once.
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        while !self.is_finished() {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return false;
+            }
+            thread::sleep(remaining.min(WAIT_INTERVAL));
+        }
+        true
+    }
+
+    /// Like [`wait`](Self::wait), also giving the unit back, e.g. for
+    /// [`take_output`](ExecutionUnit::take_output).
+    pub fn join(self) -> (ExecutionUnit, Result<ExecutionStatus, HflowError>) {
+        self.worker.join().unwrap_or_else(|payload| panic::resume_unwind(payload))
+    }
+}
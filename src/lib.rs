@@ -1,247 +1,7509 @@
-use std::sync::{Arc, Mutex};
-use colored::{Colorize, CustomColor};
-use std::io::{self, Write};
-use std::thread;
-use std::time::Duration;
+use std::fmt::{self, Display};
+#[cfg(feature = "async")]
+use std::future::Future;
+use std::str::FromStr;
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, LockResult, Mutex, OnceLock, PoisonError};
+use std::any::Any;
+use std::io::Write;
+use std::panic::{self, AssertUnwindSafe};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime};
 
-const SPINNER_FRAMES: [&str; 4] = ["—", "\\", "|", "/"];
+mod builder;
+mod cancel;
+mod clock;
+mod console;
+mod dag;
+mod error;
+mod event;
+#[cfg(feature = "async")]
+mod future;
+mod github;
+mod handle;
+mod live;
+mod macros;
+mod observer;
+mod outcome;
+mod pipeline;
+mod pause;
+mod policy;
+pub mod prelude;
+mod progress;
+mod queue;
+mod render;
+mod retry;
+mod scope;
+mod shutdown;
+mod signal;
+mod spinner;
+mod status;
+mod template;
+mod term;
+mod theme;
+#[cfg(feature = "watch")]
+mod watch;
+
+pub use builder::{ProgressManagerBuilder, TaskGroupBuilder};
+pub use cancel::CancellationToken;
+pub use clock::TimestampFormat;
+pub use colored::Color;
+pub use error::{HflowError, ParseStatusError, ParseTemplateError, RunError};
+pub use event::OutputFormat;
+pub use handle::UnitHandle;
+pub use live::ProgressHandle;
+#[doc(hidden)]
+pub use macros::private as __private;
+pub use observer::{StatusObserver, UnitId, UnitInfo};
+pub use outcome::{GroupResult, MapOutcome, RunOutcome, RunSummary, UnitOutcome};
+pub use pause::PauseHandle;
+pub use pipeline::Pipeline;
+pub use policy::{ExitPolicy, FailureMode};
+pub use progress::{BarWidth, ProgressBarStyle};
+pub use queue::QueueHandle;
+pub use retry::{Backoff, Jitter};
+pub use render::ColorMode;
+pub use scope::{Scope, ScopedGroup, ScopedUnit, scope};
+pub use shutdown::ShutdownHandle;
+pub use spinner::SpinnerStyle;
+pub use theme::{Marker, TextStyle, Theme};
+pub use status::StatusGuard;
+pub use template::LineTemplate;
+use event::Event;
+use observer::Observers;
+use progress::{MIN_BAR_WIDTH, Progress};
+use live::{LineId, RENDER_INTERVAL, Renderer};
+use render::{RenderInfo, RenderSettings};
+use retry::{RetryState, Rng};
+use status::{Failure, POISONED_REASON, StatusCell};
+use template::{LineTemplates, LineValues};
+
+/// Longest wait between two checks of the status by the display loop.
+const MAX_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long a cancelled unit waits for its closure to return by default.
+const DEFAULT_CANCEL_GRACE: Duration = Duration::from_secs(1);
+
+/// Running lines show how long the run has left once its time limit is this close.
+const REMAINING_TIME_THRESHOLD: Duration = Duration::from_secs(60);
+
+/// Reason given to the units skipped once a group reached its
+/// [`max_failures`](TaskGroup::max_failures).
+const CIRCUIT_BREAKER_TRIPPED: &str = "circuit breaker tripped";
+
+/// Why the units of a parallel group still running are cancelled after one of them failed.
+const SIBLING_FAILED: &str = "sibling failed";
+
+/// Most workers a parallel group starts beyond its units, for the units
+/// pushed to its queue while it runs.
+const MAX_QUEUE_WORKERS: usize = 64;
+
+/// How often [`StatusHandle::cancelled`] checks the cancellation token.
+const CANCEL_CHECK_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Shortest leader, a space and a dot, between a line and its right-aligned duration.
+const MIN_LEADER: usize = 2;
+
+/// Indentation of the lines printed under a finished unit.
+const REASON_INDENT: &str = "    ";
+
+/// Indentation of the unit lines under the header of a named group.
+const GROUP_INDENT: &str = "  ";
+
+/// Callback receiving the shared status of a unit.
+type StatusCallback = Box<dyn FnOnce(StatusHandle) + Send + 'static>;
+
+/// Invoked with the error returned by the closure of a failed unit, see [`ExecutionUnit::on_failure`].
+type FailureCallback = Box<dyn FnOnce(Option<&(dyn Any + Send + Sync)>, StatusHandle) + Send + 'static>;
+
+/// Main body of a unit, callable again when the unit is retried.
+type ExecuteCallback = Box<dyn Fn(StatusHandle) + Send + 'static>;
+
+/// Holds the main body of a unit between runs; taken by the worker thread
+/// and put back once it returns, so the unit can be executed again.
+type ExecuteSlot = Arc<Mutex<Option<ExecuteCallback>>>;
+
+/// Value returned by the last attempt of a unit, see [`ExecutionUnit::on_execute_returning`].
+type OutputCell = Arc<Mutex<Option<Box<dyn Any + Send>>>>;
+
+/// Invoked on the worker thread as a unit starts, see [`ExecutionUnit::on_start`].
+type StartCallback = Box<dyn FnOnce(&UnitInfo) + Send + 'static>;
+
+/// Invoked on the worker thread before every unit of a group, see [`TaskGroup::before_each`].
+type BeforeEach = Arc<dyn Fn(&UnitInfo) + Send + Sync + 'static>;
+
+/// Invoked on the worker thread after every unit of a group, see [`TaskGroup::after_each`].
+type AfterEach = Arc<dyn Fn(&UnitInfo, ExecutionStatus) -> Result<(), String> + Send + Sync + 'static>;
+
+/// Invoked when a unit shows no progress for too long, see [`ExecutionUnit::on_stall`].
+type StallCallback = Box<dyn FnMut(Duration) + Send + 'static>;
+
+/// Cleanup invoked with the final status of a unit, see [`ExecutionUnit::on_finish`].
+type FinishCallback = Box<dyn FnOnce(ExecutionStatus) + Send + 'static>;
+
+/// Invoked once a group has run, see [`TaskGroup::on_finish`], [`TaskGroup::on_success`]
+/// and [`TaskGroup::on_failure`].
+type GroupFinishCallback = Box<dyn FnOnce(&GroupResult) + Send + 'static>;
+
+/// Cleanup invoked once a whole run is over, see [`ProgressManager::on_finish`].
+type RunFinishCallback = Box<dyn FnOnce(&RunSummary) + Send + 'static>;
+
+/// How long a group waits after a unit that ended with the given status, see
+/// [`TaskGroup::delay_fn`].
+type DelayFn = Box<dyn Fn(ExecutionStatus) -> Duration + Send + 'static>;
+
+/// Predicate deciding whether a unit should run at all.
+type RunCondition = Box<dyn Fn() -> bool + Send + 'static>;
+
+/// Setup run before the first group, see [`ProgressManager::before_all`].
+type SetupHook = Box<dyn FnOnce() -> Result<(), String> + Send + 'static>;
+
+/// Teardown run after the last group, see [`ProgressManager::after_all`].
+type TeardownHook = Box<dyn FnOnce(&RunSummary) + Send + 'static>;
+
+/// Cleanup run once when a run is cut short, see [`ProgressManager::on_interrupt`],
+/// [`ProgressManager::on_timeout`] and [`ProgressManager::on_shutdown`].
+type CleanupHook = Box<dyn FnOnce() + Send + 'static>;
 
 /// Represents the possible states of an individual execution unit.
-#[derive(Clone, Copy, PartialEq)]
+///
+/// The [`Display`] and [`FromStr`] forms use stable lowercase names
+/// (`in_progress`, `completed`, ...) that are safe to store or compare,
+/// as does the serialized form with the `serde` feature.
+///
+/// ```rust
+/// # use hflow::ExecutionStatus;
+/// for status in ExecutionStatus::ALL {
+///     assert_eq!(status.to_string().parse::<ExecutionStatus>(), Ok(status));
+/// }
+/// assert_eq!(ExecutionStatus::InProgress.to_string(), "in_progress");
+/// assert!("done".parse::<ExecutionStatus>().is_err());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum ExecutionStatus {
+    /// Queued, waiting for its turn to run.
+    Pending,
     InProgress,
     Completed,
     Failed,
+    Skipped,
+    /// Finished, but with non-fatal problems. The final line is drawn in
+    /// yellow and the run still succeeds.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::prelude::*;
+    /// # use hflow::ColorMode;
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(buffer.clone());
+    /// manager.color(ColorMode::Always);
+    /// let audit = ExecutionUnit::new("Audit".to_string()).on_execute(|status| status.set(ExecutionStatus::Warning));
+    /// manager.add_group(TaskGroup::new().with_unit(audit));
+    ///
+    /// assert!(manager.start().is_ok());
+    /// assert_eq!(manager.groups()[0].units()[0].status(), ExecutionStatus::Warning);
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// let line = output.lines().find(|line| line.contains("Audit") && !line.contains("started")).unwrap();
+    /// assert!(line.starts_with("\x1b[33m"), "{:?}", line);
+    /// ```
+    Warning,
+    /// Stopped through its cancellation token before finishing.
+    Cancelled,
+    /// Failed and waiting before the next attempt.
+    Retrying,
+    /// Still running when its timeout expired.
+    TimedOut,
 }
 
-/// The smallest unit of work, containing logic and a display loop.
-pub struct ExecutionUnit {
-    status: Arc<Mutex<ExecutionStatus>>,
-    description: Arc<String>,
-    total_groups: Arc<i32>,
-    current_group_idx: Arc<i32>,
-    execute: Option<Box<dyn FnOnce(Arc<Mutex<ExecutionStatus>>) + Send + 'static>>,
-    on_failure : Option<Box<dyn FnOnce(Arc<Mutex<ExecutionStatus>>) + Send + 'static>>,
-    on_sucess : Option<Box<dyn FnOnce(Arc<Mutex<ExecutionStatus>>) + Send + 'static>>,
-}
+impl ExecutionStatus {
+    /// Every status, in declaration order.
+    pub const ALL: [ExecutionStatus; 9] = [
+        ExecutionStatus::Pending,
+        ExecutionStatus::InProgress,
+        ExecutionStatus::Completed,
+        ExecutionStatus::Failed,
+        ExecutionStatus::Skipped,
+        ExecutionStatus::Warning,
+        ExecutionStatus::Cancelled,
+        ExecutionStatus::Retrying,
+        ExecutionStatus::TimedOut,
+    ];
 
-impl ExecutionUnit {
-    /// Creates a new execution unit with a description and the closure to execute.
-    pub fn new(description: String) -> Self {
+    /// Whether the status is final: the unit is not pending, running or waiting to retry.
+    pub fn is_finished(&self) -> bool {
+        !matches!(
+            self,
+            ExecutionStatus::Pending | ExecutionStatus::InProgress | ExecutionStatus::Retrying
+        )
+    }
 
-        Self {
-            status: Arc::new(Mutex::new(ExecutionStatus::InProgress)),
-            description: Arc::new(description),
-            total_groups: Arc::new(0),
-            current_group_idx: Arc::new(0),
-            execute: None,
-            on_failure : None,
-            on_sucess : None,
+    /// Stable lowercase name of the status.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExecutionStatus::Pending => "pending",
+            ExecutionStatus::InProgress => "in_progress",
+            ExecutionStatus::Completed => "completed",
+            ExecutionStatus::Failed => "failed",
+            ExecutionStatus::Skipped => "skipped",
+            ExecutionStatus::Warning => "warning",
+            ExecutionStatus::Cancelled => "cancelled",
+            ExecutionStatus::Retrying => "retrying",
+            ExecutionStatus::TimedOut => "timed_out",
         }
     }
+}
 
-
-    pub fn set_total_groups(&mut self, total: i32) {
-        self.total_groups = Arc::new(total);
+impl Display for ExecutionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
     }
+}
 
-    pub fn set_group_index(&mut self, index: i32) {
-        self.current_group_idx = Arc::new(index);
-    }
+impl FromStr for ExecutionStatus {
+    type Err = ParseStatusError;
 
-    ///thirst for the main callback
-    pub fn on_execute<F>(mut self, callback: F) -> Self
-    where
-        F: 'static + FnOnce(Arc<Mutex<ExecutionStatus>>) + Send + 'static,
-    {
-        self.execute = Some(Box::new(callback));
-        self
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ExecutionStatus::ALL
+            .into_iter()
+            .find(|status| status.as_str() == s)
+            .ok_or_else(|| ParseStatusError { input: s.to_string() })
     }
+}
 
-    ///If it fails, the state calls this action instead of terminating the programme.
-    pub fn on_failure<F>(mut self, action: F) -> Self
-    where
-        F : FnOnce(Arc<Mutex<ExecutionStatus>>) + Send + 'static,
-    {
-        self.on_failure = Some(Box::new(action));
-        self
+/// Serialized as its [`Display`] name, only with the `serde` feature.
+///
+/// ```rust
+/// # use hflow::ExecutionStatus;
+/// for status in ExecutionStatus::ALL {
+///     let json = serde_json::to_string(&status).unwrap();
+///     assert_eq!(json, format!("\"{}\"", status));
+///     assert_eq!(serde_json::from_str::<ExecutionStatus>(&json).unwrap(), status);
+/// }
+/// assert_eq!(serde_json::to_string(&ExecutionStatus::TimedOut).unwrap(), "\"timed_out\"");
+/// assert!(serde_json::from_str::<ExecutionStatus>("\"done\"").is_err());
+/// ```
+#[cfg(feature = "serde")]
+impl serde::Serialize for ExecutionStatus {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
     }
+}
 
-    ///This function is invoked if the status changes to complete.
-    pub fn on_success<F>(mut self, action: F) -> Self
-    where
-        F : FnOnce(Arc<Mutex<ExecutionStatus>>) + Send + 'static,
-    {
-        self.on_sucess = Some(Box::new(action));
-        self
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ExecutionStatus {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
     }
+}
 
-    /// Handles the visual feedback (spinner and status) in the terminal.
-    fn display_progress(&mut self) {
-        let mut spinner = SPINNER_FRAMES.iter().cycle();
-        loop {
-            let current_status = {
-                let guard = self.status.lock().unwrap();
-                *guard
-            };
+/// Handle passed to the unit callbacks, giving access to the shared status
+/// and the cancellation token of the unit.
+#[derive(Clone)]
+pub struct StatusHandle {
+    status: Arc<StatusCell>,
+    cancellation: CancellationToken,
+    pause: PauseHandle,
+    output: ProgressHandle,
+    context: Option<render::Context>,
+    queue: Option<Arc<queue::Queue>>,
+    #[cfg(feature = "async")]
+    driver: Option<future::Driver>,
+}
 
-            match current_status {
-                ExecutionStatus::InProgress => {
-                    let output = format!(
-                        "\r\x1b[2K[{}/{}] {} {}",
-                        self.current_group_idx, self.total_groups, self.description, spinner.next().unwrap()
-                    );
-                    print!("{}", output.custom_color(CustomColor::new(121, 115, 118)));
-                    io::stdout().flush().unwrap();
-                }
-                ExecutionStatus::Completed => {
-                    let output = format!("[{}/{}] {} ✔", self.current_group_idx, self.total_groups, self.description);
-                    print!("\r\x1b[2K");
-                    println!("{}", output.green());
-                    break;
-                }
-                ExecutionStatus::Failed => {
-                    let output = format!("[{}/{}] {} ✘", self.current_group_idx, self.total_groups, self.description);
-                    print!("\r\x1b[2K");
-                    println!("{}", output.red());
+impl StatusHandle {
+    /// Completes or fails the unit from the result of its callback.
+    /// Like [`finish_with`](Self::finish_with), keeping the error for [`ExecutionUnit::on_failure`].
+    fn finish_with_error<E: Display + Send + Sync + 'static>(&self, result: Result<(), E>) {
+        let result = result.map_err(|err| {
+            let reason = err.to_string();
+            self.status.update_failure(|failure| failure.error = Some(Arc::new(err)));
+            reason
+        });
+        self.finish_with(result);
+    }
+
+    fn finish_with<E: Display>(&self, result: Result<(), E>) {
+        let mut guard = self.status.lock().unwrap_or_else(PoisonError::into_inner);
+        match result {
+            Ok(()) => {
+                if *guard == ExecutionStatus::InProgress {
+                    *guard = ExecutionStatus::Completed;
                 }
             }
-            thread::sleep(Duration::from_millis(100));
+            Err(err) => {
+                self.set_failure_reason(err.to_string());
+                *guard = ExecutionStatus::Failed;
+            }
         }
     }
 
-    /// Registers an action to be executed if the task fails.
+    /// Locks the status of the unit, e.g. `*status.lock().unwrap() = ExecutionStatus::Completed`.
+    /// Replaced by [`complete`](Self::complete), [`fail`](Self::fail) and
+    /// [`fail_with`](Self::fail_with), which never hold the lock longer
+    /// than needed; kept for one release for closures written against it.
     ///
-    /// # Important
+    /// Observers see the change once the guard is dropped. If a thread
+    /// panics while holding the guard the unit is marked as failed instead of
+    /// bringing down the display loop.
     ///
-    /// The callback **MUST** do one of these two things:
+    /// ```rust
+    /// # #![allow(deprecated)]
+    /// # use hflow::{ExecutionStatus, ExecutionUnit, HflowError};
+    /// let mut unit = ExecutionUnit::new("Poisoned".to_string()).on_execute(|status| {
+    ///     let status = status.clone();
+    ///     let _ = std::thread::spawn(move || {
+    ///         let _guard = status.lock().unwrap();
+    ///         panic!("helper thread died holding the lock");
+    ///     })
+    ///     .join();
+    /// });
     ///
-    /// 1. **Call `std::process::exit(1)`** to terminate the program
-    /// 2. **Change the status** to another state (NOT recommended)
+    /// let err = unit.execute().unwrap_err();
+    /// assert!(matches!(err, HflowError::UnitFailed { reason: Some(ref r), .. } if r == "worker panicked"));
+    /// assert_eq!(unit.status(), ExecutionStatus::Failed);
+    /// ```
+    #[deprecated(since = "0.2.0", note = "use `complete`, `fail` or `fail_with` instead")]
+    pub fn lock(&self) -> LockResult<StatusGuard<'_>> {
+        self.status.lock()
+    }
+
+    /// Marks the unit as completed, unless it already finished otherwise,
+    /// e.g. failed in another thread of the closure.
     ///
-    /// If it does neither, **it will enter an infinite loop**
-    /// repeatedly printing the failure message.
+    /// ```rust
+    /// # use hflow::{ExecutionStatus, ExecutionUnit};
+    /// let mut unit = ExecutionUnit::new("Compile".to_string()).on_execute(|status| {
+    ///     status.complete();
+    ///     status.fail();
+    ///     status.complete();
+    /// });
     ///
-    /// # Correct Example
+    /// assert!(unit.execute().is_err());
+    /// assert_eq!(unit.status(), ExecutionStatus::Failed);
+    /// ```
+    pub fn complete(&self) {
+        self.status.transition(|status| (status == ExecutionStatus::InProgress).then_some(ExecutionStatus::Completed));
+    }
+
+    /// Marks the unit as failed, see [`fail_with`](Self::fail_with) to say why.
+    pub fn fail(&self) {
+        self.status.set(ExecutionStatus::Failed);
+    }
+
+    /// Marks the unit as failed, with `reason` printed under its red line.
     ///
     /// ```rust
-    /// let mut task = ExecutionUnit::new("Migrate DB", |status| {
-    ///     migrate_db().unwrap();
-    ///     *status.lock().unwrap() = ExecutionStatus::Completed;
+    /// # use hflow::{ExecutionUnit, HflowError};
+    /// let mut unit = ExecutionUnit::new("Migrate".to_string()).on_execute(|status| {
+    ///     status.fail_with("schema is locked");
     /// });
     ///
-    /// task.set_fail_action(|status| {
-    ///     println!("Rollback executed");
-    ///     rollback_migration();
-    ///     std::process::exit(1);  // ← IMPORTANT
-    /// });
+    /// let err = unit.execute().unwrap_err();
+    /// assert!(matches!(err, HflowError::UnitFailed { reason: Some(ref r), .. } if r == "schema is locked"));
     /// ```
+    pub fn fail_with(&self, reason: impl Into<String>) {
+        self.set_failure_reason(reason);
+        self.fail();
+    }
+
+    pub fn cancellation_token(&self) -> &CancellationToken {
+        // What the closure does with the token is unknown, it may be watching it.
+        self.status.set_cancel_seen(true);
+        &self.cancellation
+    }
+
+    /// Handle to push more units to the group of the unit while it runs,
+    /// e.g. one per item the unit discovered; `None` for a unit run on its own.
+    ///
+    /// Dropped along with the closure, unlike a handle from
+    /// [`TaskGroup::queue`] moved into it, which would keep the group waiting.
+    pub fn queue(&self) -> Option<QueueHandle> {
+        self.queue.clone().map(QueueHandle::new)
+    }
+
+    /// Shorthand for `cancellation_token().is_cancelled()`, meant to be polled by long-running work.
     ///
-    /// # Incorrect Example (infinite loop)
+    /// The unit is cancelled when its timeout expires, when its group or
+    /// the manager is cancelled, or through its own token. A closure that
+    /// returns once it sees the flag ends the unit as
+    /// [`ExecutionStatus::Cancelled`] (or [`ExecutionStatus::TimedOut`]),
+    /// whatever it set. Until it returns, its line shows `cancelling…`. One
+    /// that fails without ever seeing the flag, or asking for the
+    /// [token](Self::cancellation_token), stays failed: the failure is its own.
     ///
-    /// ```rust,no_run
-    /// task.set_fail_action(|status| {
-    ///     println!("This will print infinitely");
-    ///     // ← Missing exit(1) here
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use std::time::Duration;
+    /// # use hflow::{ExecutionStatus, ExecutionUnit, ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let mut group = TaskGroup::new();
+    /// group.add_unit(ExecutionUnit::new("Crawl pages".to_string()).on_try_execute(|status| {
+    ///     while !status.is_cancelled() {
+    ///         std::thread::sleep(Duration::from_millis(10));
+    ///     }
+    ///     // Flushing what was crawled so far.
+    ///     std::thread::sleep(Duration::from_millis(300));
+    ///     Err("interrupted")
+    /// }));
+    ///
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.terminal_writer(buffer.clone());
+    /// # manager.ascii(false);
+    /// manager.add_group(group);
+    /// let token = manager.cancellation_token();
+    /// std::thread::spawn(move || {
+    ///     std::thread::sleep(Duration::from_millis(100));
+    ///     token.cancel();
     /// });
+    /// let _ = manager.start();
+    ///
+    /// assert_eq!(manager.groups()[0].units()[0].status(), ExecutionStatus::Cancelled);
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// assert!(output.contains("Crawl pages cancelling…"));
     /// ```
-    pub fn execute(&mut self) {
+    pub fn is_cancelled(&self) -> bool {
+        let cancelled = self.cancellation.is_cancelled();
+        if cancelled {
+            self.status.set_cancel_seen(true);
+        }
+        cancelled
+    }
 
-        let status = self.status.clone();
-        let on_fail = self.on_failure.take();
-        let action = self.execute.take().unwrap();
-        let success = self.on_sucess.take();
+    /// Blocks until the unit is cancelled, e.g. on a thread of the closure
+    /// that tears down a child process, see [`is_cancelled`](Self::is_cancelled).
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionStatus, ExecutionUnit};
+    /// # use std::time::Duration;
+    /// let mut server = ExecutionUnit::new("Serve preview".to_string())
+    ///     .timeout(Duration::from_millis(100))
+    ///     .on_execute(|status| status.cancelled());
+    ///
+    /// assert!(server.execute().is_err());
+    /// assert_eq!(server.status(), ExecutionStatus::TimedOut);
+    /// ```
+    pub fn cancelled(&self) {
+        while !self.cancellation.is_cancelled() {
+            thread::sleep(CANCEL_CHECK_INTERVAL);
+        }
+        self.status.set_cancel_seen(true);
+    }
 
-        let description = self.description.clone();
-        let current_idx = self.current_group_idx.clone();
-        let total = self.total_groups.clone();
+    /// Handle to suspend the spinner line while the closure talks to the terminal.
+    pub fn pause_handle(&self) -> PauseHandle {
+        self.pause.clone()
+    }
 
-        let handle = thread::spawn(move || {
-            action(status.clone());
+    /// Prints a line above the spinner instead of through it, see [`ProgressHandle::println`].
+    ///
+    /// ```rust
+    /// # use hflow::ExecutionUnit;
+    /// let mut fetch = ExecutionUnit::new("Fetch".to_string()).on_try_execute(|status| {
+    ///     status.println("using mirror eu-west");
+    ///     Ok::<(), String>(())
+    /// });
+    /// fetch.execute().unwrap();
+    /// ```
+    pub fn println(&self, message: impl AsRef<str>) {
+        self.output.println(message);
+    }
 
+    /// Handle to print above the spinner from other threads.
+    pub fn progress_handle(&self) -> ProgressHandle {
+        self.output.clone()
+    }
 
-            let final_status = {
-                let guard = status.lock().unwrap();
-                *guard
-            };
+    /// Records why the unit failed, printed under its red line.
+    ///
+    /// Errors returned from [`on_try_execute`](ExecutionUnit::on_try_execute)
+    /// closures and caught panics are recorded automatically.
+    pub fn set_failure_reason(&self, reason: impl Into<String>) {
+        let reason = reason.into();
+        self.status.update_failure(|failure| failure.reason = Some(reason));
+    }
 
-            if final_status == ExecutionStatus::Completed {
-                if let Some(callback) = success {
-                    callback(status.clone());
-                }
-            }
+    /// Marks the unit as failed with the exit code the process should end with.
+    ///
+    /// The code is carried by the [`HflowError`] returned from the run and used
+    /// by [`ProgressManager::start_or_exit`]. When several units fail, the code
+    /// of the first failure wins.
+    pub fn fail_with_code(&self, code: u8) {
+        self.status.update_failure(|failure| failure.exit_code = Some(code));
+        self.fail();
+    }
 
-            if final_status == ExecutionStatus::Failed {
-                if let Some(callback) = on_fail {
-                    println!("tenemos fail");
-                    callback(status.clone());
-                } else {
-                    println!("no tenemos fail");
-                }
-            }
+    /// Reason recorded for the failure of the unit, if any.
+    pub fn failure_reason(&self) -> Option<String> {
+        self.status.failure().reason
+    }
+
+    /// The context set with [`TaskGroup::with_context`] or
+    /// [`ProgressManager::with_context`], `None` when none was set or it is
+    /// not a `C`.
+    pub fn context<C: Send + Sync + 'static>(&self) -> Option<Arc<C>> {
+        self.context.clone()?.downcast().ok()
+    }
+
+    /// Reports how far along the unit is, replacing the spinner with a bar
+    /// like `[#####.....] 52% (eta 1m 10s)`.
+    ///
+    /// The ETA follows a smoothed rate of the recent reports and shows `--`
+    /// until a few of them have been received.
+    ///
+    /// `current` is clamped to `total`. The spinner comes back when `total`
+    /// is zero, after [`clear_progress`](Self::clear_progress), or when no
+    /// progress has been reported for two seconds.
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionStatus, ExecutionUnit};
+    /// let mut copy = ExecutionUnit::new("Copy files".to_string()).on_execute(|status| {
+    ///     let files = 40;
+    ///     for copied in 1..=files {
+    ///         status.set_progress(copied, files);
+    ///     }
+    ///     status.complete();
+    /// });
+    ///
+    /// assert_eq!(copy.execute().unwrap(), ExecutionStatus::Completed);
+    /// ```
+    ///
+    /// On a terminal, the line has no ETA until the first report, then
+    /// `(eta --)` until enough reports arrived to estimate one:
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use std::time::Duration;
+    /// # use hflow::{ColorMode, ExecutionUnit, ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.terminal_writer(buffer.clone());
+    /// manager.color(ColorMode::Never);
+    /// let copy = ExecutionUnit::new("Copy files".to_string()).on_execute(|status| {
+    ///     std::thread::sleep(Duration::from_millis(250));
+    ///     for copied in 1..=8 {
+    ///         status.set_progress(copied, 10);
+    ///         std::thread::sleep(Duration::from_millis(150));
+    ///     }
+    ///     status.complete();
+    /// });
+    /// manager.add_group(TaskGroup::new().with_unit(copy));
+    /// manager.start().unwrap();
+    ///
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// let frames: Vec<&str> = output.split("\r\x1b[J").collect();
+    /// let first_bar = frames.iter().position(|frame| frame.contains('%')).unwrap();
+    /// assert!(frames[..first_bar].iter().all(|frame| !frame.contains("eta")));
+    /// assert!(frames[first_bar].contains("% (eta --)"));
+    /// let etas: Vec<&str> = frames.iter().filter_map(|frame| frame.split("(eta ").nth(1)?.split(')').next()).collect();
+    /// // e.g. `900ms`, `1.2s` or `1m 05s`.
+    /// assert!(etas.iter().any(|eta| eta.starts_with(|c: char| c.is_ascii_digit()) && eta.ends_with('s')), "{:?}", etas);
+    /// ```
+    pub fn set_progress(&self, current: u64, total: u64) {
+        self.status.update_progress(|progress| match progress {
+            Some(progress) => progress.report(current, total),
+            None => *progress = Some(Progress::new(current, total)),
         });
+    }
+
+    /// Goes back to the spinner after [`set_progress`](Self::set_progress).
+    pub fn clear_progress(&self) {
+        self.status.update_progress(|progress| *progress = None);
+    }
 
+    /// Shows what the unit is doing after its description, e.g.
+    /// `[1/3] Build project — downloading 34/120 MB ⠋`.
+    ///
+    /// The line picks up the latest message on its next frame, so frequent
+    /// updates cost no extra redraws. The message is left out of the final
+    /// line unless the unit [keeps it](ExecutionUnit::keep_message), and is
+    /// cleared before every attempt.
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionStatus, ExecutionUnit};
+    /// let mut build = ExecutionUnit::new("Build project".to_string()).on_execute(|status| {
+    ///     status.set_message("resolving deps");
+    ///     status.set_message("linking");
+    ///     status.complete();
+    /// });
+    ///
+    /// assert_eq!(build.execute().unwrap(), ExecutionStatus::Completed);
+    /// ```
+    pub fn set_message(&self, message: impl Into<String>) {
+        self.status.set_message(Some(message.into()));
+    }
 
-        self.display_progress();
+    pub fn clear_message(&self) {
+        self.status.set_message(None);
+    }
+}
 
+/// Reads and writes the status of a unit without holding its lock across
+/// the work of the closure. Brought into scope by [`prelude`].
+///
+/// ```rust
+/// use hflow::prelude::*;
+///
+/// let mut unit = ExecutionUnit::new("Resize images".to_string()).on_execute(|status| {
+///     assert_eq!(status.get(), ExecutionStatus::InProgress);
+///     status.set_message("2 images were skipped");
+///     status.set(ExecutionStatus::Warning);
+/// });
+///
+/// assert_eq!(unit.execute(), Ok(ExecutionStatus::Warning));
+/// ```
+pub trait StatusExt {
+    /// Marks the unit as completed, unless it already finished otherwise.
+    fn complete(&self);
 
-        handle.join().unwrap();
+    /// Marks the unit as failed.
+    fn fail(&self);
 
+    /// Sets the status of the unit, e.g. [`ExecutionStatus::Warning`] for a
+    /// step that worked around a problem.
+    fn set(&self, status: ExecutionStatus);
 
-        let final_status = {
-            let guard = self.status.lock().unwrap();
-            *guard
-        };
+    /// Current status of the unit.
+    fn get(&self) -> ExecutionStatus;
+}
 
-        if final_status == ExecutionStatus::Failed {
-            std::process::exit(1);
-        }
+impl StatusExt for StatusHandle {
+    fn complete(&self) {
+        StatusHandle::complete(self);
+    }
+
+    fn fail(&self) {
+        StatusHandle::fail(self);
+    }
+
+    fn set(&self, status: ExecutionStatus) {
+        self.status.set(status);
+    }
+
+    fn get(&self) -> ExecutionStatus {
+        self.status.get()
     }
 }
 
-/// A logical group of execution units that will be processed sequentially.
-pub struct TaskGroup {
-    units: Vec<ExecutionUnit>,
+/// Cloneable, read-only access to the state of an [`ExecutionUnit`].
+#[derive(Clone)]
+pub struct UnitView {
+    status: Arc<StatusCell>,
+    description: Arc<String>,
 }
 
-impl TaskGroup {
-    pub fn new() -> Self {
-        Self { units: Vec::new() }
+impl UnitView {
+    pub fn status(&self) -> ExecutionStatus {
+        self.status.get()
     }
 
-    pub fn add_unit(&mut self, unit: ExecutionUnit) {
-        self.units.push(unit);
+    pub fn description(&self) -> &str {
+        &self.description
     }
 
-    /// Executes all units within the group one after another.
-    pub fn run(&mut self, total_groups: i32, current_idx: i32) {
-        for unit in &mut self.units {
-            unit.set_group_index(current_idx);
-            unit.set_total_groups(total_groups);
-            unit.execute();
-        }
+    pub fn unit_id(&self) -> UnitId {
+        self.status.unit()
+    }
+
+    pub fn failure_reason(&self) -> Option<String> {
+        self.status.failure().reason
+    }
+
+    pub fn is_completed(&self) -> bool {
+        self.status() == ExecutionStatus::Completed
+    }
+
+    pub fn is_failed(&self) -> bool {
+        self.status() == ExecutionStatus::Failed
     }
 }
 
-/// The main manager that orchestrates multiple task groups.
-pub struct ProgressManager {
-    groups: Vec<TaskGroup>,
+/// The smallest unit of work, containing logic and a display loop.
+pub struct ExecutionUnit {
+    status: Arc<StatusCell>,
+    description: Arc<String>,
+    counter: RenderInfo,
+    execute: Option<ExecuteSlot>,
+    on_failure: Option<FailureCallback>,
+    on_success: Option<StatusCallback>,
+    on_start: Option<StartCallback>,
+    on_finish: Option<FinishCallback>,
+    /// Hooks of the group around the unit, see [`TaskGroup::before_each`].
+    before_each: Option<BeforeEach>,
+    after_each: Option<AfterEach>,
+    run_if: Option<RunCondition>,
+    cancellation: CancellationToken,
+    pause: PauseHandle,
+    spinner: Option<SpinnerStyle>,
+    progress_bar: Option<ProgressBarStyle>,
+    theme: Option<Theme>,
+    render: RenderSettings,
+    retries: u32,
+    backoff: Backoff,
+    timeout: Option<Duration>,
+    start_after: Option<Duration>,
+    /// How long the last execution waited for [`start_after`](Self::start_after).
+    waited: Option<Duration>,
+    cancel_grace: Duration,
+    stall_warning: Option<Duration>,
+    on_stall: Option<StallCallback>,
+    /// How long the running unit has shown no progress, once past its `stall_warning`.
+    stalled: Option<Duration>,
+    allowed_to_fail: bool,
+    keep_message: bool,
+    retry_state: Arc<Mutex<RetryState>>,
+    output: OutputCell,
+    /// Names the unit for [`depends_on`](Self::depends_on).
+    id: Option<String>,
+    depends_on: Vec<String>,
+    tags: Vec<String>,
+    /// Stage of a [`Pipeline`] this one reads the value of.
+    previous_stage: Option<UnitView>,
+    /// Units that must succeed before this one runs, resolved by its group.
+    dependencies: Vec<UnitView>,
+    started_at: Option<Instant>,
+    elapsed: Option<Duration>,
+    live_line: Option<LineId>,
+    /// Left out of the run of its group, see [`ProgressManager::rerun_failed`].
+    excluded: bool,
+    /// Runs again after failing, see [`ProgressManager::rerun_failed`].
+    reran: bool,
+    /// Does not match the tags of [`ProgressManager::start_filtered`].
+    filtered: bool,
+    /// Collects the workers given up on, for a unit of a [`scope`].
+    workers: Option<scope::Workers>,
+    /// Units found by the last execution, see [`TaskGroup::expanding_unit`].
+    found: Option<Arc<Mutex<Vec<ExecutionUnit>>>>,
+    /// Added to its group while it ran, by its queue or an expanding unit.
+    added: bool,
 }
 
-impl ProgressManager {
-    pub fn new() -> Self {
-        Self { groups: Vec::new() }
+impl ExecutionUnit {
+    /// Creates a unit described by `description`, to be given a closure
+    /// with [`on_execute`](Self::on_execute) or
+    /// [`on_try_execute`](Self::on_try_execute); [`with`](Self::with) does
+    /// both at once.
+    pub fn new(description: String) -> Self {
+
+        Self {
+            status: Arc::new(StatusCell::new(UnitId::next())),
+            description: Arc::new(description),
+            counter: RenderInfo::default(),
+            execute: None,
+            on_failure : None,
+            on_success: None,
+            on_start: None,
+            before_each: None,
+            after_each: None,
+            on_finish: None,
+            run_if: None,
+            cancellation: CancellationToken::new(),
+            pause: PauseHandle::new(),
+            spinner: None,
+            progress_bar: None,
+            theme: None,
+            render: RenderSettings::default(),
+            retries: 0,
+            backoff: Backoff::default(),
+            timeout: None,
+            start_after: None,
+            waited: None,
+            cancel_grace: DEFAULT_CANCEL_GRACE,
+            stall_warning: None,
+            on_stall: None,
+            stalled: None,
+            allowed_to_fail: false,
+            keep_message: false,
+            retry_state: Arc::new(Mutex::new(RetryState::default())),
+            output: Arc::new(Mutex::new(None)),
+            id: None,
+            depends_on: Vec::new(),
+            tags: Vec::new(),
+            previous_stage: None,
+            dependencies: Vec::new(),
+            started_at: None,
+            elapsed: None,
+            live_line: None,
+            excluded: false,
+            reran: false,
+            filtered: false,
+            workers: None,
+            found: None,
+            added: false,
+        }
     }
 
-    pub fn add_group(&mut self, group: TaskGroup) {
-        self.groups.push(group);
+
+    /// Creates a unit described by `description` running `callback`, as
+    /// with [`on_try_execute`](Self::on_try_execute).
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionStatus, ExecutionUnit};
+    /// # fn migrate_db() -> Result<(), String> { Ok(()) }
+    /// let mut unit = ExecutionUnit::with("Migrate DB", |status| {
+    ///     status.set_message("applying 3 migrations");
+    ///     migrate_db()
+    /// });
+    ///
+    /// assert_eq!(unit.execute(), Ok(ExecutionStatus::Completed));
+    /// ```
+    pub fn with<F, E>(description: impl Into<String>, callback: F) -> Self
+    where
+        F: Fn(StatusHandle) -> Result<(), E> + Send + 'static,
+        E: Display + Send + Sync + 'static,
+    {
+        Self::new(description.into()).on_try_execute(callback)
     }
 
-    /// Starts the execution of all registered task groups.
-    pub fn start(&mut self) {
-        let total = self.groups.len() as i32;
-        for (idx, group) in self.groups.iter_mut().enumerate() {
-            group.run(total, (idx + 1) as i32);
+    ///Sets the total of the `[1/3]` counter, zero removes the counter.
+    pub fn set_total_groups(&mut self, total: u32) {
+        self.counter.total = Some(total).filter(|&total| total > 0);
+    }
+
+    ///Sets the position shown in the `[1/3]` counter, zero removes the counter.
+    ///
+    ///Units executed on their own, outside of a [`TaskGroup`], print no counter.
+    ///Groups set both before running their units, so a unit run again
+    ///shows its new position.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ExecutionUnit, ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(buffer.clone());
+    /// let lint = ExecutionUnit::new("Lint".to_string()).on_try_execute(|_status| Ok::<(), String>(()));
+    /// manager.add_group(TaskGroup::new().with_unit(lint));
+    /// manager.start().unwrap();
+    ///
+    /// // The second run has two groups, the counter of the first unit follows.
+    /// let test = ExecutionUnit::new("Test".to_string()).on_try_execute(|_status| Ok::<(), String>(()));
+    /// manager.add_group(TaskGroup::new().with_unit(test));
+    /// buffer.0.lock().unwrap().clear();
+    /// manager.start().unwrap();
+    ///
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// let lines: Vec<&str> = output.lines().collect();
+    /// assert_eq!(lines[0], "[1/2] Lint started");
+    /// assert_eq!(lines[2], "[2/2] Test started");
+    /// ```
+    pub fn set_group_index(&mut self, index: u32) {
+        self.counter.index = Some(index).filter(|&index| index > 0);
+    }
+
+    pub fn set_cancellation_token(&mut self, token: CancellationToken) {
+        self.cancellation = token;
+    }
+
+    pub(crate) fn set_observers(&mut self, observers: Observers) {
+        self.status.set_observers(observers);
+    }
+
+    /// Id of this unit in [`StatusObserver`] notifications.
+    pub fn unit_id(&self) -> UnitId {
+        self.status.unit()
+    }
+
+    /// Uses this spinner for the unit instead of the one set on the manager.
+    pub fn with_spinner(mut self, style: SpinnerStyle) -> Self {
+        self.spinner = Some(style);
+        self
+    }
+
+    /// Uses this spinner for the unit, e.g. for a long download among short steps.
+    ///
+    /// The spinner of a unit is the first one set of: the unit's own, the one
+    /// of its group ([`TaskGroup::spinner`]), the one of the manager
+    /// ([`ProgressManager::spinner`]), then the built-in default.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use std::time::Duration;
+    /// # use hflow::{ExecutionUnit, ProgressManager, SpinnerStyle, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let slow = |_status| {
+    ///     std::thread::sleep(Duration::from_millis(200));
+    ///     Ok::<(), String>(())
+    /// };
+    /// let mut fetch = TaskGroup::new();
+    /// fetch.spinner(SpinnerStyle::new(["(group)"], Duration::from_millis(100)));
+    /// fetch.add_unit(
+    ///     ExecutionUnit::new("Download".to_string())
+    ///         .spinner(SpinnerStyle::new(["(unit)"], Duration::from_millis(100)))
+    ///         .on_try_execute(slow),
+    /// );
+    /// fetch.add_unit(ExecutionUnit::new("Verify".to_string()).on_try_execute(slow));
+    /// let mut build = TaskGroup::new();
+    /// build.add_unit(ExecutionUnit::new("Compile".to_string()).on_try_execute(slow));
+    ///
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.terminal_writer(buffer.clone());
+    /// manager.spinner(SpinnerStyle::new(["(manager)"], Duration::from_millis(100)));
+    /// manager.add_group(fetch);
+    /// manager.add_group(build);
+    /// manager.start().unwrap();
+    ///
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// assert!(output.contains("[1/2] Download (unit)"));
+    /// assert!(output.contains("[1/2] Verify (group)"));
+    /// assert!(output.contains("[2/2] Compile (manager)"));
+    /// assert!(!output.contains("Download (group)") && !output.contains("Verify (unit)"));
+    /// ```
+    pub fn spinner(self, style: SpinnerStyle) -> Self {
+        self.with_spinner(style)
+    }
+
+    /// Prints the lines of the unit to `writer` instead of stdout when it is
+    /// executed on its own, see [`ProgressManager::writer`]. A unit run by a
+    /// group prints wherever the group does.
+    ///
+    /// Such a unit has no `[1/3]` counter in front of its lines.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::ExecutionUnit;
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let buffer = Buffer::default();
+    /// let mut lint = ExecutionUnit::new("Lint".to_string())
+    ///     .writer(buffer.clone())
+    ///     .on_try_execute(|_status| Ok::<(), String>(()));
+    /// lint.execute().unwrap();
+    ///
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// assert!(output.lines().count() > 0);
+    /// assert!(output.lines().all(|line| line.starts_with("Lint ")), "{:?}", output);
+    /// assert!(!output.contains("[0/0]"));
+    /// ```
+    pub fn writer(mut self, writer: impl Write + Send + 'static) -> Self {
+        let live = render::is_terminal(&writer);
+        self.render.renderer = Renderer::new(writer, live);
+        self.render.colors &= live;
+        self
+    }
+
+    /// Uses this progress bar for the unit instead of the one set on the manager.
+    pub fn progress_bar(mut self, style: ProgressBarStyle) -> Self {
+        self.progress_bar = Some(style);
+        self
+    }
+
+    /// Uses this theme for the unit instead of the one set on the manager.
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = Some(theme);
+        self
+    }
+
+    pub(crate) fn inherit_render(&mut self, render: RenderSettings) {
+        self.render = render;
+    }
+
+    fn theme(&self) -> &Theme {
+        self.theme.as_ref().unwrap_or(&self.render.theme)
+    }
+
+    /// Handle to pause and resume the display of this unit, e.g. around a prompt.
+    ///
+    /// ```rust
+    /// # use hflow::ExecutionUnit;
+    /// let mut unit = ExecutionUnit::new("Connect to host".to_string())
+    ///     .on_try_execute(|status| {
+    ///         let pause = status.pause_handle();
+    ///         pause.pause();
+    ///         println!("Password for host: ********");
+    ///         pause.resume();
+    ///         Ok::<(), String>(())
+    ///     });
+    ///
+    /// unit.execute().unwrap();
+    /// ```
+    pub fn pause_handle(&self) -> PauseHandle {
+        self.pause.clone()
+    }
+
+    /// Token that cancels this unit when triggered.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancellation.clone()
+    }
+
+    ///Sets the main callback, run on a worker thread, which sets the status
+    ///itself through the [`StatusHandle`].
+    ///
+    /// The callback is invoked again on every retry, see [`retries`](Self::retries).
+    pub fn on_execute<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(StatusHandle) + Send + 'static,
+    {
+        self.execute = Some(Arc::new(Mutex::new(Some(Box::new(callback)))));
+        self
+    }
+
+    ///Sets the main callback from a closure returning a `Result`.
+    ///
+    /// `Ok` marks the unit as completed and `Err` as failed, the error message
+    /// is printed under the ✘ line and passed to
+    /// [`on_failure`](Self::on_failure). This is the recommended way to define
+    /// a unit; use [`on_execute`](Self::on_execute) when the status has to be
+    /// managed by hand.
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionStatus, ExecutionUnit};
+    /// let mut unit = ExecutionUnit::new("Read config".to_string())
+    ///     .on_try_execute(|_status| {
+    ///         let value: u32 = "42".parse()?;
+    ///         assert_eq!(value, 42);
+    ///         Ok::<(), std::num::ParseIntError>(())
+    ///     });
+    ///
+    /// assert_eq!(unit.execute(), Ok(ExecutionStatus::Completed));
+    /// ```
+    pub fn on_try_execute<F, E>(self, callback: F) -> Self
+    where
+        F: Fn(StatusHandle) -> Result<(), E> + Send + 'static,
+        E: Display + Send + Sync + 'static,
+    {
+        self.on_execute(move |status| {
+            let result = callback(status.clone());
+            status.finish_with_error(result);
+        })
+    }
+
+    /// Like [`on_try_execute`](Self::on_try_execute), with the context of the
+    /// run passed along with the handle, e.g. a configuration and a database
+    /// pool every unit needs.
+    ///
+    /// The context is the one set on the group of the unit with
+    /// [`TaskGroup::with_context`], or else on the manager with
+    /// [`ProgressManager::with_context`]. The unit fails when neither is a `C`.
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionUnit, ProgressManager, TaskGroup};
+    /// struct Config {
+    ///     registry: String,
+    /// }
+    ///
+    /// let mut group = TaskGroup::new();
+    /// group.add_unit(ExecutionUnit::new("Pull image".to_string()).on_try_execute_with(|config: std::sync::Arc<Config>, status| {
+    ///     status.set_message(format!("from {}", config.registry));
+    ///     Ok::<(), String>(())
+    /// }));
+    /// group.add_unit(ExecutionUnit::new("Count rows".to_string()).on_try_execute_with(|_pool: std::sync::Arc<u64>, _status| {
+    ///     Ok::<(), String>(())
+    /// }));
+    ///
+    /// let mut manager = ProgressManager::new();
+    /// manager.with_context(Config { registry: "ghcr.io".to_string() });
+    /// manager.add_group(group);
+    /// assert!(manager.start().is_err());
+    ///
+    /// let units = manager.groups()[0].units();
+    /// assert!(units[0].is_completed());
+    /// assert_eq!(units[1].failure_reason().as_deref(), Some("no context of type `u64` was set"));
+    /// ```
+    pub fn on_try_execute_with<C, F, E>(self, callback: F) -> Self
+    where
+        C: Send + Sync + 'static,
+        F: Fn(Arc<C>, StatusHandle) -> Result<(), E> + Send + 'static,
+        E: Display,
+    {
+        self.on_try_execute(move |status| match status.context::<C>() {
+            Some(context) => callback(context, status).map_err(|err| err.to_string()),
+            None => Err(format!("no context of type `{}` was set", std::any::type_name::<C>())),
+        })
+    }
+
+    /// Sets the main callback from a closure computing a value, such as the
+    /// path of a build artifact, to collect with [`take_output`](Self::take_output)
+    /// or [`TaskGroup::run_collect`] once the unit completed.
+    ///
+    /// The unit completes when the closure returns, unless it set another
+    /// status through the handle. The value of a unit that did not complete
+    /// is dropped.
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionStatus, ExecutionUnit};
+    /// let mut migrate = ExecutionUnit::new("Run migrations".to_string()).on_execute_returning(|_status| 7_u32);
+    ///
+    /// assert_eq!(migrate.execute(), Ok(ExecutionStatus::Completed));
+    /// assert_eq!(migrate.take_output::<u32>(), Some(7));
+    /// assert_eq!(migrate.take_output::<u32>(), None);
+    /// ```
+    pub fn on_execute_returning<F, T>(self, callback: F) -> Self
+    where
+        F: Fn(StatusHandle) -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let output = self.output.clone();
+        self.on_execute(move |status| {
+            let value = callback(status.clone());
+            *output.lock().unwrap_or_else(PoisonError::into_inner) = Some(Box::new(value));
+            status.finish_with(Ok::<(), String>(()));
+        })
+    }
+
+    /// Sets the main callback from an async closure, e.g. one awaiting
+    /// `reqwest` or `sqlx` calls. The status is managed by hand through the
+    /// handle, as with [`on_execute`](Self::on_execute).
+    ///
+    /// Under [`ProgressManager::start_async`] or [`TaskGroup::run_async`] the
+    /// future is polled by the task awaiting the run, on the runtime of the
+    /// caller. Otherwise it is polled on the worker thread of the unit,
+    /// which only suits futures that do not need a runtime. When the unit is
+    /// cancelled or times out, the future is dropped.
+    ///
+    /// Only available with the `async` feature.
+    #[cfg(feature = "async")]
+    pub fn on_execute_async<F, Fut>(self, callback: F) -> Self
+    where
+        F: Fn(StatusHandle) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.on_execute(move |status| {
+            let future = Box::pin(callback(status.clone()));
+            future::run(status.driver.as_ref(), future, status.cancellation.clone());
+        })
+    }
+
+    /// Like [`on_try_execute`](Self::on_try_execute) for an async closure,
+    /// see [`on_execute_async`](Self::on_execute_async).
+    ///
+    /// ```rust
+    /// # use std::future::Future;
+    /// # use std::pin::pin;
+    /// # use std::sync::Arc;
+    /// # use std::task::{Context, Poll, Wake, Waker};
+    /// # use std::thread::{self, Thread};
+    /// # struct Unpark(Thread);
+    /// # impl Wake for Unpark {
+    /// #     fn wake(self: Arc<Self>) { self.0.unpark(); }
+    /// # }
+    /// # // Stands in for the runtime of the application, e.g. `#[tokio::main]`.
+    /// # fn block_on<F: Future>(future: F) -> F::Output {
+    /// #     let waker = Waker::from(Arc::new(Unpark(thread::current())));
+    /// #     let mut future = pin!(future);
+    /// #     loop {
+    /// #         if let Poll::Ready(output) = future.as_mut().poll(&mut Context::from_waker(&waker)) {
+    /// #             return output;
+    /// #         }
+    /// #         thread::park();
+    /// #     }
+    /// # }
+    /// use hflow::{ExecutionUnit, ProgressManager, TaskGroup};
+    ///
+    /// async fn fetch(url: &str) -> Result<usize, String> {
+    ///     Ok(url.len())
+    /// }
+    ///
+    /// let caller = thread::current().id();
+    /// let mut group = TaskGroup::new();
+    /// group.add_unit(ExecutionUnit::new("Fetch index".to_string()).on_try_execute_async(move |status| async move {
+    ///     // Polled by the task awaiting `start_async`, not on a thread of hflow.
+    ///     assert_eq!(thread::current().id(), caller);
+    ///     let size = fetch("https://example.com/index.json").await?;
+    ///     status.set_message(format!("{} bytes", size));
+    ///     Ok::<(), String>(())
+    /// }));
+    ///
+    /// let mut manager = ProgressManager::new();
+    /// manager.add_group(group);
+    /// block_on(manager.start_async()).unwrap();
+    /// assert!(manager.groups()[0].units()[0].is_completed());
+    /// ```
+    #[cfg(feature = "async")]
+    pub fn on_try_execute_async<F, Fut, E>(self, callback: F) -> Self
+    where
+        F: Fn(StatusHandle) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: Display + Send + Sync + 'static,
+    {
+        self.on_execute_async(move |status| {
+            let future = callback(status.clone());
+            async move { status.finish_with_error(future.await) }
+        })
+    }
+
+    ///Invoked once if the unit fails, after its last attempt, with the error
+    ///the closure returned to [`on_try_execute`](Self::on_try_execute), the
+    ///one whose message is printed under the red line. It is `None` when the
+    ///unit failed without returning an `E`: the closure of
+    ///[`on_execute`](Self::on_execute) set the status, panicked or timed out,
+    ///or returned an error of another type.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ExecutionUnit, HflowError};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// #[derive(Debug)]
+    /// struct MigrationError {
+    ///     version: u32,
+    /// }
+    ///
+    /// impl std::fmt::Display for MigrationError {
+    ///     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    ///         write!(f, "migration {} failed", self.version)
+    ///     }
+    /// }
+    ///
+    /// let buffer = Buffer::default();
+    /// let rolled_back = Arc::new(Mutex::new(None));
+    /// let seen = rolled_back.clone();
+    /// let mut unit = ExecutionUnit::new("Migrate DB".to_string())
+    ///     .writer(buffer.clone())
+    ///     .on_try_execute(|_status| Err(MigrationError { version: 12 }))
+    ///     .on_failure(move |err: Option<&MigrationError>, _status| {
+    ///         *seen.lock().unwrap() = err.map(|err| (err.version, err.to_string()));
+    ///     });
+    ///
+    /// let err = unit.execute().unwrap_err();
+    /// assert!(matches!(err, HflowError::UnitFailed { reason: Some(ref r), .. } if r == "migration 12 failed"));
+    /// assert_eq!(*rolled_back.lock().unwrap(), Some((12, "migration 12 failed".to_string())));
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// assert!(output.contains("[fail]") && output.ends_with("\n    migration 12 failed\n"), "{:?}", output);
+    ///
+    /// // Without a returned error there is nothing to hand over.
+    /// let missing = Arc::new(Mutex::new(None));
+    /// let seen = missing.clone();
+    /// let mut unit = ExecutionUnit::new("Migrate DB".to_string())
+    ///     .writer(Buffer::default())
+    ///     .on_execute(|status| status.fail_with("disk full"))
+    ///     .on_failure(move |err: Option<&MigrationError>, _status| *seen.lock().unwrap() = Some(err.is_none()));
+    ///
+    /// unit.execute().unwrap_err();
+    /// assert_eq!(*missing.lock().unwrap(), Some(true));
+    /// ```
+    pub fn on_failure<E, F>(mut self, action: F) -> Self
+    where
+        E: 'static,
+        F: FnOnce(Option<&E>, StatusHandle) + Send + 'static,
+    {
+        self.on_failure = Some(Box::new(move |err, status| action(err.and_then(|err| err.downcast_ref::<E>()), status)));
+        self
+    }
+
+    ///Invoked once if the unit completes, after its last attempt.
+    ///
+    /// ```rust
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::ExecutionUnit;
+    /// fn unit(succeeds: bool, calls: &Arc<Mutex<Vec<&'static str>>>) -> ExecutionUnit {
+    ///     let (success, failure) = (calls.clone(), calls.clone());
+    ///     ExecutionUnit::new("Publish".to_string())
+    ///         .on_success(move |_status| success.lock().unwrap().push("success"))
+    ///         .on_execute(move |status| if succeeds { status.complete() } else { status.fail() })
+    ///         .on_failure(move |_: Option<&String>, _status| failure.lock().unwrap().push("failure"))
+    /// }
+    ///
+    /// let calls = Arc::new(Mutex::new(Vec::new()));
+    /// unit(true, &calls).execute().unwrap();
+    /// assert_eq!(*calls.lock().unwrap(), ["success"]);
+    /// unit(false, &calls).execute().unwrap_err();
+    /// assert_eq!(*calls.lock().unwrap(), ["success", "failure"]);
+    /// ```
+    pub fn on_success<F>(mut self, action: F) -> Self
+    where
+        F: FnOnce(StatusHandle) + Send + 'static,
+    {
+        self.on_success = Some(Box::new(action));
+        self
+    }
+
+    /// Invoked once on the worker thread right before the closure first
+    /// runs, e.g. to write an audit log entry or enter a tracing span. Not
+    /// invoked for units that are skipped.
+    ///
+    /// A panic inside the callback fails the unit without running its closure.
+    ///
+    /// ```rust
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ExecutionUnit, TaskGroup};
+    /// let audit = Arc::new(Mutex::new(Vec::new()));
+    /// let mut group = TaskGroup::new();
+    /// for (host, run) in [("db-1", true), ("db-2", false), ("db-3", true)] {
+    ///     let log = audit.clone();
+    ///     group.add_unit(
+    ///         ExecutionUnit::new(format!("Patch {}", host))
+    ///             .id(host)
+    ///             .run_if(move || run)
+    ///             .on_start(move |info| log.lock().unwrap().push(format!("{}/{} {}", info.group, info.unit, info.id.as_deref().unwrap())))
+    ///             .on_try_execute(|_status| Ok::<(), String>(())),
+    ///     );
+    /// }
+    ///
+    /// group.run(1, 1).unwrap();
+    /// assert_eq!(*audit.lock().unwrap(), ["1/1 db-1", "1/3 db-3"]);
+    /// ```
+    pub fn on_start<F>(mut self, callback: F) -> Self
+    where
+        F: FnOnce(&UnitInfo) + Send + 'static,
+    {
+        self.on_start = Some(Box::new(callback));
+        self
+    }
+
+    /// Invoked once with the final status of the unit, whatever it is:
+    /// completed, failed, skipped, cancelled or timed out. Runs after
+    /// [`on_success`](Self::on_success) or [`on_failure`](Self::on_failure)
+    /// and before the group moves on, e.g. to remove a temporary directory.
+    ///
+    /// A panic inside the callback is caught and printed under the unit,
+    /// its status stays as it was.
+    ///
+    /// ```rust
+    /// # use std::sync::{Arc, Mutex};
+    /// # use std::time::Duration;
+    /// # use hflow::{ExecutionStatus, ExecutionUnit};
+    /// let finished = Arc::new(Mutex::new(Vec::new()));
+    /// let seen = finished.clone();
+    /// let mut unit = ExecutionUnit::new("Render video".to_string())
+    ///     .timeout(Duration::from_millis(50))
+    ///     .on_execute(|status| status.cancelled())
+    ///     .on_finish(move |status| {
+    ///         seen.lock().unwrap().push(status);
+    ///         panic!("lock file already removed");
+    ///     });
+    ///
+    /// assert!(unit.execute().is_err());
+    /// assert_eq!(unit.status(), ExecutionStatus::TimedOut);
+    /// assert_eq!(*finished.lock().unwrap(), [ExecutionStatus::TimedOut]);
+    /// ```
+    pub fn on_finish<F>(mut self, callback: F) -> Self
+    where
+        F: FnOnce(ExecutionStatus) + Send + 'static,
+    {
+        self.on_finish = Some(Box::new(callback));
+        self
+    }
+
+    ///Invoked synchronously, with the old and new status, on every transition of the unit.
+    ///
+    /// Each transition is reported exactly once, including the final one, from
+    /// whichever thread performed it and after the status lock was released.
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionStatus, ExecutionUnit};
+    /// # use std::sync::{Arc, Mutex};
+    /// let transitions = Arc::new(Mutex::new(Vec::new()));
+    /// let log = transitions.clone();
+    /// let mut unit = ExecutionUnit::new("Deploy".to_string())
+    ///     .on_status_change(move |old, new| log.lock().unwrap().push((old, new)))
+    ///     .on_try_execute(|_| Ok::<(), String>(()));
+    ///
+    /// unit.execute().unwrap();
+    /// assert_eq!(
+    ///     *transitions.lock().unwrap(),
+    ///     [
+    ///         (ExecutionStatus::Pending, ExecutionStatus::InProgress),
+    ///         (ExecutionStatus::InProgress, ExecutionStatus::Completed),
+    ///     ]
+    /// );
+    /// ```
+    ///
+    /// A panicking hook is ignored, the unit still ends with the status its closure set:
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionStatus, ExecutionUnit};
+    /// let mut unit = ExecutionUnit::new("Deploy".to_string())
+    ///     .on_status_change(|_, _| panic!("observer bug"))
+    ///     .on_try_execute(|_| Ok::<(), String>(()));
+    ///
+    /// assert_eq!(unit.execute(), Ok(ExecutionStatus::Completed));
+    /// ```
+    pub fn on_status_change<F>(self, hook: F) -> Self
+    where
+        F : Fn(ExecutionStatus, ExecutionStatus) + Send + Sync + 'static,
+    {
+        self.status.set_hook(Arc::new(hook));
+        self
+    }
+
+    ///Runs the closure again, up to `retries` more times, when it sets the status to failed.
+    ///
+    /// `on_failure` only fires once the last attempt has failed.
+    ///
+    /// ```rust
+    /// # use hflow::{Backoff, ExecutionStatus, ExecutionUnit};
+    /// # use std::sync::atomic::{AtomicU32, Ordering};
+    /// # use std::time::Duration;
+    /// let calls = AtomicU32::new(0);
+    /// let mut unit = ExecutionUnit::new("Flaky request".to_string())
+    ///     .retries(3)
+    ///     .backoff(Backoff::Fixed(Duration::from_millis(10)))
+    ///     .on_execute(move |status| {
+    ///         let attempt = calls.fetch_add(1, Ordering::SeqCst) + 1;
+    ///         if attempt < 3 {
+    ///             status.fail();
+    ///         } else {
+    ///             status.complete();
+    ///         }
+    ///     });
+    ///
+    /// assert_eq!(unit.execute(), Ok(ExecutionStatus::Completed));
+    /// ```
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    ///Sets the wait between attempts, accepts a [`Backoff`] or a plain `Duration`.
+    ///
+    /// A [`Backoff::custom`] closure that panics fails the unit instead of
+    /// unwinding out of [`execute`](Self::execute).
+    ///
+    /// ```rust
+    /// # use hflow::{Backoff, ExecutionStatus, ExecutionUnit, HflowError};
+    /// let mut unit = ExecutionUnit::new("Flaky request".to_string())
+    ///     .retries(2)
+    ///     .backoff(Backoff::custom(|_| panic!("no delay for you")))
+    ///     .on_execute(|status| status.fail());
+    ///
+    /// let err = unit.execute().unwrap_err();
+    /// assert!(matches!(err, HflowError::UnitFailed { reason: Some(ref r), .. } if r.contains("no delay for you")));
+    /// assert_eq!(unit.status(), ExecutionStatus::Failed);
+    /// ```
+    pub fn backoff(mut self, backoff: impl Into<Backoff>) -> Self {
+        self.backoff = backoff.into();
+        self
+    }
+
+    ///Keeps the last [`set_message`](StatusHandle::set_message) on the final line.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ExecutionUnit, ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(buffer.clone());
+    /// # manager.ascii(false);
+    /// let mut group = TaskGroup::new();
+    /// for keep in [false, true] {
+    ///     group.add_unit(ExecutionUnit::new("Sync".to_string()).keep_message(keep).on_try_execute(|status| {
+    ///         status.set_message("12 files");
+    ///         Ok::<(), String>(())
+    ///     }));
+    /// }
+    /// manager.add_group(group);
+    /// manager.start().unwrap();
+    ///
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// let lines: Vec<&str> = output.lines().collect();
+    /// assert!(lines[1].starts_with("[1/1] Sync [ok]"));
+    /// assert!(lines[3].starts_with("[1/1] Sync — 12 files [ok]"));
+    /// ```
+    pub fn keep_message(mut self, keep: bool) -> Self {
+        self.keep_message = keep;
+        self
+    }
+
+    ///Lets the unit fail without failing the run, for best effort steps.
+    ///
+    /// The failure is still shown in red and `on_failure` still runs, but
+    /// [`execute`](Self::execute) returns `Ok(ExecutionStatus::Failed)` so the
+    /// group carries on and the run is not considered failed.
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionStatus, ExecutionUnit, ProgressManager, TaskGroup};
+    /// let mut group = TaskGroup::new();
+    /// group.add_unit(
+    ///     ExecutionUnit::new("Warm up cache".to_string())
+    ///         .allowed_to_fail(true)
+    ///         .on_try_execute(|_| Err("cache server down")),
+    /// );
+    /// group.add_unit(ExecutionUnit::new("Serve".to_string()).on_try_execute(|_| Ok::<(), &str>(())));
+    ///
+    /// let mut manager = ProgressManager::new();
+    /// manager.add_group(group);
+    /// assert!(manager.start().is_ok());
+    /// assert!(manager.groups()[0].units()[0].is_failed());
+    /// ```
+    pub fn allowed_to_fail(mut self, allowed: bool) -> Self {
+        self.allowed_to_fail = allowed;
+        self
+    }
+
+    ///Labels the unit with `tag`, e.g. `db` or `slow`, to pick the units
+    ///to run with [`ProgressManager::start_filtered`]. May be called several times,
+    ///a tag given twice is only kept once.
+    ///
+    ///Tags are reported in events, [`UnitInfo`] and [`UnitOutcome`], and
+    ///give units a timeout with [`TaskGroup::tag_timeout`]. They are matched
+    ///as exact strings; globs such as `db-*` may come later.
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionUnit, TaskGroup};
+    /// let unit = ExecutionUnit::new("Drop tables".to_string())
+    ///     .tag("db")
+    ///     .tag("destructive")
+    ///     .tag("db")
+    ///     .on_try_execute(|_status| Ok::<(), String>(()));
+    /// assert_eq!(unit.tags(), ["db", "destructive"]);
+    ///
+    /// let mut group = TaskGroup::new().with_unit(unit);
+    /// let result = group.run_report(1, 1);
+    /// assert_eq!(result.units[0].tags, ["db", "destructive"]);
+    /// ```
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        let tag = tag.into();
+        if !self.tags.contains(&tag) {
+            self.tags.push(tag);
+        }
+        self
+    }
+
+    /// The timeout of the unit, or the one of its first tag given a
+    /// [`tag_timeout`](TaskGroup::tag_timeout).
+    fn effective_timeout(&self) -> Option<Duration> {
+        self.timeout.or_else(|| {
+            let tagged = self.render.tag_timeouts.iter().find(|(tag, _)| self.tags.contains(tag));
+            tagged.map(|&(_, timeout)| timeout)
+        })
+    }
+
+    ///Gives up on the unit when it is still running after `timeout`, retries included.
+    ///
+    /// A timed out unit reports [`ExecutionStatus::TimedOut`] and runs `on_failure`.
+    /// Its worker thread cannot be killed: its cancellation token is triggered
+    /// so a cooperative closure can stop, see [`cancel_grace`](Self::cancel_grace).
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionUnit, HflowError};
+    /// # use std::time::Duration;
+    /// let mut unit = ExecutionUnit::new("Wait for remote service".to_string())
+    ///     .timeout(Duration::from_millis(200))
+    ///     .on_execute(|status| while !status.is_cancelled() {});
+    ///
+    /// assert!(matches!(unit.execute(), Err(HflowError::TimedOut { .. })));
+    /// ```
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Waits `delay` once the turn of the unit comes before running it, e.g.
+    /// to let a restarted service warm up. The line counts down with
+    /// `starting in 27s…` meanwhile.
+    ///
+    /// A unit cancelled while waiting reports [`ExecutionStatus::Cancelled`]
+    /// without running its closure. The wait is left out of the duration of
+    /// the unit and of its [`timeout`](Self::timeout), the final line shows
+    /// it apart, as in `✔ — 1.2s (waited 30s)`.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use std::time::{Duration, Instant};
+    /// # use hflow::{ExecutionStatus, ExecutionUnit};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let buffer = Buffer::default();
+    /// let wait = Duration::from_millis(500);
+    /// let mut check = ExecutionUnit::new("Health check".to_string())
+    ///     .writer(buffer.clone())
+    ///     .start_after(wait)
+    ///     .on_try_execute(|_status| Ok::<(), String>(()));
+    ///
+    /// let started = Instant::now();
+    /// assert_eq!(check.execute(), Ok(ExecutionStatus::Completed));
+    /// // The whole wait went by, none of it counted as running the unit.
+    /// assert!(started.elapsed() >= wait + check.elapsed().unwrap());
+    ///
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// let line = output.lines().last().unwrap();
+    /// assert!(line.starts_with("Health check [ok] — "), "{:?}", line);
+    /// assert!(line.contains(" (waited "), "{:?}", line);
+    /// ```
+    pub fn start_after(mut self, delay: Duration) -> Self {
+        self.start_after = Some(delay);
+        self
+    }
+
+    ///How long the line shows `cancelling…` once the unit is cancelled or
+    ///timed out, waiting for the closure to notice
+    ///[`is_cancelled`](StatusHandle::is_cancelled) and return; 1 second by
+    ///default. A closure still running after that is left to finish on its
+    ///own, its worker thread detached.
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionStatus, ExecutionUnit};
+    /// # use std::sync::{mpsc, Arc};
+    /// # use std::sync::atomic::{AtomicBool, Ordering};
+    /// # use std::time::Duration;
+    /// let noticed = Arc::new(AtomicBool::new(false));
+    /// let seen = noticed.clone();
+    /// let (started, running) = mpsc::channel();
+    /// let mut unit = ExecutionUnit::new("Upload".to_string())
+    ///     .cancel_grace(Duration::from_secs(5))
+    ///     .on_execute(move |status| {
+    ///         let _ = started.send(());
+    ///         while !status.is_cancelled() {
+    ///             std::thread::sleep(Duration::from_millis(5));
+    ///         }
+    ///         // Cleaning up takes a while, well within the grace period.
+    ///         std::thread::sleep(Duration::from_millis(50));
+    ///         seen.store(true, Ordering::SeqCst);
+    ///     });
+    ///
+    /// let token = unit.cancellation_token();
+    /// std::thread::spawn(move || {
+    ///     running.recv().unwrap();
+    ///     token.cancel();
+    /// });
+    /// assert_eq!(unit.execute(), Ok(ExecutionStatus::Cancelled));
+    /// assert!(noticed.load(Ordering::SeqCst));
+    /// ```
+    pub fn cancel_grace(mut self, grace: Duration) -> Self {
+        self.cancel_grace = grace;
+        self
+    }
+
+    /// Warns when the unit runs for `window` without setting a message or
+    /// progress, as a stuck unit looks the same as a slow one otherwise: its
+    /// live line ends with a yellow `(no progress for 45s)` and
+    /// [`on_stall`](Self::on_stall) is invoked. The next message or progress
+    /// clears the warning and starts the window again. Overrides
+    /// [`ProgressManager::stall_warning`].
+    ///
+    /// ```rust
+    /// # use std::sync::{Arc, Mutex};
+    /// # use std::time::Duration;
+    /// # use hflow::ExecutionUnit;
+    /// let stalls = Arc::new(Mutex::new(0));
+    /// let count = stalls.clone();
+    /// let mut unit = ExecutionUnit::new("Sync mirror".to_string())
+    ///     .stall_warning(Duration::from_millis(100))
+    ///     .on_stall(move |_idle| *count.lock().unwrap() += 1)
+    ///     .on_try_execute(|status| {
+    ///         std::thread::sleep(Duration::from_millis(300));
+    ///         status.set_message("resumed");
+    ///         std::thread::sleep(Duration::from_millis(50));
+    ///         Ok::<(), String>(())
+    ///     });
+    ///
+    /// unit.execute().unwrap();
+    /// assert_eq!(*stalls.lock().unwrap(), 1);
+    /// ```
+    pub fn stall_warning(mut self, window: Duration) -> Self {
+        self.stall_warning = Some(window);
+        self
+    }
+
+    /// Invoked once every time the unit stalls, with how long it has shown
+    /// no progress, see [`stall_warning`](Self::stall_warning). It runs on
+    /// the thread drawing the line of the unit.
+    pub fn on_stall<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(Duration) + Send + 'static,
+    {
+        self.on_stall = Some(Box::new(callback));
+        self
+    }
+
+    ///Names the unit, for other units of its group to [`depend on`](Self::depends_on).
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    ///Only runs the unit once the units of its group with these ids succeeded.
+    ///
+    ///The group runs its units in an order where each one comes after its
+    ///dependencies, otherwise keeping the order they were added in, and
+    ///[`TaskGroup::parallel`] runs the ones that do not depend on each other
+    ///at the same time. A unit whose dependency did not complete is skipped,
+    ///with `dependency failed` and the description of the dependency as its
+    ///reason. An unknown id or a dependency cycle fails the run before any
+    ///unit starts.
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionUnit, HflowError, TaskGroup};
+    /// # use std::sync::{Arc, Mutex};
+    /// let ran = Arc::new(Mutex::new(Vec::new()));
+    /// let unit = |description: &str, id: &str| {
+    ///     let ran = ran.clone();
+    ///     let id = id.to_string();
+    ///     ExecutionUnit::new(description.to_string()).id(id.clone()).on_try_execute(move |_status| {
+    ///         ran.lock().unwrap().push(id.clone());
+    ///         Ok::<(), String>(())
+    ///     })
+    /// };
+    ///
+    /// let mut database = TaskGroup::new();
+    /// database.add_unit(unit("Run migrations", "migrate").depends_on(["create-db", "seed"]));
+    /// database.add_unit(unit("Seed data", "seed").depends_on(["create-db"]));
+    /// database.add_unit(unit("Create database", "create-db"));
+    /// database.run(1, 1).unwrap();
+    /// assert_eq!(*ran.lock().unwrap(), ["create-db", "seed", "migrate"]);
+    ///
+    /// let mut cyclic = TaskGroup::new();
+    /// cyclic.add_unit(unit("Build", "build").depends_on(["test"]));
+    /// cyclic.add_unit(unit("Test", "test").depends_on(["build"]));
+    /// let err = cyclic.run(1, 1).unwrap_err();
+    /// assert_eq!(err, HflowError::DependencyCycle { cycle: vec!["build".into(), "test".into(), "build".into()] });
+    /// assert_eq!(err.to_string(), "dependency cycle: build -> test -> build");
+    /// ```
+    ///
+    ///Units that depend on a failed unit are skipped, the others still run:
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionStatus, ExecutionUnit, ExitPolicy, TaskGroup};
+    /// let mut deploy = TaskGroup::new();
+    /// deploy.parallel(2);
+    /// deploy.exit_policy(ExitPolicy::ContinueAll);
+    /// deploy.add_unit(ExecutionUnit::new("Build image".to_string()).id("image").on_try_execute(|_status| Err("disk full")));
+    /// deploy.add_unit(ExecutionUnit::new("Push image".to_string()).id("push").depends_on(["image"]).on_execute(|_status| unreachable!()));
+    /// deploy.add_unit(ExecutionUnit::new("Build docs".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    /// assert!(deploy.run(1, 1).is_err());
+    ///
+    /// let units = deploy.units();
+    /// assert_eq!(units[1].status(), ExecutionStatus::Skipped);
+    /// assert_eq!(units[1].failure_reason().as_deref(), Some("dependency failed: Build image"));
+    /// assert!(units[2].is_completed());
+    /// ```
+    pub fn depends_on<I>(mut self, ids: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.depends_on.extend(ids.into_iter().map(Into::into));
+        self
+    }
+
+    ///Only runs the unit when the predicate returns true, otherwise it is marked as skipped.
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionStatus, ExecutionUnit};
+    /// let mut unit = ExecutionUnit::new("Apply migrations".to_string())
+    ///     .run_if(|| false)
+    ///     .on_execute(|_status| unreachable!());
+    ///
+    /// assert_eq!(unit.execute(), Ok(ExecutionStatus::Skipped));
+    /// ```
+    pub fn run_if<F>(mut self, predicate: F) -> Self
+    where
+        F : Fn() -> bool + Send + 'static,
+    {
+        self.run_if = Some(Box::new(predicate));
+        self
+    }
+
+    /// Reason recorded for the failure of the unit, available after execution.
+    ///
+    /// ```rust
+    /// # use hflow::ExecutionUnit;
+    /// let mut unit = ExecutionUnit::new("Upload artifacts".to_string())
+    ///     .on_try_execute(|_status| Err("bucket not found"));
+    ///
+    /// assert!(unit.execute().is_err());
+    /// assert_eq!(unit.failure_reason().as_deref(), Some("bucket not found"));
+    /// ```
+    pub fn failure_reason(&self) -> Option<String> {
+        self.status.failure().reason
+    }
+
+    /// Takes the value returned by the closure set with
+    /// [`on_execute_returning`](Self::on_execute_returning).
+    ///
+    /// `None` unless the unit completed, when the value was already taken,
+    /// or when it is not a `T`.
+    pub fn take_output<T: 'static>(&mut self) -> Option<T> {
+        if self.status() != ExecutionStatus::Completed {
+            return None;
+        }
+        self.take_returned()
+    }
+
+    /// Like [`take_output`](Self::take_output), whatever the status of the unit.
+    fn take_returned<T: 'static>(&mut self) -> Option<T> {
+        let mut output = self.output.lock().unwrap_or_else(PoisonError::into_inner);
+        match output.take()?.downcast::<T>() {
+            Ok(value) => Some(*value),
+            Err(other) => {
+                *output = Some(other);
+                None
+            }
+        }
+    }
+
+    /// Why the unit did not complete: the failure reason, or how long it
+    /// was given before it timed out.
+    fn finish_reason(&self) -> Option<String> {
+        match self.status() {
+            ExecutionStatus::TimedOut => {
+                Some(format!("timed out after {}", format_duration(self.effective_timeout().unwrap_or_default())))
+            }
+            ExecutionStatus::Failed | ExecutionStatus::Skipped => self.failure_reason(),
+            _ => None,
+        }
+    }
+
+    /// Current status of the unit, taking the status lock for the read.
+    pub fn status(&self) -> ExecutionStatus {
+        self.status.get()
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn is_completed(&self) -> bool {
+        self.status() == ExecutionStatus::Completed
+    }
+
+    pub fn is_failed(&self) -> bool {
+        self.status() == ExecutionStatus::Failed
+    }
+
+    /// How long the last run took, from the start of
+    /// [`execute`](Self::execute) until the unit reached its final status.
+    ///
+    /// `None` if the unit has not run or was skipped.
+    ///
+    /// ```rust
+    /// # use std::time::Duration;
+    /// # use hflow::{ExecutionStatus, ExecutionUnit};
+    /// let mut build = ExecutionUnit::new("Build image".to_string()).on_execute(|status| {
+    ///     std::thread::sleep(Duration::from_millis(50));
+    ///     status.complete();
+    /// });
+    /// assert_eq!(build.elapsed(), None);
+    ///
+    /// build.execute().unwrap();
+    /// assert!(build.elapsed().unwrap() >= Duration::from_millis(50));
+    /// ```
+    pub fn elapsed(&self) -> Option<Duration> {
+        self.elapsed
+    }
+
+    pub fn is_allowed_to_fail(&self) -> bool {
+        self.allowed_to_fail
+    }
+
+    /// Read-only view of the unit that can be moved to another thread and
+    /// queried while the unit runs.
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionStatus, ExecutionUnit};
+    /// let mut unit = ExecutionUnit::new("Compile".to_string())
+    ///     .on_try_execute(|_status| Ok::<(), String>(()));
+    /// let view = unit.view();
+    /// assert_eq!(view.status(), ExecutionStatus::Pending);
+    ///
+    /// let watcher = std::thread::spawn(move || {
+    ///     while !view.status().is_finished() {}
+    ///     view.is_completed()
+    /// });
+    /// unit.execute().unwrap();
+    /// assert!(watcher.join().unwrap());
+    /// ```
+    pub fn view(&self) -> UnitView {
+        UnitView {
+            status: self.status.clone(),
+            description: self.description.clone(),
+        }
+    }
+
+    /// Handle sharing the state of this unit, as passed to its callbacks.
+    fn status_handle(&self) -> StatusHandle {
+        StatusHandle {
+            status: self.status.clone(),
+            cancellation: self.cancellation.clone(),
+            pause: self.pause.clone(),
+            output: ProgressHandle::new(self.render.renderer.clone(), self.render.format),
+            context: self.render.context.clone(),
+            queue: self.render.queue.clone(),
+            #[cfg(feature = "async")]
+            driver: self.render.driver.clone(),
+        }
+    }
+
+    /// Handles the visual feedback (spinner and status) in the terminal.
+    fn display_progress(&mut self, worker: &JoinHandle<()>) {
+        let style = self.spinner.clone().unwrap_or_else(|| self.render.spinner.clone());
+        let tick = style.interval.min(MAX_POLL_INTERVAL);
+        let max_attempts = self.retries + 1;
+        let started = self.started_at.unwrap_or_else(Instant::now);
+        if self.live_line.is_none() && self.render.is_live() {
+            self.live_line = Some(self.render.renderer.add(self.running_line(style.frame(Duration::ZERO))));
+        }
+        let mut reported = (None, None);
+        let suspended = Cell::new(false);
+        // When the unit was cancelled, while waiting for the closure to return.
+        let mut cancelling: Option<Instant> = None;
+        let mut grace = self.cancel_grace;
+        self.pause.set_active(true);
+        loop {
+            // Read first: once the worker is done, the status it left is final.
+            let worker_done = worker.is_finished();
+            let running = matches!(self.status(), ExecutionStatus::InProgress | ExecutionStatus::Retrying);
+            if running
+                && cancelling.is_none()
+                && (self.effective_timeout().is_some_and(|timeout| started.elapsed() >= timeout) || self.render.past_deadline())
+            {
+                self.retry_state.lock().unwrap().timed_out = true;
+                self.cancellation.cancel();
+            }
+            // A closure that already returned may still be ended as cancelled by its worker.
+            if cancelling.is_none() && (running || !worker_done) && self.cancellation.is_cancelled() {
+                cancelling = Some(Instant::now());
+                if self.render.shutdown.as_ref().is_some_and(ShutdownHandle::is_requested) {
+                    grace = self.render.shutdown_grace.unwrap_or(grace);
+                }
+            }
+            let retry = *self.retry_state.lock().unwrap();
+            // Gives up on a closure that ignores the cancellation, leaving it to run on its own.
+            let given_up = cancelling.is_some_and(|since| since.elapsed() >= grace);
+            let observed = self.status.transition(|status| {
+                let cancelled = if retry.timed_out { ExecutionStatus::TimedOut } else { ExecutionStatus::Cancelled };
+                let ended = matches!(status, ExecutionStatus::Cancelled | ExecutionStatus::TimedOut);
+                (given_up && !ended).then_some(cancelled)
+            });
+            // A worker that died outside the closure left a status that will never change.
+            let observed = if worker_done && !observed.is_finished() {
+                self.status.update_failure(|failure| {
+                    failure.reason.get_or_insert_with(|| POISONED_REASON.to_string());
+                });
+                self.status.transition(|status| (!status.is_finished()).then_some(ExecutionStatus::Failed))
+            } else {
+                observed
+            };
+
+            let current_status = match observed {
+                // A failed attempt that will be retried keeps the spinner going.
+                ExecutionStatus::Failed if retry.attempt < max_attempts && cancelling.is_none() && !worker_done => {
+                    ExecutionStatus::InProgress
+                }
+                // Whatever the closure set, the worker ends a cancelled unit as cancelled.
+                ExecutionStatus::Cancelled | ExecutionStatus::TimedOut => observed,
+                // The worker may still end the unit otherwise, once cancelled or after the `after_each` hook.
+                _ if (cancelling.is_some() || self.after_each.is_some()) && !worker_done => ExecutionStatus::InProgress,
+                other => other,
+            };
+
+            let paused = !current_status.is_finished()
+                && self.pause.frame(
+                    || suspended.set(self.display_paused()),
+                    || {
+                        if suspended.replace(false) {
+                            self.render.renderer.resume();
+                        }
+                    },
+                );
+            if paused {
+                thread::sleep(tick);
+                continue;
+            }
+
+            if !self.render.is_human() {
+                self.emit_updates(&mut reported);
+            }
+
+            if current_status.is_finished() {
+                self.elapsed = Some(started.elapsed());
+            }
+            self.check_stall(started, current_status == ExecutionStatus::InProgress && cancelling.is_none());
+
+            match current_status {
+                ExecutionStatus::Pending => {
+                    if let Some(id) = self.live_line {
+                        self.render.renderer.update(id, self.live_pending_line());
+                    }
+                }
+                ExecutionStatus::InProgress | ExecutionStatus::Retrying if cancelling.is_some() => {
+                    self.draw_running(&format!("cancelling{} {}", self.render.ellipsis(), style.frame(started.elapsed())));
+                }
+                ExecutionStatus::InProgress => match self.status.progress().filter(Progress::is_determinate) {
+                    Some(progress) => self.draw_running(&self.bar(&progress)),
+                    None => self.draw_running(style.frame(started.elapsed())),
+                },
+                ExecutionStatus::Completed => {
+                    self.draw_status(&self.theme().success, "[ok]");
+                    break;
+                }
+                ExecutionStatus::Failed => {
+                    let allowed = if self.allowed_to_fail { " (allowed to fail)" } else { "" };
+                    let failure = &self.theme().failure;
+                    let marker = format!("{}{}", self.render.marker(failure, "[fail]"), allowed);
+                    let mut output = self.final_line(&marker, true, failure.style);
+                    output.push_str(&self.failure_details());
+                    self.retire(&output);
+                    break;
+                }
+                ExecutionStatus::Warning => {
+                    self.draw_status(&self.theme().warning, "[warn]");
+                    break;
+                }
+                ExecutionStatus::Skipped => {
+                    self.display_skipped();
+                    break;
+                }
+                ExecutionStatus::Retrying => {
+                    let remaining = retry
+                        .resume_at
+                        .map(|at| at.saturating_duration_since(Instant::now()))
+                        .unwrap_or_default();
+                    let output = format!(
+                        "retrying in {}s (attempt {}/{}) {}",
+                        remaining.as_secs_f64().ceil() as u64,
+                        retry.attempt + 1,
+                        max_attempts,
+                        style.frame(started.elapsed())
+                    );
+                    self.draw_running(&output);
+                }
+                ExecutionStatus::TimedOut => {
+                    let timed_out = &self.theme().timed_out;
+                    let symbol = self.render.marker(timed_out, "[timeout]");
+                    let marker = match self.effective_timeout().filter(|&timeout| started.elapsed() >= timeout) {
+                        Some(timeout) => format!("{} after {}", symbol, format_duration(timeout)),
+                        None => format!("{} (run time limit)", symbol),
+                    };
+                    self.draw_final(&marker, false, timed_out.style);
+                    break;
+                }
+                ExecutionStatus::Cancelled => {
+                    let cancelled = &self.theme().cancelled;
+                    let marker = self.render.marker(cancelled, "[cancelled]");
+                    match self.render.cancel_reason.as_ref().and_then(|reason| reason.get()) {
+                        Some(reason) => self.draw_final(&format!("{} ({})", marker, reason), true, cancelled.style),
+                        None => self.draw_final(marker, true, cancelled.style),
+                    }
+                    break;
+                }
+            }
+            // Woken up early by a status change, so the final line is drawn at once.
+            // A closure that returned leaves no change to wait for once its worker ends.
+            let wait = if observed.is_finished() && !worker_done { CANCEL_CHECK_INTERVAL } else { tick };
+            self.status.wait_change(observed, wait);
+        }
+        if suspended.get() {
+            self.render.renderer.resume();
+        }
+        self.pause.set_active(false);
+        self.live_line = None;
+        self.report_finished();
+    }
+
+    /// Event about this unit, which is identified by its position in the run.
+    fn event(&self, name: &str) -> Event {
+        Event::new(name)
+            .value("group", self.render.group)
+            .optional_text("group_name", self.render.group_name.as_deref())
+            .value("unit", self.render.unit)
+            .text("description", &self.description)
+            .list("tags", &self.tags)
+    }
+
+    /// Reports the message and progress set by the closure since they were
+    /// last `reported`.
+    fn emit_updates(&self, reported: &mut (Option<String>, Option<(u64, u64)>)) {
+        let message = self.status.message();
+        if message.is_some() && message != reported.0 {
+            self.render.emit(self.event("unit_message").text("message", message.as_deref().unwrap_or_default()));
+        }
+        reported.0 = message;
+
+        let progress = self.status.progress().map(|progress| (progress.current, progress.total));
+        if let Some((current, total)) = progress.filter(|_| progress != reported.1) {
+            self.render.emit(self.event("unit_progress").value("current", current).value("total", total));
+        }
+        reported.1 = progress;
+    }
+
+    /// Reports the final status of the unit as an event, or a GitHub
+    /// Actions annotation for a failure.
+    fn report_finished(&self) {
+        let status = self.status();
+        if self.render.is_human() {
+            if matches!(status, ExecutionStatus::Failed | ExecutionStatus::TimedOut) {
+                let message = match self.finish_reason() {
+                    Some(reason) => format!("{}: {}", self.description, reason),
+                    None => self.description.to_string(),
+                };
+                let level = if self.allowed_to_fail { "warning" } else { "error" };
+                self.render.workflow_command(level, &message);
+                if self.render.bell_on_failure && !self.allowed_to_fail {
+                    self.render.bell();
+                }
+            }
+            return;
+        }
+        let mut event = self
+            .event("unit_finished")
+            .text("status", status.as_str())
+            .value("elapsed_ms", self.elapsed.unwrap_or_default().as_millis());
+        if matches!(status, ExecutionStatus::Failed | ExecutionStatus::TimedOut) {
+            if let Some(reason) = self.failure_reason() {
+                event = event.text("reason", &reason);
+            }
+            if self.allowed_to_fail {
+                event = event.value("allowed_to_fail", true);
+            }
+        }
+        self.render.emit(event);
+    }
+
+    /// `[idx/total] description` followed by `suffix`.
+    fn line(&self, suffix: &str) -> String {
+        self.line_with(&self.shown_description(self.keep_message), suffix)
+    }
+
+    /// The description, followed by the last message when `with_message` is set.
+    fn shown_description(&self, with_message: bool) -> String {
+        match self.status.message().filter(|_| with_message) {
+            Some(message) => format!("{}{}{}", self.description, self.render.separator(), message),
+            None => self.description.to_string(),
+        }
+    }
+
+    fn line_with(&self, description: &str, suffix: &str) -> String {
+        match self.counter() {
+            Some(counter) => format!("{}{} {} {}", self.render.indent, counter, description, suffix),
+            None => format!("{}{} {}", self.render.indent, description, suffix),
+        }
+    }
+
+    /// The `[1/3]` counter, unless the position of the unit was never set.
+    fn counter(&self) -> Option<String> {
+        let (idx, total) = self.counter.index.zip(self.total())?;
+        Some(format!("[{}/{}]", idx, total))
+    }
+
+    /// Total shown in the counter, which grows as units are pushed to a named group.
+    fn total(&self) -> Option<u32> {
+        match &self.render.unit_total {
+            Some(total) => u32::try_from(total.load(Ordering::SeqCst)).ok(),
+            None => self.counter.total,
+        }
+    }
+
+    /// Like [`line`](Self::line), with the description shortened so the
+    /// line fits on a single row of the terminal. Only used for live lines,
+    /// final lines keep the full description.
+    fn fitted_line(&self, suffix: &str) -> String {
+        // The last column is left free so the cursor never wraps.
+        let width = self.render.renderer.width().saturating_sub(1);
+        let description = self.shown_description(true);
+        let line = self.line_with(&description, suffix);
+        let overflow = term::width(&line).saturating_sub(width);
+        if overflow == 0 {
+            return line;
+        }
+        let description_len = term::width(&description);
+        if overflow < description_len {
+            self.line_with(&term::truncate(&description, description_len - overflow, self.render.ellipsis()), suffix)
+        } else {
+            term::truncate(&line, width, self.render.ellipsis())
+        }
+    }
+
+    /// Appends the time the unit took, frozen once it finished.
+    fn with_elapsed(&self, marker: &str) -> String {
+        match self.elapsed {
+            Some(elapsed) => format!("{}{}{}", marker, self.render.separator(), self.duration(elapsed)),
+            None => marker.to_string(),
+        }
+    }
+
+    /// The time the unit took, followed by the time it waited to start, if it did.
+    fn duration(&self, elapsed: Duration) -> String {
+        match self.waited.filter(|waited| !waited.is_zero()) {
+            Some(waited) => format!("{} (waited {})", format_duration(elapsed), format_duration(waited)),
+            None => format_duration(elapsed),
+        }
+    }
+
+    /// Line of a running unit, followed by the time it has been running.
+    fn running_line(&self, suffix: &str) -> String {
+        let Some(idle) = self.stalled else {
+            return self.plain_running_line(suffix, "");
+        };
+        // The warning stands out in its own color, unless it was cut off.
+        let note = self.stall_note(idle);
+        let line = self.plain_running_line(suffix, &note);
+        let painted_note = self.render.paint(&note, self.theme().warning.style);
+        let (body, reset) = match line.strip_suffix("\x1b[0m") {
+            Some(body) => (body, "\x1b[0m"),
+            None => (line.as_str(), ""),
+        };
+        match body.strip_suffix(&note) {
+            Some(rest) => format!("{}{}{}", rest, reset, painted_note),
+            None => line.clone(),
+        }
+    }
+
+    /// The line of a running unit, followed by `note`.
+    fn plain_running_line(&self, suffix: &str, note: &str) -> String {
+        let note = if note.is_empty() { String::new() } else { format!(" {}", note) };
+        if let Some(template) = &self.render.templates.running {
+            let line = self.fit(&format!("{}{}", self.templated(template, suffix, ""), note));
+            return self.render.paint(&line, self.theme().in_progress);
+        }
+        let running_for = self.started_at.map(|at| at.elapsed()).unwrap_or_default();
+        let mut suffix = format!("{}{}{}", suffix, self.render.separator(), format_duration(running_for));
+        if let Some(deadline) = self.render.deadline {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left < REMAINING_TIME_THRESHOLD {
+                suffix.push_str(&format!(" ({} left)", format_duration(left)));
+            }
+        }
+        suffix.push_str(&note);
+        self.paint_line(&self.fitted_line(&suffix), self.theme().in_progress)
+    }
+
+    /// Tracks how long the running unit has shown no progress, warning once
+    /// it goes past its [`stall_warning`](Self::stall_warning).
+    fn check_stall(&mut self, started: Instant, running: bool) {
+        let Some(window) = self.stall_warning.or(self.render.stall_warning) else {
+            return;
+        };
+        let since = self.status.updated().filter(|&at| at >= started).unwrap_or(started);
+        let idle = since.elapsed();
+        if !running || idle < window {
+            self.stalled = None;
+            return;
+        }
+        if self.stalled.is_none() {
+            if let Some(callback) = &mut self.on_stall {
+                callback(idle);
+            }
+            if self.render.announces_start() {
+                let line = format!("{} {}", self.line("").trim_end(), self.stall_note(idle));
+                self.render.renderer.println(&self.render.stamp(&self.render.paint(&line, self.theme().warning.style)));
+            }
+            self.render.emit(self.event("unit_stalled").value("idle_ms", idle.as_millis()));
+        }
+        self.stalled = Some(idle);
+    }
+
+    fn stall_note(&self, idle: Duration) -> String {
+        format!("(no progress for {}s)", idle.as_secs())
+    }
+
+    /// The progress bar of a running unit, as wide as its style asks for.
+    fn bar(&self, progress: &Progress) -> String {
+        let style = self.progress_bar.as_ref().unwrap_or(&self.render.progress_bar);
+        let cells = match style.width {
+            BarWidth::Fixed(cells) => cells,
+            BarWidth::Remaining => {
+                // Measure the line around an empty bar, the bar takes what is left of the row.
+                let empty = progress.bar(style, 0);
+                let line = match &self.render.templates.running {
+                    Some(template) => self.templated(template, &empty, ""),
+                    None => {
+                        let running_for = self.started_at.map(|at| at.elapsed()).unwrap_or_default();
+                        self.line(&format!("{}{}{}", empty, self.render.separator(), format_duration(running_for)))
+                    }
+                };
+                // The last column is left free so the cursor never wraps.
+                let room = self.render.renderer.width().saturating_sub(1);
+                room.saturating_sub(term::width(&line)).max(MIN_BAR_WIDTH)
+            }
+        };
+        progress.bar(style, cells)
+    }
+
+    /// Updates the live line of the unit; nothing is printed when the output
+    /// is not a terminal.
+    fn draw_running(&self, suffix: &str) {
+        if let Some(id) = self.live_line {
+            self.render.renderer.update(id, self.running_line(suffix));
+        }
+    }
+
+    /// Prints the final line of the unit in place of its live line.
+    fn draw_final(&self, marker: &str, timed: bool, style: TextStyle) {
+        self.retire(&self.final_line(marker, timed, style));
+    }
+
+    /// Final line ending with the marker of a status and the time the unit took.
+    fn draw_status(&self, marker: &Marker, plain: &str) {
+        self.draw_final(self.render.marker(marker, plain), true, marker.style);
+    }
+
+    /// Final line of the unit ending with `marker`, followed by the time the
+    /// unit took when `timed`. A template gets `marker` as its `{status}`.
+    fn final_line(&self, marker: &str, timed: bool, style: TextStyle) -> String {
+        let templates = &self.render.templates;
+        let template = match self.status() {
+            ExecutionStatus::Failed | ExecutionStatus::TimedOut | ExecutionStatus::Cancelled => &templates.failure,
+            _ => &templates.success,
+        };
+        match template.as_ref().or(templates.running.as_ref()) {
+            Some(template) => self.render.paint(&self.templated(template, "", marker), style),
+            None => match (self.elapsed.filter(|_| timed), self.aligned_width()) {
+                (Some(elapsed), Some(width)) => self.paint_line(&self.aligned_line(marker, elapsed, width), style),
+                _ if timed => self.paint_line(&self.line(&self.with_elapsed(marker)), style),
+                _ => self.paint_line(&self.line(marker), style),
+            },
+        }
+    }
+
+    /// Width of the terminal when durations are right-aligned and the width is known.
+    fn aligned_width(&self) -> Option<usize> {
+        if self.render.aligned_durations && self.render.renderer.is_live() {
+            self.render.renderer.known_width()
+        } else {
+            None
+        }
+    }
+
+    /// Line ending with `marker`, followed by a dotted leader and `elapsed`
+    /// against the right edge of a terminal of `width` columns. The
+    /// description is cut when the line would not fit.
+    fn aligned_line(&self, marker: &str, elapsed: Duration, width: usize) -> String {
+        // The last column is left free so the cursor never wraps.
+        let duration = format!(" {}", self.duration(elapsed));
+        let room = width.saturating_sub(1 + term::width(&duration));
+        let description = self.shown_description(self.keep_message);
+        let mut line = self.line_with(&description, marker);
+        let overflow = (term::width(&line) + MIN_LEADER).saturating_sub(room);
+        if overflow > 0 {
+            let description_len = term::width(&description);
+            line = if overflow < description_len {
+                let description = term::truncate(&description, description_len - overflow, self.render.ellipsis());
+                self.line_with(&description, marker)
+            } else {
+                term::truncate(&line, room.saturating_sub(MIN_LEADER), self.render.ellipsis())
+            };
+        }
+        let dots = room.saturating_sub(term::width(&line) + 1);
+        format!("{} {}{}", line, ".".repeat(dots), duration)
+    }
+
+    /// Line of a unit that is not running, pending or paused, ending with
+    /// `marker`; `fitted` to the terminal width for the live block.
+    fn idle_line(&self, marker: &str, style: TextStyle, fitted: bool) -> String {
+        match &self.render.templates.running {
+            Some(template) => {
+                let line = self.templated(template, "", marker);
+                self.render.paint(&if fitted { self.fit(&line) } else { line }, style)
+            }
+            None if fitted => self.paint_line(&self.fitted_line(marker), style),
+            None => self.paint_line(&self.line(marker), style),
+        }
+    }
+
+    /// Line laid out by `template`, with `spinner` and `status` for the
+    /// placeholders of the same name.
+    fn templated(&self, template: &LineTemplate, spinner: &str, status: &str) -> String {
+        let elapsed = self
+            .elapsed
+            .or_else(|| self.started_at.map(|at| at.elapsed()))
+            .map(format_duration)
+            .unwrap_or_default();
+        let percent = self
+            .status
+            .progress()
+            .filter(Progress::is_determinate)
+            .map(|progress| format!("{}%", progress.percent()))
+            .unwrap_or_default();
+        let values = LineValues {
+            description: &self.description,
+            idx: &self.counter.index.map(|idx| idx.to_string()).unwrap_or_default(),
+            total: &self.total().map(|total| total.to_string()).unwrap_or_default(),
+            spinner,
+            status,
+            elapsed: &elapsed,
+            message: &self.status.message().unwrap_or_default(),
+            percent: &percent,
+        };
+        format!("{}{}", self.render.indent, template.render(&values))
+    }
+
+    /// Cuts a live line so it fits on a single row of the terminal.
+    fn fit(&self, line: &str) -> String {
+        term::truncate(line, self.render.renderer.width().saturating_sub(1), self.render.ellipsis())
+    }
+
+    /// Paints a line of the unit, with the counter in its own style when the theme sets one.
+    fn paint_line(&self, line: &str, style: TextStyle) -> String {
+        if let (Some(counter_style), Some(counter)) = (self.theme().counter, self.counter()) {
+            let counter = format!("{}{}", self.render.indent, counter);
+            if let Some(rest) = line.strip_prefix(&counter) {
+                return format!("{}{}", self.render.paint(&counter, counter_style), self.render.paint(rest, style));
+            }
+        }
+        self.render.paint(line, style)
+    }
+
+    /// Removes the live line of the unit and prints `output` for good.
+    fn retire(&self, output: &str) {
+        if !self.render.is_human() {
+            return;
+        }
+        let output = self.render.stamp(output);
+        self.render.renderer.finish(self.live_line, &output);
+        if matches!(self.status(), ExecutionStatus::Failed | ExecutionStatus::TimedOut) {
+            self.render.renderer.keep(&output);
+        }
+    }
+
+    /// The failure reason, if any, on indented dimmed lines to print under the red one.
+    fn failure_details(&self) -> String {
+        let Some(reason) = self.failure_reason() else {
+            return String::new();
+        };
+        let width = self.render.renderer.width().saturating_sub(REASON_INDENT.len());
+        term::wrap(&reason, width)
+            .iter()
+            .map(|line| format!("\n{}{}{}", self.render.indent, REASON_INDENT, self.render.paint(line, TextStyle::Dimmed)))
+            .collect()
+    }
+
+    /// Prints what the unit would do in a dry run, evaluating its
+    /// [`run_if`](Self::run_if) predicate but nothing else.
+    fn display_planned(&self) {
+        let would_run = self.run_if.as_ref().is_none_or(|predicate| predicate());
+        if !self.render.is_human() {
+            return self.render.emit(self.event("unit_planned").value("would_run", would_run));
+        }
+        let planned = if would_run { "(dry-run) would run" } else { "(dry-run) skipped" };
+        let output = self.idle_line(planned, self.theme().pending.style, false);
+        self.render.renderer.println(&self.render.stamp(&output));
+    }
+
+    /// Prints the line announcing the unit when lines are only appended.
+    fn display_started(&self) {
+        let output = self.idle_line("started", self.theme().in_progress, false);
+        self.render.renderer.println(&self.render.stamp(&output));
+    }
+
+    /// Prints a paused marker and hides the live lines until the unit resumes,
+    /// leaving the cursor on a fresh line.
+    ///
+    /// Returns whether the renderer was suspended, which never happens in
+    /// quiet mode or with [`OutputFormat::JsonLines`].
+    fn display_paused(&self) -> bool {
+        if self.render.quiet || !self.render.is_human() {
+            return false;
+        }
+        let paused = &self.theme().paused;
+        let output = self.idle_line(self.render.marker(paused, "[paused]"), paused.style, false);
+        self.render.renderer.suspend(&self.render.stamp(&output));
+        true
+    }
+
+    /// Shows the pending line of the unit in the live block until it runs.
+    pub(crate) fn reserve_line(&mut self) {
+        self.live_line = Some(self.render.renderer.add(self.live_pending_line()));
+    }
+
+    /// Prints the pending line for good when the unit will not run.
+    pub(crate) fn release_line(&mut self) {
+        if let Some(id) = self.live_line.take() {
+            self.render.renderer.finish(Some(id), &self.render.stamp(&self.pending_line()));
+        }
+    }
+
+    /// Dimmed line shown for a unit that has not started yet.
+    fn pending_line(&self) -> String {
+        let pending = &self.theme().pending;
+        self.idle_line(self.render.marker(pending, "[pending]"), pending.style, false)
+    }
+
+    /// [`pending_line`](Self::pending_line) fitted to the terminal width.
+    fn live_pending_line(&self) -> String {
+        let pending = &self.theme().pending;
+        self.idle_line(self.render.marker(pending, "[pending]"), pending.style, true)
+    }
+
+    /// Prints the final line of a unit that did not run.
+    fn display_skipped(&self) {
+        let skipped = &self.theme().skipped;
+        let marker = self.render.marker(skipped, "[skipped]");
+        let mut output = self.final_line(marker, false, skipped.style);
+        output.push_str(&self.failure_details());
+        self.retire(&output);
+    }
+
+    fn display_filtered(&self) {
+        let skipped = &self.theme().skipped;
+        // Only the symbol of the theme, `↷ filtered out` rather than `↷ skipped filtered out`.
+        let symbol = skipped.symbol.split_whitespace().next().unwrap_or_default();
+        let marker = format!("{} filtered out", if self.render.colors { symbol } else { "[skipped]" });
+        self.retire(&self.final_line(&marker, false, TextStyle::Dimmed));
+    }
+
+    /// Whether the unit has one of the tags in `include`, when there is
+    /// any, and none of the tags in `exclude`.
+    fn matches_tags(&self, include: &[&str], exclude: &[&str]) -> bool {
+        let tagged = |tags: &[&str]| self.tags.iter().any(|tag| tags.contains(&tag.as_str()));
+        (include.is_empty() || tagged(include)) && !tagged(exclude)
+    }
+
+    /// Counts down `delay` on the line of the unit before it starts.
+    /// Returns false when the unit was cancelled in the meantime.
+    fn wait_to_start(&mut self, delay: Duration) -> bool {
+        let deadline = Instant::now() + delay;
+        let waiting = |unit: &Self, fitted: bool| {
+            let left = deadline.saturating_duration_since(Instant::now());
+            let marker = format!("starting in {}s{}", left.as_secs_f64().ceil() as u64, unit.render.ellipsis());
+            unit.idle_line(&marker, unit.theme().pending.style, fitted)
+        };
+        self.render.emit(self.event("unit_waiting").value("delay_ms", delay.as_millis()));
+        if self.render.is_live() {
+            match self.live_line {
+                Some(id) => self.render.renderer.update(id, waiting(self, true)),
+                None => self.live_line = Some(self.render.renderer.add(waiting(self, true))),
+            }
+        } else if self.render.announces_start() {
+            self.render.renderer.println(&self.render.stamp(&waiting(self, false)));
+        }
+        let started = Instant::now();
+        while !self.cancellation.is_cancelled() {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                break;
+            }
+            if let Some(id) = self.live_line {
+                self.render.renderer.update(id, waiting(self, true));
+            }
+            thread::sleep(left.min(MAX_POLL_INTERVAL));
+        }
+        self.waited = Some(started.elapsed());
+        !self.cancellation.is_cancelled()
+    }
+
+    /// Waits for the units this one depends on to finish, returning the
+    /// description of the first one that did not succeed. Stops waiting
+    /// once the unit is cancelled.
+    fn wait_for_dependencies(&self) -> Option<String> {
+        for dependency in &self.dependencies {
+            let mut status = dependency.status();
+            while !status.is_finished() {
+                if self.cancellation.is_cancelled() {
+                    return None;
+                }
+                dependency.status.wait_change(status, MAX_POLL_INTERVAL);
+                status = dependency.status();
+            }
+            if !matches!(status, ExecutionStatus::Completed | ExecutionStatus::Warning) {
+                return Some(dependency.description().to_string());
+            }
+        }
+        None
+    }
+
+    /// Forgets the last execution, so that the units depending on this
+    /// one wait for it again.
+    #[cfg(feature = "watch")]
+    fn reset(&mut self) {
+        self.status.set(ExecutionStatus::Pending);
+        self.status.update_failure(|failure| *failure = Failure::default());
+        self.status.set_message(None);
+        self.status.update_progress(|progress| *progress = None);
+        self.started_at = None;
+        self.elapsed = None;
+        self.waited = None;
+    }
+
+    /// Takes the units found by an expanding unit, only kept when the unit succeeded.
+    fn take_found(&self) -> Vec<ExecutionUnit> {
+        let Some(found) = &self.found else {
+            return Vec::new();
+        };
+        let mut found = std::mem::take(&mut *found.lock().unwrap_or_else(PoisonError::into_inner));
+        if !matches!(self.status(), ExecutionStatus::Completed | ExecutionStatus::Warning) {
+            if let Some(queue) = &self.render.queue {
+                queue.total.fetch_sub(found.len(), Ordering::SeqCst);
+            }
+            found.clear();
+        }
+        for unit in &mut found {
+            unit.added = true;
+        }
+        found
+    }
+
+    /// Whether every unit this one depends on has finished.
+    fn is_ready(&self) -> bool {
+        self.dependencies.iter().all(|dependency| dependency.status().is_finished())
+    }
+
+    /// Settles a unit left behind when its group stopped early: skipped
+    /// when a unit it depends on did not succeed or for `reason` when there
+    /// is one, printed as pending otherwise.
+    pub(crate) fn stop(&mut self, reason: Option<&str>) {
+        let blocked = self.dependencies.iter().any(|dependency| {
+            let status = dependency.status();
+            status.is_finished() && !matches!(status, ExecutionStatus::Completed | ExecutionStatus::Warning)
+        });
+        // Units left over once the run timed out are skipped as well.
+        if blocked || self.render.past_deadline() {
+            // Skipping never fails.
+            let _ = self.execute();
+        } else if let Some(reason) = reason {
+            let reason = reason.to_string();
+            self.status.update_failure(|failure| *failure = Failure { reason: Some(reason), ..Failure::default() });
+            self.skip();
+        } else {
+            self.release_line();
+        }
+    }
+
+    /// Ends the unit as skipped without running it, keeping the reason already set.
+    fn skip(&mut self) {
+        self.status.set(ExecutionStatus::Skipped);
+        self.display_skipped();
+        self.live_line = None;
+        self.report_finished();
+        self.finish();
+    }
+
+    /// Fails with [`HflowError::MissingCallback`] when the unit has no closure.
+    fn check_callback(&self) -> Result<(), HflowError> {
+        match self.execute {
+            Some(_) => Ok(()),
+            None => Err(HflowError::MissingCallback { description: self.description.to_string() }),
+        }
+    }
+
+    /// Invokes the [`on_finish`](Self::on_finish) callback with the final status.
+    fn finish(&mut self) {
+        if let Some(callback) = self.on_finish.take() {
+            let status = self.status();
+            run_finish_callback(&self.render, "on_finish", || callback(status));
+        }
+    }
+
+    /// Runs the unit on a worker thread and displays its progress until it
+    /// reaches a final status.
+    ///
+    /// `on_success` or `on_failure` are invoked exactly once, on the calling
+    /// thread, right after the final line has been printed.
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionUnit, HflowError};
+    /// # fn migrate_db() -> Result<(), String> { Err("table already exists".to_string()) }
+    /// # fn rollback_migration() {}
+    /// let mut task = ExecutionUnit::new("Migrate DB".to_string())
+    ///     .on_try_execute(|_status| migrate_db())
+    ///     .on_failure(|err: Option<&String>, _status| {
+    ///         println!("Rollback executed after: {}", err.unwrap());
+    ///         rollback_migration();
+    ///     });
+    ///
+    /// assert!(task.execute().is_err());
+    ///
+    /// let mut broken = ExecutionUnit::new("Read manifest".to_string())
+    ///     .on_execute(|_status| panic!("manifest.toml not found"));
+    ///
+    /// assert!(matches!(broken.execute(), Err(HflowError::UnitFailed { .. })));
+    /// ```
+    ///
+    /// Units whose [`run_if`](Self::run_if) predicate returns false, or whose
+    /// cancellation token was triggered before they started, are not executed
+    /// at all and report [`ExecutionStatus::Skipped`].
+    ///
+    /// If the token is cancelled while the unit runs, the line shows
+    /// `cancelling…` until the closure returns and the unit reports
+    /// [`ExecutionStatus::Cancelled`], whatever the closure set, unless it
+    /// failed without having seen the cancellation. A closure
+    /// that never checks [`StatusHandle::is_cancelled`] is given up on after
+    /// the [`cancel_grace`](Self::cancel_grace), its worker thread then left
+    /// to finish on its own instead of being joined.
+    ///
+    /// A panic inside the closure is caught and marks the unit as failed, with
+    /// the panic message as the failure reason.
+    ///
+    /// A failed unit returns [`HflowError::UnitFailed`]; deciding whether the
+    /// process should stop is left to the caller. A unit never given a
+    /// closure returns [`HflowError::MissingCallback`] instead of running,
+    /// unless it is skipped anyway.
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionStatus, ExecutionUnit, HflowError};
+    /// let mut unit = ExecutionUnit::new("Migrate DB".to_string());
+    ///
+    /// let err = unit.execute().unwrap_err();
+    /// assert_eq!(err, HflowError::MissingCallback { description: "Migrate DB".to_string() });
+    /// assert_eq!(unit.status(), ExecutionStatus::Pending);
+    /// ```
+    ///
+    /// A closure that panics fails its unit, and the run, without unwinding
+    /// into the caller:
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ColorMode, ExecutionStatus, ExecutionUnit, ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(buffer.clone());
+    /// manager.color(ColorMode::Never);
+    /// let migrate = ExecutionUnit::new("Migrate DB".to_string()).on_execute(|_status| panic!("disk full"));
+    /// manager.add_group(TaskGroup::new().with_unit(migrate));
+    ///
+    /// assert!(manager.start().is_err());
+    /// let unit = &manager.groups()[0].units()[0];
+    /// assert_eq!(unit.status(), ExecutionStatus::Failed);
+    /// assert!(unit.failure_reason().unwrap().contains("disk full"));
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// assert!(output.contains("disk full"));
+    /// ```
+    pub fn execute(&mut self) -> Result<ExecutionStatus, HflowError> {
+        if self.filtered {
+            self.display_filtered();
+            self.live_line = None;
+            self.report_finished();
+            self.finish();
+            return Ok(ExecutionStatus::Skipped);
+        }
+        if self.render.dry_run {
+            self.display_planned();
+            return Ok(self.status());
+        }
+        self.started_at = None;
+        self.elapsed = None;
+        self.waited = None;
+        self.output.lock().unwrap_or_else(PoisonError::into_inner).take();
+        self.status.update_failure(|failure| *failure = Failure::default());
+        self.status.set_cancel_seen(false);
+
+        let failed_dependency = self.wait_for_dependencies();
+        if let Some(dependency) = &failed_dependency {
+            let reason = format!("dependency failed: {}", dependency);
+            self.status.update_failure(|failure| failure.reason = Some(reason));
+        }
+        if failed_dependency.is_none() && self.render.past_deadline() {
+            self.status.update_failure(|failure| failure.reason = Some("run time limit reached".to_string()));
+        }
+        let condition_met = failed_dependency.is_none() && self.run_if.as_ref().is_none_or(|predicate| predicate());
+        if !condition_met || self.cancellation.is_cancelled() || self.render.past_deadline() {
+            self.skip();
+            return Ok(ExecutionStatus::Skipped);
+        }
+
+        self.check_callback()?;
+        if let Some(delay) = self.start_after
+            && !self.wait_to_start(delay)
+        {
+            self.status.set(ExecutionStatus::Cancelled);
+            self.draw_status(&self.theme().cancelled, "[cancelled]");
+            self.live_line = None;
+            self.report_finished();
+            self.finish();
+            return Ok(ExecutionStatus::Cancelled);
+        }
+
+        self.started_at = Some(Instant::now());
+        self.status.set(ExecutionStatus::InProgress);
+        if self.render.announces_start() {
+            self.display_started();
+        }
+        self.render.emit(self.event("unit_started"));
+
+        let status = self.status_handle();
+        let Some(slot) = self.execute.clone() else {
+            unreachable!("checked by check_callback");
+        };
+        // Still missing when the worker of a previous execution was detached and has not returned.
+        let action = slot.lock().unwrap_or_else(PoisonError::into_inner).take();
+        *self.retry_state.lock().unwrap() = RetryState::default();
+        let retry_state = self.retry_state.clone();
+        let max_attempts = self.retries + 1;
+        let backoff = self.backoff.clone();
+        let deadline = self.render.deadline;
+        let on_start = self.on_start.take();
+        let (before_each, after_each) = (self.before_each.clone(), self.after_each.clone());
+        let hooked = on_start.is_some() || before_each.is_some() || after_each.is_some();
+        let info = hooked.then(|| UnitInfo {
+            description: self.description.to_string(),
+            unit_id: self.unit_id(),
+            id: self.id.clone(),
+            tags: self.tags.clone(),
+            group: self.render.group,
+            unit: self.render.unit,
+            group_name: self.render.group_name.clone(),
+            started_at: SystemTime::now(),
+        });
+
+        let handle = thread::spawn(move || {
+            let Some(action) = action else {
+                status.set_failure_reason("still running from a previous execution");
+                status.status.set(ExecutionStatus::Failed);
+                return;
+            };
+            if let (Some(callback), Some(info)) = (on_start, &info)
+                && let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| callback(info)))
+            {
+                status.set_failure_reason(format!("on_start {}", panic_message(payload.as_ref())));
+                status.status.set(ExecutionStatus::Failed);
+                *slot.lock().unwrap_or_else(PoisonError::into_inner) = Some(action);
+                return;
+            }
+            if let (Some(hook), Some(info)) = (&before_each, &info)
+                && let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| hook(info)))
+            {
+                status.set_failure_reason(format!("before_each {}", panic_message(payload.as_ref())));
+                status.status.set(ExecutionStatus::Failed);
+                *slot.lock().unwrap_or_else(PoisonError::into_inner) = Some(action);
+                return;
+            }
+            let mut rng = Rng::from_entropy();
+            for attempt in 1..=max_attempts {
+                retry_state.lock().unwrap().attempt = attempt;
+                status.status.update_failure(|failure| *failure = Failure::default());
+                status.clear_progress();
+                status.clear_message();
+                if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| action(status.clone()))) {
+                    status.set_failure_reason(panic_message(payload.as_ref()));
+                    // The closure may have panicked while holding the guard.
+                    *status.status.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = ExecutionStatus::Failed;
+                }
+
+                let failed = status.status.get() == ExecutionStatus::Failed;
+                if !failed || attempt == max_attempts || status.cancellation.is_cancelled() {
+                    break;
+                }
+
+                let delay = match panic::catch_unwind(AssertUnwindSafe(|| backoff.jittered_delay(attempt, &mut rng))) {
+                    Ok(delay) => delay,
+                    Err(payload) => {
+                        status.set_failure_reason(format!("backoff {}", panic_message(payload.as_ref())));
+                        break;
+                    }
+                };
+                retry_state.lock().unwrap().resume_at = Some(Instant::now() + delay);
+                status.status.set(ExecutionStatus::Retrying);
+                wait_or_cancel(delay, &status.cancellation);
+                retry_state.lock().unwrap().resume_at = None;
+
+                if status.cancellation.is_cancelled() {
+                    break;
+                }
+                status.status.set(ExecutionStatus::InProgress);
+            }
+            *slot.lock().unwrap_or_else(PoisonError::into_inner) = Some(action);
+
+
+            if status.cancellation.is_cancelled() {
+                // The run may have timed out before the display loop noticed.
+                let past_deadline = deadline.is_some_and(|deadline| Instant::now() >= deadline);
+                let timed_out = retry_state.lock().unwrap().timed_out || past_deadline;
+                let cancelled = if timed_out { ExecutionStatus::TimedOut } else { ExecutionStatus::Cancelled };
+                // A closure that failed without seeing the cancellation failed on its
+                // own, e.g. at the same time as a sibling whose failure cancelled it.
+                let failed_on_its_own = !timed_out && !status.status.cancel_seen();
+                status.status.transition(|current| match current {
+                    ExecutionStatus::Cancelled | ExecutionStatus::TimedOut => None,
+                    ExecutionStatus::Failed if failed_on_its_own => None,
+                    _ => Some(cancelled),
+                });
+            }
+
+            if let (Some(hook), Some(info)) = (&after_each, &info) {
+                let ended = status.status.get();
+                let reason = match panic::catch_unwind(AssertUnwindSafe(|| hook(info, ended))) {
+                    Ok(Ok(())) => None,
+                    Ok(Err(err)) => Some(format!("after_each: {}", err)),
+                    Err(payload) => Some(format!("after_each {}", panic_message(payload.as_ref()))),
+                };
+                if let Some(reason) = reason
+                    && matches!(ended, ExecutionStatus::Completed | ExecutionStatus::Warning)
+                {
+                    status.set_failure_reason(reason);
+                    status.status.set(ExecutionStatus::Failed);
+                }
+            }
+        });
+
+
+        self.display_progress(&handle);
+
+
+        // Cancelled and timed out workers may never return, they are detached.
+        let displayed = self.status();
+        let final_status = match displayed {
+            ExecutionStatus::Cancelled | ExecutionStatus::TimedOut if !handle.is_finished() => {
+                if let Some(workers) = &self.workers {
+                    workers.lock().unwrap_or_else(PoisonError::into_inner).push(handle);
+                }
+                displayed
+            }
+            _ => {
+                if handle.join().is_err() {
+                    self.status.update_failure(|failure| {
+                        failure.reason.get_or_insert_with(|| POISONED_REASON.to_string());
+                    });
+                    self.status.set(ExecutionStatus::Failed);
+                }
+                self.status()
+            }
+        };
+
+        let result = self.settle(final_status);
+        self.finish();
+        result
+    }
+
+    /// Invokes `on_success` or `on_failure` for `final_status` and turns it
+    /// into the result of [`execute`](Self::execute).
+    fn settle(&mut self, final_status: ExecutionStatus) -> Result<ExecutionStatus, HflowError> {
+        // Callbacks run here, after the final line has been printed, so their
+        // output never lands on the spinner line.
+        let handle = self.status_handle();
+
+        if final_status == ExecutionStatus::Completed
+            && let Some(callback) = self.on_success.take()
+        {
+            callback(handle.clone());
+        }
+
+        let failed = matches!(final_status, ExecutionStatus::Failed | ExecutionStatus::TimedOut);
+        if failed && self.allowed_to_fail {
+            self.run_failure_callbacks(handle);
+            return Ok(final_status);
+        }
+
+        if let Some(err) = self.error() {
+            self.run_failure_callbacks(handle);
+            return Err(err);
+        }
+
+        Ok(final_status)
+    }
+
+    /// Invokes `on_failure` with the error of the closure.
+    fn run_failure_callbacks(&mut self, handle: StatusHandle) {
+        if let Some(callback) = self.on_failure.take() {
+            let error = self.status.failure().error.filter(|_| self.status() != ExecutionStatus::TimedOut);
+            callback(error.as_deref(), handle);
+        }
+    }
+
+    /// Runs the unit on a thread of its own, leaving the calling thread free
+    /// while its line is drawn; see [`execute`](Self::execute) for how it runs.
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionStatus, ExecutionUnit};
+    /// # use std::time::Duration;
+    /// let upload = ExecutionUnit::new("Upload logs".to_string())
+    ///     .on_execute_returning(|status| {
+    ///         while !status.is_cancelled() {
+    ///             std::thread::sleep(Duration::from_millis(10));
+    ///         }
+    ///         42_u64
+    ///     })
+    ///     .spawn();
+    ///
+    /// upload.set_message("3 of 12 files");
+    /// assert!(!upload.wait_timeout(Duration::from_millis(50)));
+    /// assert_eq!(upload.status(), ExecutionStatus::InProgress);
+    ///
+    /// upload.cancel();
+    /// assert_eq!(upload.wait(), Ok(ExecutionStatus::Cancelled));
+    ///
+    /// let (mut report, result) = ExecutionUnit::new("Build report".to_string())
+    ///     .on_execute_returning(|_status| "report.html")
+    ///     .spawn()
+    ///     .join();
+    /// assert_eq!(result, Ok(ExecutionStatus::Completed));
+    /// assert_eq!(report.take_output::<&str>(), Some("report.html"));
+    /// ```
+    pub fn spawn(self) -> UnitHandle {
+        UnitHandle::spawn(self)
+    }
+
+    /// The error of a unit that failed or timed out, whether or not it was
+    /// allowed to.
+    fn error(&self) -> Option<HflowError> {
+        match self.status() {
+            ExecutionStatus::Failed => Some(HflowError::UnitFailed {
+                description: self.description.to_string(),
+                reason: self.failure_reason(),
+                exit_code: self.status.failure().exit_code,
+            }),
+            ExecutionStatus::TimedOut => Some(HflowError::TimedOut {
+                description: self.description.to_string(),
+                timeout: self.effective_timeout().unwrap_or_default(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Cancels `cancellation` once `deadline` passes, unless `finished` is
+/// cancelled first, see [`ProgressManager::total_timeout`].
+fn watch_deadline(deadline: Instant, cancellation: CancellationToken, finished: CancellationToken) -> JoinHandle<()> {
+    thread::spawn(move || {
+        while !finished.is_cancelled() {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                cancellation.cancel();
+                break;
+            }
+            thread::sleep(left.min(MAX_POLL_INTERVAL));
+        }
+    })
+}
+
+/// Runs the `hook` callback, e.g. `on_finish`, printing a panic instead
+/// of letting it through, under the unit or group the callback belongs to.
+fn run_finish_callback(render: &RenderSettings, hook: &str, callback: impl FnOnce()) {
+    let Err(payload) = panic::catch_unwind(AssertUnwindSafe(callback)) else {
+        return;
+    };
+    let message = format!("{} {}", hook, panic_message(payload.as_ref()));
+    if render.is_human() {
+        let failure = &render.theme.failure;
+        let line = format!("{}{} {}", render.indent, render.marker(failure, "[fail]"), message);
+        render.renderer.println(&render.stamp(&render.paint(&line, failure.style)));
+    } else {
+        render.emit(Event::new("callback_panicked").text("callback", hook).text("message", &message));
+    }
+}
+
+/// Formats a duration for humans: `850ms`, `12.4s`, `2m 03s`.
+fn format_duration(duration: Duration) -> String {
+    let millis = duration.as_millis();
+    if millis < 1000 {
+        format!("{}ms", millis)
+    } else if millis < 60_000 {
+        let seconds = format!("{:.1}", duration.as_secs_f64());
+        format!("{}s", seconds.strip_suffix(".0").unwrap_or(&seconds))
+    } else {
+        let seconds = duration.as_secs();
+        format!("{}m {:02}s", seconds / 60, seconds % 60)
+    }
+}
+
+/// Turns the payload of a caught panic into a printable reason.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic payload".to_string());
+    format!("panicked: {}", message)
+}
+
+/// Sleeps for `delay`, waking up early if the token is cancelled.
+fn wait_or_cancel(delay: Duration, token: &CancellationToken) {
+    let deadline = Instant::now() + delay;
+    while !token.is_cancelled() {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        thread::sleep(remaining.min(Duration::from_millis(50)));
+    }
+}
+
+/// A logical group of execution units that will be processed sequentially.
+pub struct TaskGroup {
+    name: Option<String>,
+    units: Vec<ExecutionUnit>,
+    show_pending: bool,
+    cancellation: CancellationToken,
+    observers: Observers,
+    exit_policy: Option<ExitPolicy>,
+    inherited_exit_policy: ExitPolicy,
+    spinner: Option<SpinnerStyle>,
+    max_concurrency: usize,
+    continue_on_error: bool,
+    max_failures: Option<usize>,
+    tag_timeouts: Vec<(String, Duration)>,
+    delay: Option<DelayFn>,
+    before_each: Option<BeforeEach>,
+    after_each: Option<AfterEach>,
+    context: Option<render::Context>,
+    on_success: Option<GroupFinishCallback>,
+    on_failure: Option<GroupFinishCallback>,
+    on_finish: Option<GroupFinishCallback>,
+    queue: Arc<queue::Queue>,
+    render: RenderSettings,
+}
+
+impl Default for TaskGroup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Collects units into an unnamed group, in order.
+///
+/// ```rust
+/// # use hflow::{ExecutionUnit, TaskGroup};
+/// let hosts = ["web-1", "web-2", "web-3"];
+/// let mut group: TaskGroup = hosts
+///     .iter()
+///     .map(|host| ExecutionUnit::new(format!("Deploy to {}", host)).on_try_execute(|_status| Ok::<(), String>(())))
+///     .collect();
+/// group.extend([ExecutionUnit::new("Smoke test".to_string()).on_try_execute(|_status| Ok::<(), String>(()))]);
+///
+/// assert_eq!(group.len(), 4);
+/// group.run(1, 1).unwrap();
+/// assert!(group.units().iter().all(|unit| unit.is_completed()));
+/// ```
+impl FromIterator<ExecutionUnit> for TaskGroup {
+    fn from_iter<I: IntoIterator<Item = ExecutionUnit>>(units: I) -> Self {
+        let mut group = Self::new();
+        group.extend(units);
+        group
+    }
+}
+
+impl Extend<ExecutionUnit> for TaskGroup {
+    fn extend<I: IntoIterator<Item = ExecutionUnit>>(&mut self, units: I) {
+        self.units.extend(units);
+    }
+}
+
+impl TaskGroup {
+    pub fn new() -> Self {
+        Self {
+            name: None,
+            units: Vec::new(),
+            show_pending: false,
+            cancellation: CancellationToken::new(),
+            observers: Observers::default(),
+            exit_policy: None,
+            inherited_exit_policy: ExitPolicy::default(),
+            spinner: None,
+            max_concurrency: 1,
+            continue_on_error: false,
+            max_failures: None,
+            tag_timeouts: Vec::new(),
+            delay: None,
+            before_each: None,
+            after_each: None,
+            context: None,
+            on_success: None,
+            on_failure: None,
+            on_finish: None,
+            queue: Arc::default(),
+            render: RenderSettings::default(),
+        }
+    }
+
+    /// Starts a group built in a chain of calls, see [`TaskGroupBuilder`].
+    pub fn builder() -> TaskGroupBuilder {
+        TaskGroupBuilder::new(Self::new())
+    }
+
+    /// Creates a group printed under a `Group 2/3: name` header, with its
+    /// units numbered within the group and indented beneath it.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ExecutionUnit, ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let mut build = TaskGroup::new();
+    /// build.add_unit(ExecutionUnit::new("Compile".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    ///
+    /// let mut database = TaskGroup::new_named("Database setup");
+    /// database.add_unit(ExecutionUnit::new("Create schema".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    /// database.add_unit(ExecutionUnit::new("Seed data".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    ///
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(buffer.clone());
+    /// # manager.ascii(false);
+    /// manager.add_group(build);
+    /// manager.add_group(database);
+    /// manager.start().unwrap();
+    ///
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// let lines: Vec<&str> = output.lines().collect();
+    /// assert_eq!(lines[0], "[1/2] Compile started");
+    /// assert!(lines[1].starts_with("[1/2] Compile [ok]"));
+    /// assert_eq!(lines[2], "Group 2/2: Database setup");
+    /// assert_eq!(lines[3], "  [1/2] Create schema started");
+    /// assert!(lines[4].starts_with("  [1/2] Create schema [ok]"));
+    /// assert!(lines[6].starts_with("  [2/2] Seed data [ok]"));
+    /// ```
+    ///
+    /// Names identify groups in events, observer notifications and the
+    /// summary, and are looked up by
+    /// [`ProgressManager::start_from_group`]: a run whose groups share a
+    /// name starts with a warning, as only the first of them can be found.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(buffer.clone());
+    /// manager.add_group(TaskGroup::new_named("Deploy"));
+    /// manager.add_group(TaskGroup::new_named("Deploy"));
+    /// manager.start().unwrap();
+    ///
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// assert!(output.starts_with("warning: 2 groups are named \"Deploy\", lookups by name only find the first one\n"));
+    /// ```
+    pub fn new_named(name: impl Into<String>) -> Self {
+        Self {
+            name: Some(name.into()),
+            ..Self::new()
+        }
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Adds `unit` after the units added so far, returning the group to
+    /// chain calls.
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionUnit, TaskGroup};
+    /// let mut group = TaskGroup::new_named("Build");
+    /// group
+    ///     .add_unit(ExecutionUnit::new("Compile".to_string()).on_try_execute(|_status| Ok::<(), String>(())))
+    ///     .add_unit(ExecutionUnit::new("Link".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    /// assert_eq!(group.len(), 2);
+    /// ```
+    pub fn add_unit(&mut self, unit: ExecutionUnit) -> &mut Self {
+        self.units.push(unit);
+        self
+    }
+
+    /// Like [`add_unit`](Self::add_unit), taking and returning the group
+    /// for one-liners such as `TaskGroup::new_named("Build").with_unit(compile)`.
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionUnit, TaskGroup};
+    /// let group = TaskGroup::new_named("Build")
+    ///     .with_unit(ExecutionUnit::new("Compile".to_string()).on_try_execute(|_status| Ok::<(), String>(())))
+    ///     .with_unit(ExecutionUnit::new("Link".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    /// assert_eq!(group.name(), Some("Build"));
+    /// assert_eq!(group.len(), 2);
+    /// ```
+    pub fn with_unit(mut self, unit: ExecutionUnit) -> Self {
+        self.add_unit(unit);
+        self
+    }
+
+    /// Adds one unit per item of `items`, described by `description` and
+    /// running `body` with the item, in a sequential group as well as a
+    /// [`parallel`](Self::parallel) one. Like [`on_execute`](ExecutionUnit::on_execute),
+    /// `body` sets the status of the unit by hand.
+    ///
+    /// Every item is moved into its unit, so items need not be `Clone`; as
+    /// a consequence the first attempt owns it, and a retry of the unit
+    /// fails without calling `body` again.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ExecutionStatus, ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// struct Host {
+    ///     name: String,
+    /// }
+    ///
+    /// let hosts = (1..=5).map(|i| Host { name: format!("web-{}", i) });
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(buffer.clone());
+    /// let mut deploy = TaskGroup::new_named("Deploy");
+    /// deploy.parallel(2);
+    /// deploy.for_each(hosts, |host| format!("Deploy to {}", host.name), |host, status| {
+    ///     status.set_message(format!("{} is up", host.name));
+    ///     status.complete();
+    /// });
+    /// manager.add_group(deploy);
+    /// manager.start().unwrap();
+    ///
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// let results: Vec<&str> = output.lines().filter(|line| line.contains("[ok]")).collect();
+    /// assert_eq!(results.len(), 5);
+    /// assert!(results.iter().any(|line| line.contains("Deploy to web-3")));
+    /// ```
+    pub fn for_each<T, I, D, F>(&mut self, items: I, description: D, body: F)
+    where
+        I: IntoIterator<Item = T>,
+        T: Send + 'static,
+        D: Fn(&T) -> String,
+        F: Fn(T, StatusHandle) + Clone + Send + 'static,
+    {
+        for item in items {
+            let description = description(&item);
+            let (item, body) = (Mutex::new(Some(item)), body.clone());
+            self.units.push(ExecutionUnit::new(description).on_execute(move |status| {
+                match item.lock().unwrap_or_else(PoisonError::into_inner).take() {
+                    Some(item) => body(item, status),
+                    None => status.finish_with(Err("the item was moved into an earlier attempt")),
+                }
+            }));
+        }
+    }
+
+    /// Adds a unit that finds more work, e.g. the migrations left to apply:
+    /// the units `discover` returns run right after it, set up like the
+    /// other units of the group, and the `[i/n]` counters of the units
+    /// after it shift to make room for them. Its line ends with
+    /// `found 14 units`, unless `discover` set a message of its own.
+    ///
+    /// When `discover` fails, or the unit does not complete, none of the
+    /// units it returned run. In a [`parallel`](Self::parallel) group the
+    /// units found run as soon as a worker is free, numbered after the
+    /// units the group started with.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ExecutionUnit, ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(buffer.clone());
+    /// let mut database = TaskGroup::new_named("Database");
+    /// database.expanding_unit("List migrations", |status| {
+    ///     let pending = ["0007_add_users", "0008_add_index"];
+    ///     status.set_message(format!("found {} migrations", pending.len()));
+    ///     let migrations = pending.map(|name| {
+    ///         ExecutionUnit::new(format!("Apply {}", name)).on_try_execute(|_status| Ok::<(), String>(()))
+    ///     });
+    ///     Ok::<_, String>(migrations.into())
+    /// });
+    /// database.add_unit(ExecutionUnit::new("Vacuum".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    /// manager.add_group(database);
+    /// manager.start().unwrap();
+    ///
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// let finished: Vec<&str> = output.lines().filter(|line| !line.ends_with(" started")).collect();
+    /// assert!(finished[1].starts_with("  [1/4] List migrations — found 2 migrations [ok]"));
+    /// assert!(finished[2].starts_with("  [2/4] Apply 0007_add_users [ok]"));
+    /// assert!(finished[3].starts_with("  [3/4] Apply 0008_add_index [ok]"));
+    /// assert!(finished[4].starts_with("  [4/4] Vacuum [ok]"));
+    /// ```
+    pub fn expanding_unit<F, E>(&mut self, description: impl Into<String>, discover: F)
+    where
+        F: Fn(StatusHandle) -> Result<Vec<ExecutionUnit>, E> + Send + 'static,
+        E: Display + Send + Sync + 'static,
+    {
+        let found = Arc::new(Mutex::new(Vec::new()));
+        let slot = found.clone();
+        let mut unit = ExecutionUnit::new(description.into()).keep_message(true).on_try_execute(move |status| {
+            let units = discover(status.clone())?;
+            if status.status.message().is_none() {
+                let noun = if units.len() == 1 { "unit" } else { "units" };
+                status.set_message(format!("found {} {}", units.len(), noun));
+            }
+            // Counted right away, so the line of this unit already shows the new total.
+            let count = units.len();
+            let previous = std::mem::replace(&mut *slot.lock().unwrap_or_else(PoisonError::into_inner), units).len();
+            if let Some(queue) = &status.queue {
+                queue.total.fetch_add(count, Ordering::SeqCst);
+                queue.total.fetch_sub(previous, Ordering::SeqCst);
+            }
+            Ok::<(), E>(())
+        });
+        unit.found = Some(found);
+        self.units.push(unit);
+    }
+
+    /// Adds the stages of `pipeline`, in order, see [`Pipeline`].
+    pub fn add_pipeline<T>(&mut self, pipeline: Pipeline<T>) {
+        self.units.extend(pipeline.into_units());
+    }
+
+    pub fn units(&self) -> &[ExecutionUnit] {
+        &self.units
+    }
+
+    /// Number of units added so far, not counting the ones pushed to its
+    /// [`queue`](Self::queue).
+    pub fn len(&self) -> usize {
+        self.units.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.units.is_empty()
+    }
+
+    /// Handle to push units to the group while it runs, from another thread,
+    /// e.g. one per item found by a crawler that runs alongside.
+    ///
+    /// Once the group ran its units it waits for more as long as any handle
+    /// is alive, from this method or [`StatusHandle::queue`], and runs them
+    /// as they come, up to its [`parallel`](Self::parallel) limit. It is done
+    /// once every handle was dropped, or closed with [`QueueHandle::close`]:
+    /// a handle kept alive next to the run, or moved into the closure of one
+    /// of the units, keeps it waiting. The `[i/n]` counters of a named group
+    /// count the units as they are pushed.
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionUnit, ProgressManager, TaskGroup};
+    /// let mut uploads = TaskGroup::new_named("Upload");
+    /// uploads.parallel(4);
+    /// uploads.add_unit(ExecutionUnit::new("List prefixes".to_string()).on_try_execute(|status| {
+    ///     let queue = status.queue().unwrap();
+    ///     for prefix in ["images/", "videos/", "docs/"] {
+    ///         queue.push(ExecutionUnit::new(format!("Sync {}", prefix)).on_try_execute(|_status| Ok::<(), String>(())));
+    ///     }
+    ///     Ok::<(), String>(())
+    /// }));
+    /// let queue = uploads.queue();
+    /// let crawler = std::thread::spawn(move || {
+    ///     queue.push(ExecutionUnit::new("Sync backups/".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    /// });
+    ///
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(std::io::sink());
+    /// manager.add_group(uploads);
+    /// manager.start().unwrap();
+    /// crawler.join().unwrap();
+    /// assert_eq!(manager.groups()[0].units().len(), 5);
+    /// assert!(manager.groups()[0].units().iter().all(|unit| unit.is_completed()));
+    /// ```
+    pub fn queue(&self) -> QueueHandle {
+        QueueHandle::new(self.queue.clone())
+    }
+
+    pub fn set_cancellation_token(&mut self, token: CancellationToken) {
+        self.cancellation = token;
+    }
+
+    pub(crate) fn set_observers(&mut self, observers: Observers) {
+        self.observers = observers;
+    }
+
+    /// Token that cancels this group: the running unit stops and the remaining ones are skipped.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancellation.clone()
+    }
+
+    /// Overrides the manager's [`ExitPolicy`] for failures of units in this group.
+    ///
+    /// With [`ExitPolicy::StopAndReturn`] the units left after a failure are
+    /// printed as skipped, with the step that failed as their reason.
+    ///
+    /// ```rust
+    /// # use hflow::{ColorMode, ExecutionStatus, ExecutionUnit, ExitPolicy, ProgressManager, TaskGroup};
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let mut release = TaskGroup::new();
+    /// release.exit_policy(ExitPolicy::StopAndReturn);
+    /// release.add_unit(ExecutionUnit::new("Build".to_string()).on_try_execute(|_| Err::<(), _>("linker not found")));
+    /// release.add_unit(ExecutionUnit::new("Test".to_string()).on_try_execute(|_| Ok::<(), String>(())));
+    /// release.add_unit(ExecutionUnit::new("Package".to_string()).on_try_execute(|_| Ok::<(), String>(())));
+    ///
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(buffer.clone());
+    /// manager.color(ColorMode::Never);
+    /// manager.add_group(release);
+    /// assert!(manager.start().is_err());
+    ///
+    /// let units = manager.groups()[0].units();
+    /// assert_eq!(units[0].status(), ExecutionStatus::Failed);
+    /// assert!(units[1..].iter().all(|unit| unit.status() == ExecutionStatus::Skipped));
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// assert!(output.contains("Test [skipped]\n    previous step failed: Build\n"));
+    /// assert!(output.contains("Package [skipped]\n    previous step failed: Build\n"));
+    /// ```
+    pub fn exit_policy(&mut self, policy: ExitPolicy) {
+        self.exit_policy = Some(policy);
+    }
+
+    /// Runs every unit of the group even after some failed, e.g. to check
+    /// all endpoints at once. Each failure prints its line and reason as
+    /// usual, and [`run`](Self::run) returns the first one once the group
+    /// is done; see [`run_report`](Self::run_report) for all of them.
+    ///
+    /// Only the units of this group carry on: the [`ExitPolicy`] still
+    /// decides what happens after the group, once it has failed, so
+    /// [`ExitPolicy::ExitProcess`] exits then instead of at the first failure.
+    pub fn continue_on_error(&mut self, enabled: bool) {
+        self.continue_on_error = enabled;
+    }
+
+    /// Gives the units tagged `tag` that set no [`timeout`](ExecutionUnit::timeout)
+    /// of their own this one, e.g. a longer one for `slow` units. A unit
+    /// with several such tags gets the timeout set first. Overrides
+    /// [`ProgressManager::tag_timeout`] for the same tag.
+    ///
+    /// ```rust
+    /// # use std::time::Duration;
+    /// # use hflow::{ExecutionStatus, ExecutionUnit, TaskGroup};
+    /// let wait = |status: hflow::StatusHandle| {
+    ///     std::thread::sleep(Duration::from_millis(300));
+    ///     status.complete();
+    /// };
+    /// let mut group = TaskGroup::new();
+    /// group.tag_timeout("slow", Duration::from_secs(5));
+    /// group.tag_timeout("quick", Duration::from_millis(100));
+    /// group.add_unit(ExecutionUnit::new("Build".to_string()).tag("slow").on_execute(wait));
+    /// group.add_unit(ExecutionUnit::new("Lint".to_string()).tag("quick").on_execute(wait));
+    /// group.continue_on_error(true);
+    ///
+    /// assert!(group.run(1, 1).is_err());
+    /// assert_eq!(group.units()[0].status(), ExecutionStatus::Completed);
+    /// assert_eq!(group.units()[1].status(), ExecutionStatus::TimedOut);
+    /// ```
+    pub fn tag_timeout(&mut self, tag: impl Into<String>, timeout: Duration) {
+        self.tag_timeouts.push((tag.into(), timeout));
+    }
+
+    /// Stops the group once `failures` units have failed, when it goes on
+    /// after failures with [`continue_on_error`](Self::continue_on_error)
+    /// or [`ExitPolicy::ContinueAll`]: no other unit starts, the running ones
+    /// are cancelled and the rest are skipped as `circuit breaker tripped`.
+    /// The group then returns [`HflowError::CircuitBreakerTripped`].
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionStatus, ExecutionUnit, HflowError, TaskGroup};
+    /// let mut hosts = TaskGroup::new();
+    /// hosts.continue_on_error(true);
+    /// hosts.max_failures(2);
+    /// for host in ["web-1", "web-2", "web-3", "web-4"] {
+    ///     hosts.add_unit(ExecutionUnit::new(format!("Upgrade {}", host)).on_try_execute(|_status| Err("apt lock held")));
+    /// }
+    ///
+    /// let result = hosts.run_report(1, 1);
+    /// assert_eq!(result.error, Some(HflowError::CircuitBreakerTripped { failures: 2 }));
+    /// let statuses: Vec<_> = result.units.iter().map(|unit| unit.status).collect();
+    /// assert_eq!(statuses, [ExecutionStatus::Failed, ExecutionStatus::Failed, ExecutionStatus::Skipped, ExecutionStatus::Skipped]);
+    /// assert_eq!(result.units[2].failure_reason.as_deref(), Some("circuit breaker tripped"));
+    /// ```
+    pub fn max_failures(&mut self, failures: usize) {
+        self.max_failures = Some(failures.max(1));
+    }
+
+    /// Waits `delay` between one unit finishing and the next one starting,
+    /// e.g. to stay under the rate limit of an API. The wait is shown as a
+    /// dimmed `waiting 2s…` line, except in quiet mode, and ends early when
+    /// the group is cancelled.
+    ///
+    /// There is no wait after the last unit, nor after a unit that was
+    /// skipped. Only groups running one unit at a time wait, see
+    /// [`parallel`](Self::parallel).
+    ///
+    /// ```rust
+    /// # use std::time::{Duration, Instant};
+    /// # use hflow::{ExecutionUnit, TaskGroup};
+    /// let mut requests = TaskGroup::new();
+    /// requests.delay_between(Duration::from_millis(100));
+    /// for page in 1..=3 {
+    ///     requests.add_unit(ExecutionUnit::new(format!("GET /orders?page={}", page)).on_try_execute(|_status| Ok::<(), String>(())));
+    /// }
+    ///
+    /// let started = Instant::now();
+    /// requests.run(1, 1).unwrap();
+    /// assert!(started.elapsed() >= Duration::from_millis(200));
+    /// ```
+    pub fn delay_between(&mut self, delay: Duration) {
+        self.delay_fn(move |_| delay);
+    }
+
+    /// Like [`delay_between`](Self::delay_between), with the wait depending
+    /// on how the previous unit ended, e.g. longer after a failure.
+    ///
+    /// ```rust
+    /// # use std::time::Duration;
+    /// # use hflow::{ExecutionStatus, TaskGroup};
+    /// let mut requests = TaskGroup::new();
+    /// requests.delay_fn(|previous| match previous {
+    ///     ExecutionStatus::Failed => Duration::from_secs(5),
+    ///     _ => Duration::from_millis(500),
+    /// });
+    /// ```
+    pub fn delay_fn<F>(&mut self, delay: F)
+    where
+        F: Fn(ExecutionStatus) -> Duration + Send + 'static,
+    {
+        self.delay = Some(Box::new(delay));
+    }
+
+    /// Invoked once the group has run, however it ended, with how every
+    /// unit ended; after the [`ExecutionUnit::on_finish`] callbacks of its
+    /// units and before the next group starts. Not invoked when
+    /// [`ExitPolicy::ExitProcess`] ends the process from a group [run](Self::run)
+    /// on its own; under a [`ProgressManager`] the process only exits once
+    /// the run is over.
+    ///
+    /// A panic inside the callback is caught and printed, the result of the
+    /// group stays as it was.
+    ///
+    /// ```rust
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ExecutionUnit, TaskGroup};
+    /// let released = Arc::new(Mutex::new(None));
+    /// let mut deploy = TaskGroup::new();
+    /// deploy.add_unit(ExecutionUnit::new("Push image".to_string()).on_try_execute(|_status| Err("registry down")));
+    /// let result = released.clone();
+    /// deploy.on_finish(move |report| *result.lock().unwrap() = Some(report.is_success()));
+    ///
+    /// assert!(deploy.run(1, 1).is_err());
+    /// assert_eq!(*released.lock().unwrap(), Some(false));
+    /// ```
+    pub fn on_finish<F>(&mut self, callback: F)
+    where
+        F: FnOnce(&GroupResult) + Send + 'static,
+    {
+        self.on_finish = Some(Box::new(callback));
+    }
+
+    /// Invoked once the group has run when no unit failed or timed out,
+    /// except those allowed to, e.g. to announce that the migrations are
+    /// done. It runs before [`on_finish`](Self::on_finish), after the
+    /// callbacks of the units, and may take its time: a dimmed
+    /// `finalizing group…` line is shown meanwhile, except in quiet mode.
+    ///
+    /// A panic inside the callback is caught and printed, the result of the
+    /// group stays as it was.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ExecutionUnit, ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let buffer = Buffer::default();
+    /// let messages = Arc::new(Mutex::new(Vec::new()));
+    /// let mut migrations = TaskGroup::new_named("migrations");
+    /// migrations.add_unit(ExecutionUnit::new("Create users".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    /// migrations.add_unit(ExecutionUnit::new("Create orders".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    /// let posted = messages.clone();
+    /// migrations.on_success(move |result| {
+    ///     posted.lock().unwrap().push(format!("migrations done: {} units", result.units.len()));
+    /// });
+    /// migrations.on_failure(|_result| unreachable!());
+    ///
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(buffer.clone());
+    /// manager.add_group(migrations);
+    /// manager.start().unwrap();
+    ///
+    /// assert_eq!(*messages.lock().unwrap(), ["migrations done: 2 units"]);
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// assert!(output.contains("  finalizing group…") || output.contains("  finalizing group..."));
+    /// ```
+    pub fn on_success<F>(&mut self, callback: F)
+    where
+        F: FnOnce(&GroupResult) + Send + 'static,
+    {
+        self.on_success = Some(Box::new(callback));
+    }
+
+    /// Invoked once when a unit of the group failed or timed out, with how
+    /// every unit ended, e.g. to run a rollback. It fires when the group
+    /// gives up on the failure, or once every unit ran when it
+    /// [continues on error](Self::continue_on_error), and never together
+    /// with [`on_success`](Self::on_success). Like it, the callback runs
+    /// before [`on_finish`](Self::on_finish) under a `finalizing group…`
+    /// line, and is not invoked when [`ExitPolicy::ExitProcess`] ends the
+    /// process from a group run on its own.
+    ///
+    /// ```rust
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ExecutionStatus, ExecutionUnit, TaskGroup};
+    /// let rollback = Arc::new(Mutex::new(Vec::new()));
+    /// let mut deploy = TaskGroup::new();
+    /// deploy.add_unit(ExecutionUnit::new("Push image".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    /// deploy.add_unit(ExecutionUnit::new("Migrate".to_string()).on_try_execute(|_status| Err("lock timeout")));
+    /// deploy.add_unit(ExecutionUnit::new("Restart".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    /// let undo = rollback.clone();
+    /// deploy.on_failure(move |result| {
+    ///     for unit in &result.units {
+    ///         undo.lock().unwrap().push((unit.description.clone(), unit.status, unit.failure_reason.clone()));
+    ///     }
+    ///     assert!(result.units[0].elapsed.is_some());
+    /// });
+    /// deploy.on_success(|_result| unreachable!());
+    ///
+    /// assert!(deploy.run(1, 1).is_err());
+    /// let rollback = rollback.lock().unwrap();
+    /// assert_eq!(rollback[0], ("Push image".to_string(), ExecutionStatus::Completed, None));
+    /// assert_eq!(rollback[1], ("Migrate".to_string(), ExecutionStatus::Failed, Some("lock timeout".to_string())));
+    /// assert_eq!(rollback[2].1, ExecutionStatus::Skipped);
+    /// ```
+    pub fn on_failure<F>(&mut self, callback: F)
+    where
+        F: FnOnce(&GroupResult) + Send + 'static,
+    {
+        self.on_failure = Some(Box::new(callback));
+    }
+
+    /// Invoked before every unit of the group runs, e.g. to snapshot the
+    /// state the units change. It runs on the worker thread of the unit, so
+    /// it may take its time while the lines keep being drawn, after
+    /// [`ExecutionUnit::on_start`] and once per unit, not once per attempt
+    /// when the unit is [retried](ExecutionUnit::retries).
+    ///
+    /// A panic inside the hook fails the unit without running it.
+    pub fn before_each<F>(&mut self, hook: F)
+    where
+        F: Fn(&UnitInfo) + Send + Sync + 'static,
+    {
+        self.before_each = Some(Arc::new(hook));
+    }
+
+    /// Invoked after every unit of the group ran, with the status it ended
+    /// with, e.g. to check the invariants a migration must keep. Like
+    /// [`before_each`](Self::before_each) it runs once per unit on its
+    /// worker thread, before its final line is drawn.
+    ///
+    /// An error returned by the hook, or a panic inside it, fails a unit
+    /// that completed, with the error as its reason. Units that did not
+    /// complete keep their status.
+    ///
+    /// ```rust
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ExecutionStatus, ExecutionUnit, TaskGroup};
+    /// let tables = Arc::new(Mutex::new(vec!["users"]));
+    /// let mut migrate = TaskGroup::new();
+    /// migrate.continue_on_error(true);
+    /// let before = Arc::new(Mutex::new(0));
+    /// let (seen, count) = (tables.clone(), before.clone());
+    /// migrate.before_each(move |_info| *count.lock().unwrap() = seen.lock().unwrap().len());
+    /// let (seen, count) = (tables.clone(), before.clone());
+    /// migrate.after_each(move |info, _status| match seen.lock().unwrap().len() >= *count.lock().unwrap() {
+    ///     true => Ok(()),
+    ///     false => Err(format!("{} dropped a table", info.description)),
+    /// });
+    /// let created = tables.clone();
+    /// migrate.add_unit(ExecutionUnit::new("Add orders".to_string()).on_try_execute(move |_status| {
+    ///     created.lock().unwrap().push("orders");
+    ///     Ok::<(), String>(())
+    /// }));
+    /// let dropped = tables.clone();
+    /// migrate.add_unit(ExecutionUnit::new("Rename users".to_string()).on_try_execute(move |_status| {
+    ///     dropped.lock().unwrap().retain(|table| *table != "users");
+    ///     Ok::<(), String>(())
+    /// }));
+    ///
+    /// assert!(migrate.run(1, 1).is_err());
+    /// assert_eq!(migrate.units()[0].status(), ExecutionStatus::Completed);
+    /// assert_eq!(migrate.units()[1].status(), ExecutionStatus::Failed);
+    /// assert_eq!(migrate.units()[1].failure_reason().as_deref(), Some("after_each: Rename users dropped a table"));
+    /// ```
+    pub fn after_each<F, E>(&mut self, hook: F)
+    where
+        F: Fn(&UnitInfo, ExecutionStatus) -> Result<(), E> + Send + Sync + 'static,
+        E: Display,
+    {
+        self.after_each = Some(Arc::new(move |info: &UnitInfo, status| hook(info, status).map_err(|err| err.to_string())));
+    }
+
+    /// Spinner used by the units of this group that do not set their own,
+    /// instead of the one set on the manager, see [`ExecutionUnit::spinner`].
+    pub fn spinner(&mut self, style: SpinnerStyle) {
+        self.spinner = Some(style);
+    }
+
+    /// Context passed to the units of this group instead of the one set on
+    /// the manager, see [`ExecutionUnit::on_try_execute_with`].
+    pub fn with_context<C: Send + Sync + 'static>(&mut self, context: C) {
+        self.context = Some(Arc::new(context));
+    }
+
+    pub(crate) fn inherit_exit_policy(&mut self, policy: ExitPolicy) {
+        self.inherited_exit_policy = policy;
+    }
+
+    pub(crate) fn inherit_render(&mut self, render: RenderSettings) {
+        self.render = render;
+    }
+
+    /// Policy applied when one of the units fails.
+    pub fn effective_exit_policy(&self) -> ExitPolicy {
+        self.exit_policy.unwrap_or(self.inherited_exit_policy)
+    }
+
+    /// Runs up to `max_concurrency` units of the group at the same time, in
+    /// the order they were added once the units they
+    /// [depend on](ExecutionUnit::depends_on) finished, instead of one after another. Every running
+    /// unit has its own live line. One or less runs the units in sequence.
+    ///
+    /// After a failure, [`ExitPolicy::StopAndReturn`] cancels the units still
+    /// running and starts no other, [`ExitPolicy::ContinueAll`] lets every
+    /// unit run to the end. Either way [`run`](Self::run) only returns once
+    /// every started unit has finished.
+    ///
+    /// The units cancelled end with `✗ cancelled (sibling failed)`. A unit
+    /// failing without having seen the cancellation, e.g. at nearly the same
+    /// time as the first one, still reports its own failure.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use std::sync::atomic::{AtomicUsize, Ordering};
+    /// # use std::time::Duration;
+    /// # use hflow::{ColorMode, ExecutionStatus, ExecutionUnit, ExitPolicy, ProgressManager, StatusHandle, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let slow = |_status| {
+    ///     std::thread::sleep(Duration::from_millis(300));
+    ///     Ok::<(), String>(())
+    /// };
+    /// // Counts the units running at once and keeps the highest count.
+    /// let running = Arc::new([AtomicUsize::new(0), AtomicUsize::new(0)]);
+    /// let counted = |running: &Arc<[AtomicUsize; 2]>| {
+    ///     let running = running.clone();
+    ///     move |_status: StatusHandle| {
+    ///         let now = running[0].fetch_add(1, Ordering::SeqCst) + 1;
+    ///         running[1].fetch_max(now, Ordering::SeqCst);
+    ///         std::thread::sleep(Duration::from_millis(300));
+    ///         running[0].fetch_sub(1, Ordering::SeqCst);
+    ///         Ok::<(), String>(())
+    ///     }
+    /// };
+    /// let mut checks = TaskGroup::new();
+    /// checks.parallel(3);
+    /// for check in ["Lint", "Test", "Build docs"] {
+    ///     checks.add_unit(ExecutionUnit::new(check.to_string()).on_try_execute(counted(&running)));
+    /// }
+    ///
+    /// assert_eq!(checks.run(1, 1).unwrap(), vec![ExecutionStatus::Completed; 3]);
+    /// assert_eq!(running[1].load(Ordering::SeqCst), 3);
+    ///
+    /// // A failure cancels the units still running.
+    /// let mut checks = TaskGroup::new();
+    /// checks.parallel(2);
+    /// checks.exit_policy(ExitPolicy::StopAndReturn);
+    /// checks.add_unit(ExecutionUnit::new("Lint".to_string()).on_try_execute(|_status| {
+    ///     std::thread::sleep(Duration::from_millis(100));
+    ///     Err("3 warnings")
+    /// }));
+    /// checks.add_unit(ExecutionUnit::new("Test".to_string()).on_execute(|status| {
+    ///     while !status.is_cancelled() {
+    ///         std::thread::sleep(Duration::from_millis(10));
+    ///     }
+    /// }));
+    /// checks.add_unit(ExecutionUnit::new("Build docs".to_string()).on_try_execute(slow));
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(buffer.clone());
+    /// manager.color(ColorMode::Never);
+    /// manager.add_group(checks);
+    /// assert!(manager.start().is_err());
+    /// let statuses: Vec<_> = manager.groups()[0].units().iter().map(ExecutionUnit::status).collect();
+    /// assert_eq!(statuses, [ExecutionStatus::Failed, ExecutionStatus::Cancelled, ExecutionStatus::Skipped]);
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// let test = output.lines().filter(|line| line.contains(" Test ")).last().unwrap();
+    /// assert!(test.starts_with("[1/1] Test [cancelled] (sibling failed) — "), "{:?}", output);
+    ///
+    /// // A unit failing right after the first one is not taken for cancelled.
+    /// let mut checks = TaskGroup::new();
+    /// checks.parallel(2);
+    /// checks.exit_policy(ExitPolicy::StopAndReturn);
+    /// checks.add_unit(ExecutionUnit::new("Lint".to_string()).on_try_execute(|_status| {
+    ///     std::thread::sleep(Duration::from_millis(100));
+    ///     Err("3 warnings")
+    /// }));
+    /// checks.add_unit(ExecutionUnit::new("Test".to_string()).on_try_execute(|_status| {
+    ///     std::thread::sleep(Duration::from_millis(150));
+    ///     Err("2 tests failed")
+    /// }));
+    /// assert!(checks.run(1, 1).is_err());
+    /// assert_eq!(checks.units()[1].status(), ExecutionStatus::Failed);
+    /// assert_eq!(checks.units()[1].failure_reason().as_deref(), Some("2 tests failed"));
+    ///
+    /// // Or they all run to the end.
+    /// let mut checks = TaskGroup::new();
+    /// checks.parallel(2);
+    /// checks.exit_policy(ExitPolicy::ContinueAll);
+    /// checks.add_unit(ExecutionUnit::new("Lint".to_string()).on_try_execute(|_status| Err("3 warnings")));
+    /// checks.add_unit(ExecutionUnit::new("Test".to_string()).on_try_execute(slow));
+    /// checks.add_unit(ExecutionUnit::new("Build docs".to_string()).on_try_execute(slow));
+    /// assert!(checks.run(1, 1).is_err());
+    /// let statuses: Vec<_> = checks.units().iter().map(ExecutionUnit::status).collect();
+    /// assert_eq!(statuses, [ExecutionStatus::Failed, ExecutionStatus::Completed, ExecutionStatus::Completed]);
+    /// ```
+    pub fn parallel(&mut self, max_concurrency: usize) {
+        self.max_concurrency = max_concurrency.max(1);
+    }
+
+    /// Lists every unit up front as pending and updates each line in place as it runs.
+    ///
+    /// Only takes effect when the output is a terminal; otherwise units are printed as they run.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use std::time::Duration;
+    /// # use hflow::{ColorMode, ExecutionStatus, ExecutionUnit, ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.terminal_writer(buffer.clone());
+    /// manager.color(ColorMode::Never);
+    /// let mut group = TaskGroup::new();
+    /// group.show_pending(true);
+    /// for step in ["Fetch", "Build"] {
+    ///     group.add_unit(ExecutionUnit::new(step.to_string()).on_try_execute(|_status| {
+    ///         std::thread::sleep(Duration::from_millis(100));
+    ///         Ok::<(), String>(())
+    ///     }));
+    /// }
+    /// assert_eq!(group.units()[1].status(), ExecutionStatus::Pending);
+    /// manager.add_group(group);
+    /// manager.start().unwrap();
+    ///
+    /// // Build is first drawn as pending, while Fetch runs.
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// let pending = output.find("[1/1] Build [pending]").unwrap();
+    /// assert_eq!(output.find("Build"), Some(pending + "[1/1] ".len()));
+    /// assert!(pending < output.find("[1/1] Fetch [ok]").unwrap());
+    /// ```
+    pub fn show_pending(&mut self, enabled: bool) {
+        self.show_pending = enabled;
+    }
+
+    /// Executes all units within the group one after another.
+    ///
+    /// Returns the final status of every unit, in order, or the error of the
+    /// first unit that failed. What happens after a failure depends on the
+    /// [`effective_exit_policy`](Self::effective_exit_policy): the process
+    /// exits, the remaining units are not executed, or they all run and the
+    /// first error is returned at the end.
+    ///
+    /// Units left out after a failure are skipped, with the failed unit as
+    /// the reason, and printed as such. In a group whose units declare
+    /// dependencies only the dependents of the failed unit are skipped, the
+    /// others are left pending.
+    ///
+    /// Units run after the units they [depend on](ExecutionUnit::depends_on),
+    /// an unknown id or a dependency cycle is returned before any unit starts.
+    ///
+    /// Units of a named group are numbered within the group instead, so
+    /// `total_groups` and `current_idx` only matter for nameless groups.
+    pub fn run(&mut self, total_groups: u32, current_idx: u32) -> Result<Vec<ExecutionStatus>, HflowError> {
+        let result = self.run_units(total_groups, current_idx);
+        let outcome = match &result {
+            Ok(_) => self.on_success.take().map(|callback| ("on_success", callback)),
+            Err(_) => self.on_failure.take().map(|callback| ("on_failure", callback)),
+        };
+        if outcome.is_none() && self.on_finish.is_none() {
+            return result;
+        }
+        let report = self.report(result.as_ref().err().cloned());
+        let mut render = self.render.clone();
+        if let Some(name) = &self.name {
+            render.indent = if render.concurrent_groups { format!("{}: ", name) } else { GROUP_INDENT.to_string() };
+        }
+        if let Some((hook, callback)) = outcome {
+            let line = format!("{}finalizing group{}", render.indent, render.ellipsis());
+            let line = render.paint(&line, TextStyle::Dimmed);
+            render.emit(Event::new("group_finalizing").value("group", render.group).text("hook", hook));
+            let live = render.is_live().then(|| render.renderer.add(line.clone()));
+            if live.is_none() && render.is_human() && !render.quiet {
+                render.renderer.println(&render.stamp(&line));
+            }
+            run_finish_callback(&render, hook, || callback(&report));
+            if let Some(live) = live {
+                render.renderer.remove(live);
+            }
+        }
+        if let Some(callback) = self.on_finish.take() {
+            run_finish_callback(&render, "on_finish", || callback(&report));
+        }
+        result
+    }
+
+    /// Body of [`run`](Self::run), before the [`on_success`](Self::on_success),
+    /// [`on_failure`](Self::on_failure) and [`on_finish`](Self::on_finish) callbacks.
+    fn run_units(&mut self, total_groups: u32, current_idx: u32) -> Result<Vec<ExecutionStatus>, HflowError> {
+        let mut order = self.resolve_dependencies()?;
+        order.retain(|&idx| !self.units[idx].excluded);
+        let mut render = self.render.clone();
+        if let Some(name) = &self.name {
+            render.indent = if render.concurrent_groups { format!("{}: ", name) } else { GROUP_INDENT.to_string() };
+        }
+        if let Some(spinner) = &self.spinner {
+            render.spinner = spinner.clone();
+        }
+        if let Some(context) = &self.context {
+            render.context = Some(context.clone());
+        }
+        render.group = current_idx as usize;
+        render.group_name = self.name.clone();
+        // The timeouts of the group come before the ones of the manager.
+        render.tag_timeouts = self.tag_timeouts.iter().chain(&self.render.tag_timeouts).cloned().collect();
+        render.queue = Some(self.queue.clone());
+        let cancel_reason = Arc::new(OnceLock::new());
+        render.cancel_reason = Some(cancel_reason.clone());
+        if self.name.is_some() {
+            self.queue.total.store(self.units.len() + self.queue.len(), Ordering::SeqCst);
+            render.unit_total = Some(self.queue.total.clone());
+        }
+        // Cancels the units of a parallel group still running after a failure.
+        let siblings = self.cancellation.child_token();
+        let total_units = self.units.len() as u32;
+        let (named, observers) = (self.name.is_some(), self.observers.clone());
+        let (before_each, after_each) = (self.before_each.clone(), self.after_each.clone());
+        // Numbers the unit at `idx` of the group, pushed ones included, and hands it what it needs to run.
+        let prepare = |unit: &mut ExecutionUnit, idx: usize| {
+            if named {
+                unit.set_group_index(idx as u32 + 1);
+                unit.set_total_groups(total_units);
+            } else {
+                unit.set_group_index(current_idx);
+                unit.set_total_groups(total_groups);
+            }
+            unit.set_cancellation_token(siblings.child_token());
+            unit.set_observers(observers.clone());
+            unit.before_each = before_each.clone();
+            unit.after_each = after_each.clone();
+            let mut render = render.clone();
+            render.unit = idx + 1;
+            unit.inherit_render(render);
+        };
+        for (idx, unit) in self.units.iter_mut().enumerate() {
+            prepare(unit, idx);
+        }
+        let ids: Vec<UnitId> = self.units.iter().map(ExecutionUnit::unit_id).collect();
+        for observer in self.observers.iter() {
+            observer.on_group_started(current_idx as usize, self.name.as_deref(), &ids);
+        }
+
+        let pending_block = self.show_pending && self.render.is_live();
+        if pending_block {
+            for unit in self.units.iter_mut().filter(|unit| !unit.excluded) {
+                unit.reserve_line();
+            }
+        }
+
+        let policy = match self.effective_exit_policy() {
+            _ if self.continue_on_error => ExitPolicy::ContinueAll,
+            ExitPolicy::ExitProcess if self.render.defer_exit => ExitPolicy::StopAndReturn,
+            policy => policy,
+        };
+        // Units without dependencies follow each other: after a failure all
+        // the ones left are skipped, not only the dependents.
+        let dependent = self.units.iter().any(|unit| !unit.dependencies.is_empty());
+        if self.max_concurrency > 1 {
+            return self.run_parallel(policy, &siblings, &cancel_reason, &order, dependent, &prepare);
+        }
+        let mut first_error = None;
+        let mut failures = 0;
+        let mut position = 0;
+        loop {
+            if position == order.len() {
+                let Some(unit) = self.queue.pop(|| false, &siblings) else {
+                    break;
+                };
+                order.push(self.adopt(unit, &prepare));
+            }
+            let idx = order[position];
+            let result = self.units[idx].execute();
+            self.expand(idx, position, &mut order, &prepare);
+            if let Err(err) = result {
+                match policy {
+                    ExitPolicy::ExitProcess => {
+                        self.render.renderer.restore_cursor();
+                        std::process::exit(err.exit_code());
+                    }
+                    ExitPolicy::StopAndReturn => {
+                        let reason = (!dependent).then(|| format!("previous step failed: {}", self.units[idx].description()));
+                        self.adopt_queued(&mut order, &prepare);
+                        for &idx in &order[position + 1..] {
+                            self.units[idx].stop(reason.as_deref());
+                        }
+                        return Err(err);
+                    }
+                    ExitPolicy::ContinueAll => {
+                        first_error.get_or_insert(err);
+                        failures += 1;
+                        if self.max_failures == Some(failures) {
+                            self.adopt_queued(&mut order, &prepare);
+                            for &idx in &order[position + 1..] {
+                                self.units[idx].stop(Some(CIRCUIT_BREAKER_TRIPPED));
+                            }
+                            return Err(HflowError::CircuitBreakerTripped { failures });
+                        }
+                    }
+                }
+            }
+            if position + 1 < order.len() {
+                self.wait_after(idx, &render, &siblings);
+            }
+            position += 1;
+        }
+
+        match first_error {
+            Some(err) => Err(err),
+            None => Ok(self.units.iter().map(ExecutionUnit::status).collect()),
+        }
+    }
+
+    /// Like [`run`](Self::run), for async code, see [`ProgressManager::start_async`].
+    ///
+    /// Only available with the `async` feature.
+    #[cfg(feature = "async")]
+    pub fn run_async(
+        &mut self,
+        total_groups: u32,
+        current_idx: u32,
+    ) -> impl Future<Output = Result<Vec<ExecutionStatus>, HflowError>> + Send + '_ {
+        let cancellation = self.cancellation.clone();
+        future::drive(self, TaskGroup::new(), cancellation, move |group, driver| {
+            group.render.driver = Some(driver);
+            let result = group.run(total_groups, current_idx);
+            group.render.driver = None;
+            result
+        })
+    }
+
+    /// Like [`run`](Self::run), reporting how every unit ended instead of
+    /// only the first failure.
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionStatus, ExecutionUnit, TaskGroup};
+    /// let mut endpoints = TaskGroup::new();
+    /// endpoints.continue_on_error(true);
+    /// for (path, ok) in [("/health", true), ("/login", false), ("/search", true), ("/admin", false)] {
+    ///     endpoints.add_unit(ExecutionUnit::new(format!("GET {}", path)).on_try_execute(move |_status| {
+    ///         if ok { Ok(()) } else { Err("503 Service Unavailable") }
+    ///     }));
+    /// }
+    ///
+    /// let result = endpoints.run_report(1, 1);
+    /// assert!(!result.is_success());
+    /// let statuses: Vec<_> = result.units.iter().map(|unit| unit.status).collect();
+    /// assert_eq!(statuses, [ExecutionStatus::Completed, ExecutionStatus::Failed, ExecutionStatus::Completed, ExecutionStatus::Failed]);
+    /// let failed: Vec<_> = result.failures().map(|unit| unit.description.as_str()).collect();
+    /// assert_eq!(failed, ["GET /login", "GET /admin"]);
+    /// assert_eq!(result.units[1].failure_reason.as_deref(), Some("503 Service Unavailable"));
+    /// ```
+    pub fn run_report(&mut self, total_groups: u32, current_idx: u32) -> GroupResult {
+        let error = self.run(total_groups, current_idx).err();
+        self.report(error)
+    }
+
+    /// How every unit ended after the group ran, with the first unit that
+    /// failed and was not allowed to as the error of the group.
+    fn outcome(&self) -> GroupResult {
+        let error = self.units.iter().filter(|unit| !unit.is_allowed_to_fail()).find_map(ExecutionUnit::error);
+        self.report(error)
+    }
+
+    /// How every unit ended, with `error` as the error of the group.
+    fn report(&self, error: Option<HflowError>) -> GroupResult {
+        GroupResult {
+            name: self.name.clone(),
+            units: self
+                .units
+                .iter()
+                .map(|unit| UnitOutcome {
+                    description: unit.description().to_string(),
+                    status: unit.status(),
+                    tags: unit.tags().to_vec(),
+                    elapsed: unit.elapsed(),
+                    output: None,
+                    failure_reason: unit.finish_reason(),
+                })
+                .collect(),
+            error,
+        }
+    }
+
+    /// Like [`run`](Self::run) for units that all return a `T` from
+    /// [`ExecutionUnit::on_execute_returning`], collecting how each of them
+    /// ended. Units that did not complete have no output, failed and timed
+    /// out ones have a reason instead.
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionStatus, ExecutionUnit, TaskGroup};
+    /// let mut build = TaskGroup::new();
+    /// build.add_unit(ExecutionUnit::new("Build server".to_string()).on_execute_returning(|_status| "target/server"));
+    /// build.add_unit(ExecutionUnit::new("Build client".to_string()).on_execute_returning(|status| {
+    ///     status.fail_with("linker not found");
+    ///     "target/client"
+    /// }));
+    /// build.add_unit(ExecutionUnit::new("Build docs".to_string()).on_execute_returning(|_status| "target/doc"));
+    ///
+    /// let outcomes = build.run_collect::<&str>(1, 1);
+    /// assert_eq!(outcomes[0].output, Some("target/server"));
+    /// assert_eq!(outcomes[1].status, ExecutionStatus::Failed);
+    /// assert_eq!(outcomes[1].output, None);
+    /// assert_eq!(outcomes[1].failure_reason.as_deref(), Some("linker not found"));
+    /// // The run stopped at the failure.
+    /// assert_eq!(outcomes[2].status, ExecutionStatus::Skipped);
+    /// assert_eq!(outcomes[2].failure_reason.as_deref(), Some("previous step failed: Build client"));
+    /// ```
+    pub fn run_collect<T: 'static>(&mut self, total_groups: u32, current_idx: u32) -> Vec<UnitOutcome<T>> {
+        // Failures end up in the outcomes.
+        let _ = self.run(total_groups, current_idx);
+        self.units
+            .iter_mut()
+            .map(|unit| UnitOutcome {
+                description: unit.description().to_string(),
+                status: unit.status(),
+                tags: unit.tags().to_vec(),
+                elapsed: unit.elapsed(),
+                output: unit.take_output(),
+                failure_reason: unit.finish_reason(),
+            })
+            .collect()
+    }
+
+    /// Splices the units found by the unit at `idx`, the one at `position`
+    /// in `order`, right after it and renumbers the units that follow.
+    fn expand(&mut self, idx: usize, position: usize, order: &mut Vec<usize>, prepare: &dyn Fn(&mut ExecutionUnit, usize)) {
+        let found = self.units[idx].take_found();
+        if found.is_empty() {
+            return;
+        }
+        let count = found.len();
+        for later in order.iter_mut().filter(|later| **later > idx) {
+            *later += count;
+        }
+        order.splice(position + 1..position + 1, idx + 1..=idx + count);
+        self.units.splice(idx + 1..idx + 1, found);
+        for (later, unit) in self.units.iter_mut().enumerate().skip(idx + 1) {
+            prepare(unit, later);
+        }
+    }
+
+    /// Adds a unit taken from the [`queue`](Self::queue) to the group, returning its index.
+    fn adopt(&mut self, mut unit: ExecutionUnit, prepare: &dyn Fn(&mut ExecutionUnit, usize)) -> usize {
+        let idx = self.units.len();
+        unit.added = true;
+        prepare(&mut unit, idx);
+        self.units.push(unit);
+        idx
+    }
+
+    /// Adds the units still in the [`queue`](Self::queue) to the end of `order`, for
+    /// a group stopping early to settle them along with the others.
+    fn adopt_queued(&mut self, order: &mut Vec<usize>, prepare: &dyn Fn(&mut ExecutionUnit, usize)) {
+        for unit in self.queue.drain() {
+            order.push(self.adopt(unit, prepare));
+        }
+    }
+
+    /// Runs `f` over `items`, up to `concurrency` at a time, and returns
+    /// what it returned for each of them in the order of `items`, whatever
+    /// order they finished in. Every item gets its own unit described by
+    /// `description` and its own live line, under a `37/50 done` footer.
+    ///
+    /// The units are added to the group, which is run right away like
+    /// [`run`](Self::run) does, along with the units it held before. A
+    /// failed item neither cancels the others nor stops the group, see
+    /// [`continue_on_error`](Self::continue_on_error), so no result is
+    /// lost. An item is moved into its unit, as with [`for_each`](Self::for_each).
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionStatus, TaskGroup};
+    /// let mut resolve = TaskGroup::new_named("Resolve");
+    /// let hosts = ["example.com", "localhost", "bad host", "example.org"];
+    /// let outcomes = resolve.parallel_map(hosts, 2, |host| format!("Resolve {}", host), |host, _status| {
+    ///     match host.contains(' ') {
+    ///         true => Err(format!("invalid host name: {}", host)),
+    ///         false => Ok(host.len()),
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(outcomes.len(), 4);
+    /// assert_eq!(outcomes[0].result, Some(Ok(11)));
+    /// assert_eq!(outcomes[1].description, "Resolve localhost");
+    /// assert_eq!(outcomes[2].status, ExecutionStatus::Failed);
+    /// assert_eq!(outcomes[2].result, Some(Err("invalid host name: bad host".to_string())));
+    /// assert_eq!(outcomes[3].result, Some(Ok(11)));
+    /// ```
+    pub fn parallel_map<T, R, E, I, D, F>(&mut self, items: I, concurrency: usize, description: D, f: F) -> Vec<MapOutcome<R, E>>
+    where
+        I: IntoIterator<Item = T>,
+        T: Send + 'static,
+        R: Send + 'static,
+        E: Display + Send + 'static,
+        D: Fn(&T) -> String,
+        F: Fn(T, StatusHandle) -> Result<R, E> + Clone + Send + 'static,
+    {
+        let first = self.units.len();
+        let items: Vec<T> = items.into_iter().collect();
+        let total = items.len();
+        let renderer = self.render.renderer.clone();
+        let indent = if self.name.is_some() { GROUP_INDENT } else { "" };
+        let footer = (self.render.is_live() && total > 0).then(|| {
+            renderer.add_footer(self.render.paint(&format!("{}0/{} done", indent, total), TextStyle::Dimmed))
+        });
+        let done = Arc::new(AtomicUsize::new(0));
+        for item in items {
+            let mut unit = ExecutionUnit::new(description(&item));
+            let (output, item, f) = (unit.output.clone(), Mutex::new(Some(item)), f.clone());
+            unit = unit.on_execute(move |status| {
+                let Some(item) = item.lock().unwrap_or_else(PoisonError::into_inner).take() else {
+                    status.finish_with(Err("the item was moved into an earlier attempt"));
+                    return;
+                };
+                let result = f(item, status.clone());
+                status.finish_with(result.as_ref().map(|_| ()));
+                *output.lock().unwrap_or_else(PoisonError::into_inner) = Some(Box::new(result));
+            });
+            if let Some(footer) = footer {
+                let (done, renderer) = (done.clone(), renderer.clone());
+                let line = self.render.clone();
+                unit = unit.on_finish(move |_status| {
+                    let done = done.fetch_add(1, Ordering::SeqCst) + 1;
+                    renderer.update(footer, line.paint(&format!("{}{}/{} done", indent, done, total), TextStyle::Dimmed));
+                });
+            }
+            self.units.push(unit);
+        }
+        self.parallel(concurrency);
+        self.continue_on_error(true);
+        // Failures end up in the outcomes.
+        let _ = self.run(1, 1);
+        if let Some(footer) = footer {
+            renderer.remove(footer);
+        }
+        self.units[first..]
+            .iter_mut()
+            .map(|unit| MapOutcome {
+                description: unit.description().to_string(),
+                status: unit.status(),
+                result: unit.take_returned(),
+            })
+            .collect()
+    }
+
+    /// Waits the [`delay_fn`](Self::delay_fn) of the group after the unit at
+    /// `idx` ran, on a dimmed line counting down, until `cancellation` fires.
+    fn wait_after(&self, idx: usize, render: &RenderSettings, cancellation: &CancellationToken) {
+        let unit = &self.units[idx];
+        let Some(delay) = &self.delay else {
+            return;
+        };
+        if unit.started_at.is_none() || cancellation.is_cancelled() {
+            return;
+        }
+        let delay = delay(unit.status());
+        if delay.is_zero() {
+            return;
+        }
+        let waiting = |left: Duration| {
+            let line = format!("{}waiting {}s{}", render.indent, left.as_secs_f64().ceil() as u64, render.ellipsis());
+            render.paint(&line, TextStyle::Dimmed)
+        };
+        render.emit(Event::new("waiting").value("group", render.group).value("delay_ms", delay.as_millis()));
+        if !render.is_live() {
+            if render.is_human() && !render.quiet {
+                render.renderer.println(&render.stamp(&waiting(delay)));
+            }
+            return wait_or_cancel(delay, cancellation);
+        }
+        let deadline = Instant::now() + delay;
+        let line = render.renderer.add(waiting(delay));
+        while !cancellation.is_cancelled() {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                break;
+            }
+            render.renderer.update(line, waiting(left));
+            thread::sleep(left.min(MAX_POLL_INTERVAL));
+        }
+        render.renderer.remove(line);
+    }
+
+    /// Leaves every unit out of the next run but the ones that failed or
+    /// timed out, and the ones skipped because a unit they depend on did.
+    /// Returns how many units are left in.
+    fn select_failed(&mut self) -> usize {
+        for unit in &mut self.units {
+            unit.reran = matches!(unit.status(), ExecutionStatus::Failed | ExecutionStatus::TimedOut);
+        }
+        // Dependents are selected after their dependencies, whatever the order of the units.
+        loop {
+            let selected: Vec<UnitId> = self.units.iter().filter(|unit| unit.reran).map(ExecutionUnit::unit_id).collect();
+            let blocked = self.units.iter_mut().filter(|unit| {
+                !unit.reran
+                    && unit.status() == ExecutionStatus::Skipped
+                    && unit.dependencies.iter().any(|dependency| selected.contains(&dependency.unit_id()))
+            });
+            let mut changed = false;
+            for unit in blocked {
+                unit.reran = true;
+                changed = true;
+            }
+            if !changed {
+                break;
+            }
+        }
+        for unit in &mut self.units {
+            unit.excluded = !unit.reran;
+        }
+        self.units.iter().filter(|unit| unit.reran).count()
+    }
+
+    /// Sets every unit back to pending, as before its first run, and drops
+    /// the ones added while it ran.
+    #[cfg(feature = "watch")]
+    fn reset(&mut self) {
+        self.units.retain(|unit| !unit.added);
+        for unit in &mut self.units {
+            unit.reset();
+        }
+    }
+
+    /// Skips the units that do not match the tags in the next run, leaving
+    /// them out altogether when `hide` is set, see [`ProgressManager::start_filtered`].
+    fn filter(&mut self, include: &[&str], exclude: &[&str], hide: bool) {
+        for unit in self.units.iter_mut().filter(|unit| !unit.matches_tags(include, exclude)) {
+            unit.filtered = true;
+            unit.excluded = hide;
+            unit.status.set(ExecutionStatus::Skipped);
+            unit.status.update_failure(|failure| failure.reason = Some("filtered out".to_string()));
+        }
+    }
+
+    /// Skips every unit of a group left out of a run, e.g. before the one
+    /// it resumed at, see [`ProgressManager::start_from`].
+    fn skip_all(&mut self, reason: &str) {
+        for unit in &mut self.units {
+            unit.status.set(ExecutionStatus::Skipped);
+            unit.status.update_failure(|failure| failure.reason = Some(reason.to_string()));
+        }
+    }
+
+    /// Puts every unit back into the runs of the group.
+    fn clear_selection(&mut self) {
+        for unit in &mut self.units {
+            unit.excluded = false;
+            unit.reran = false;
+            unit.filtered = false;
+        }
+    }
+
+    /// Every unit is left out of the run, the group is not run at all.
+    fn is_excluded(&self) -> bool {
+        !self.units.is_empty() && self.units.iter().all(|unit| unit.excluded || unit.filtered)
+    }
+
+    /// The group has no units and none can be pushed to its queue.
+    fn has_nothing_to_run(&self) -> bool {
+        self.units.is_empty() && self.queue.len() == 0 && !self.queue.has_producers()
+    }
+
+    /// Fills in the dependencies of every unit from the ids they depend on
+    /// and the stages of their pipeline, returning the order the units run in.
+    fn resolve_dependencies(&mut self) -> Result<Vec<usize>, HflowError> {
+        let mut graph = Vec::with_capacity(self.units.len());
+        for unit in &self.units {
+            let mut dependencies = Vec::new();
+            if let Some(stage) = &unit.previous_stage {
+                dependencies.extend(self.units.iter().position(|other| other.unit_id() == stage.unit_id()));
+            }
+            for id in &unit.depends_on {
+                match self.units.iter().position(|other| other.id.as_ref() == Some(id)) {
+                    Some(idx) => dependencies.push(idx),
+                    None => {
+                        return Err(HflowError::UnknownDependency {
+                            description: unit.description().to_string(),
+                            dependency: id.clone(),
+                        });
+                    }
+                }
+            }
+            graph.push(dependencies);
+        }
+
+        let order = dag::order(&graph).map_err(|cycle| HflowError::DependencyCycle {
+            cycle: cycle
+                .into_iter()
+                .map(|idx| {
+                    let unit = &self.units[idx];
+                    unit.id.clone().unwrap_or_else(|| unit.description().to_string())
+                })
+                .collect(),
+        })?;
+        let views: Vec<UnitView> = self.units.iter().map(ExecutionUnit::view).collect();
+        for (unit, dependencies) in self.units.iter_mut().zip(&graph) {
+            unit.dependencies = dependencies.iter().map(|&idx| views[idx].clone()).collect();
+        }
+        Ok(order)
+    }
+
+    /// Runs the units on up to `max_concurrency` threads, each taking the
+    /// first unit in `order` whose dependencies have all finished.
+    fn run_parallel(
+        &mut self,
+        policy: ExitPolicy,
+        siblings: &CancellationToken,
+        cancel_reason: &OnceLock<&'static str>,
+        order: &[usize],
+        dependent: bool,
+        prepare: &(dyn Fn(&mut ExecutionUnit, usize) + Sync),
+    ) -> Result<Vec<ExecutionStatus>, HflowError> {
+        let renderer = self.render.renderer.clone();
+        let group = self.cancellation.clone();
+        let max_failures = self.max_failures;
+        // Units pushed while the group runs may need more workers than the units it started with.
+        let workers = self.max_concurrency.min(order.len().max(MAX_QUEUE_WORKERS));
+        let pushed = self.queue.clone();
+        let next_idx = AtomicUsize::new(self.units.len());
+        let adopted = Mutex::new(Vec::new());
+        // Units taken by a worker and not finished yet, which may still push units.
+        let running = AtomicUsize::new(0);
+        let mut units: Vec<Option<&mut ExecutionUnit>> = self.units.iter_mut().map(Some).collect();
+        let queue: Vec<&mut ExecutionUnit> = order.iter().filter_map(|&idx| units[idx].take()).collect();
+        let queue = Mutex::new(queue);
+        // Signalled whenever a unit finishes, which may let others start.
+        let finished = Condvar::new();
+        let first_error = Mutex::new(None);
+        // Why the units left once the others were cancelled are skipped, if they are.
+        let skip_reason: Mutex<Option<String>> = Mutex::new(None);
+        let failures = AtomicUsize::new(0);
+        let run = |unit: &mut ExecutionUnit| {
+            if siblings.is_cancelled() {
+                // Cancelled from outside the group, e.g. by a shutdown, the unit is skipped.
+                if group.is_cancelled() {
+                    let _ = unit.execute();
+                } else {
+                    let reason = skip_reason.lock().unwrap_or_else(PoisonError::into_inner).clone();
+                    unit.stop(reason.as_deref());
+                }
+                return;
+            }
+            let result = unit.execute();
+            let found = unit.take_found();
+            if !found.is_empty() {
+                pushed.push_front(found);
+            }
+            let Err(err) = result else {
+                return;
+            };
+            match policy {
+                ExitPolicy::ExitProcess => {
+                    renderer.restore_cursor();
+                    std::process::exit(err.exit_code());
+                }
+                ExitPolicy::StopAndReturn => {
+                    first_error.lock().unwrap_or_else(PoisonError::into_inner).get_or_insert(err);
+                    if !dependent {
+                        skip_reason
+                            .lock()
+                            .unwrap_or_else(PoisonError::into_inner)
+                            .get_or_insert_with(|| format!("previous step failed: {}", unit.description()));
+                    }
+                    if !group.is_cancelled() {
+                        let _ = cancel_reason.set(SIBLING_FAILED);
+                    }
+                    siblings.cancel();
+                }
+                ExitPolicy::ContinueAll => {
+                    first_error.lock().unwrap_or_else(PoisonError::into_inner).get_or_insert(err);
+                    // Only the failure reaching the limit trips the breaker.
+                    if max_failures == Some(failures.fetch_add(1, Ordering::SeqCst) + 1) {
+                        *skip_reason.lock().unwrap_or_else(PoisonError::into_inner) = Some(CIRCUIT_BREAKER_TRIPPED.to_string());
+                        let _ = cancel_reason.set(CIRCUIT_BREAKER_TRIPPED);
+                        siblings.cancel();
+                    }
+                }
+            }
+        };
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| {
+                    loop {
+                        let mut waiting = queue.lock().unwrap_or_else(PoisonError::into_inner);
+                        let unit = loop {
+                            if waiting.is_empty() {
+                                break None;
+                            }
+                            if siblings.is_cancelled() {
+                                break Some(waiting.remove(0));
+                            }
+                            match waiting.iter().position(|unit| unit.is_ready()) {
+                                Some(idx) => break Some(waiting.remove(idx)),
+                                None => {
+                                    waiting = finished
+                                        .wait_timeout(waiting, MAX_POLL_INTERVAL)
+                                        .unwrap_or_else(PoisonError::into_inner)
+                                        .0;
+                                }
+                            }
+                        };
+                        if unit.is_some() {
+                            running.fetch_add(1, Ordering::SeqCst);
+                        }
+                        drop(waiting);
+                        if let Some(unit) = unit {
+                            run(unit);
+                        } else {
+                            // The units the group started with are all taken, the ones pushed meanwhile are next.
+                            let Some(mut unit) = pushed.pop(|| running.load(Ordering::SeqCst) > 0, siblings) else {
+                                break;
+                            };
+                            running.fetch_add(1, Ordering::SeqCst);
+                            unit.added = true;
+                            let idx = next_idx.fetch_add(1, Ordering::SeqCst);
+                            prepare(&mut unit, idx);
+                            run(&mut unit);
+                            adopted.lock().unwrap_or_else(PoisonError::into_inner).push((idx, unit));
+                        }
+                        running.fetch_sub(1, Ordering::SeqCst);
+                        finished.notify_all();
+                    }
+                });
+            }
+        });
+        let mut adopted = adopted.into_inner().unwrap_or_else(PoisonError::into_inner);
+        adopted.sort_by_key(|&(idx, _)| idx);
+        self.units.extend(adopted.into_iter().map(|(_, unit)| unit));
+        let failures = failures.into_inner();
+        if max_failures.is_some_and(|max| failures >= max) {
+            return Err(HflowError::CircuitBreakerTripped { failures });
+        }
+        match first_error.into_inner().unwrap_or_else(PoisonError::into_inner) {
+            Some(err) => Err(err),
+            None => Ok(self.units.iter().map(ExecutionUnit::status).collect()),
+        }
+    }
+}
+
+/// The main manager that orchestrates multiple task groups.
+pub struct ProgressManager {
+    groups: Vec<TaskGroup>,
+    cancellation: CancellationToken,
+    observers: Vec<Arc<dyn StatusObserver + Send + Sync>>,
+    exit_policy: ExitPolicy,
+    failure_mode: Option<FailureMode>,
+    spinner: Option<SpinnerStyle>,
+    progress_bar: ProgressBarStyle,
+    theme: Option<Theme>,
+    color: ColorMode,
+    ascii: Option<bool>,
+    quiet: bool,
+    append_only: bool,
+    dry_run: bool,
+    hide_filtered: bool,
+    /// Index of the group [`start_from`](Self::start_from) resumed the run at.
+    resumed_from: Option<usize>,
+    aligned_durations: bool,
+    clear_on_finish: bool,
+    bell_on_failure: bool,
+    bell_on_finish: bool,
+    handle_interrupts: bool,
+    on_interrupt: Option<CleanupHook>,
+    total_timeout: Option<Duration>,
+    on_timeout: Option<CleanupHook>,
+    shutdown: ShutdownHandle,
+    shutdown_grace: Option<Duration>,
+    stall_warning: Option<Duration>,
+    tag_timeouts: Vec<(String, Duration)>,
+    on_shutdown: Option<CleanupHook>,
+    before_all: Option<SetupHook>,
+    after_all: Option<TeardownHook>,
+    on_finish: Option<RunFinishCallback>,
+    run_summary: Option<RunSummary>,
+    /// Set when a group failed under [`ExitPolicy::ExitProcess`], the process
+    /// exits with it once the run wound down.
+    exit_code: Option<i32>,
+    escape_sequences: bool,
+    timestamps: bool,
+    timestamp_format: TimestampFormat,
+    format: Option<OutputFormat>,
+    /// The output is stdout or stderr, where the format may be detected.
+    standard_stream: bool,
+    templates: LineTemplates,
+    tick_interval: Duration,
+    width: Option<usize>,
+    context: Option<render::Context>,
+    renderer: Arc<Renderer>,
+    #[cfg(feature = "async")]
+    driver: Option<future::Driver>,
+}
+
+impl Default for ProgressManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Collects groups into a manager with the default settings, run in order.
+///
+/// ```rust
+/// # use hflow::{ExecutionUnit, ProgressManager, TaskGroup};
+/// let stages = ["Build", "Test", "Release"];
+/// let mut manager: ProgressManager = stages
+///     .iter()
+///     .map(|stage| {
+///         let mut group = TaskGroup::new_named(*stage);
+///         group.add_unit(ExecutionUnit::new(format!("Run {}", stage)).on_try_execute(|_status| Ok::<(), String>(())));
+///         group
+///     })
+///     .collect();
+/// manager.extend(std::iter::once(TaskGroup::new_named("Cleanup")));
+///
+/// assert_eq!(manager.len(), 4);
+/// # manager.writer(std::io::sink());
+/// manager.start().unwrap();
+/// ```
+impl FromIterator<TaskGroup> for ProgressManager {
+    fn from_iter<I: IntoIterator<Item = TaskGroup>>(groups: I) -> Self {
+        let mut manager = Self::new();
+        manager.extend(groups);
+        manager
+    }
+}
+
+impl Extend<TaskGroup> for ProgressManager {
+    fn extend<I: IntoIterator<Item = TaskGroup>>(&mut self, groups: I) {
+        self.groups.extend(groups);
+    }
+}
+
+impl Drop for ProgressManager {
+    fn drop(&mut self) {
+        self.renderer.restore_cursor();
+    }
+}
+
+impl ProgressManager {
+    pub fn new() -> Self {
+        let cancellation = CancellationToken::new();
+        Self {
+            groups: Vec::new(),
+            shutdown: ShutdownHandle::new(cancellation.clone()),
+            cancellation,
+            observers: Vec::new(),
+            exit_policy: ExitPolicy::default(),
+            failure_mode: None,
+            spinner: None,
+            progress_bar: ProgressBarStyle::default(),
+            theme: None,
+            color: ColorMode::default(),
+            ascii: None,
+            quiet: false,
+            append_only: false,
+            dry_run: false,
+            hide_filtered: false,
+            resumed_from: None,
+            aligned_durations: false,
+            clear_on_finish: false,
+            bell_on_failure: false,
+            bell_on_finish: false,
+            handle_interrupts: false,
+            on_interrupt: None,
+            total_timeout: None,
+            on_timeout: None,
+            shutdown_grace: None,
+            stall_warning: None,
+            tag_timeouts: Vec::new(),
+            on_shutdown: None,
+            escape_sequences: console::supports_ansi(),
+            timestamps: false,
+            timestamp_format: TimestampFormat::default(),
+            format: None,
+            standard_stream: true,
+            templates: LineTemplates::default(),
+            tick_interval: RENDER_INTERVAL,
+            width: None,
+            context: None,
+            before_all: None,
+            after_all: None,
+            on_finish: None,
+            run_summary: None,
+            exit_code: None,
+            renderer: Renderer::stdout(),
+            #[cfg(feature = "async")]
+            driver: None,
+        }
+    }
+
+    /// Starts a manager built in a chain of calls with its groups, see
+    /// [`ProgressManagerBuilder`].
+    pub fn builder() -> ProgressManagerBuilder {
+        ProgressManagerBuilder::new()
+    }
+
+    /// Spinner used by every unit that neither sets its own nor belongs to a
+    /// group that does, see [`ExecutionUnit::spinner`].
+    ///
+    /// ```rust
+    /// # use hflow::{ProgressManager, SpinnerStyle};
+    /// # use std::time::Duration;
+    /// let mut manager = ProgressManager::new();
+    /// manager.spinner(SpinnerStyle::dots());
+    /// manager.spinner(SpinnerStyle::new(["◐", "◓", "◑", "◒"], Duration::from_millis(120)));
+    /// ```
+    pub fn spinner(&mut self, style: SpinnerStyle) {
+        self.spinner = Some(style);
+    }
+
+    /// Progress bar drawn by every unit that reports progress and does not
+    /// set its own, `[#####.....]` by default.
+    pub fn progress_bar(&mut self, style: ProgressBarStyle) {
+        self.progress_bar = style;
+    }
+
+    /// Value shared with every unit, such as a configuration or a connection
+    /// pool, instead of cloning it into each closure. Units receive it as an
+    /// `Arc<C>` with [`ExecutionUnit::on_try_execute_with`] or
+    /// [`StatusHandle::context`]; [`TaskGroup::with_context`] overrides it
+    /// for the units of a group.
+    pub fn with_context<C: Send + Sync + 'static>(&mut self, context: C) {
+        self.context = Some(Arc::new(context));
+    }
+
+    /// Handle to print lines above the live output from any thread, e.g.
+    /// `handle.println("cache warmed")`, see [`ProgressHandle`].
+    ///
+    /// Take the handle after setting the [`writer`](Self::writer).
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ExecutionUnit, ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(buffer.clone());
+    ///
+    /// let handle = manager.handle();
+    /// let watcher = std::thread::spawn(move || handle.println("config reloaded"));
+    /// watcher.join().unwrap();
+    ///
+    /// let mut group = TaskGroup::new();
+    /// group.add_unit(ExecutionUnit::new("Serve".to_string()).on_try_execute(|status| {
+    ///     status.println("listening on :8080");
+    ///     Ok::<(), String>(())
+    /// }));
+    /// manager.add_group(group);
+    /// manager.start().unwrap();
+    ///
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// let lines: Vec<&str> = output.lines().collect();
+    /// assert_eq!(&lines[..3], ["config reloaded", "[1/1] Serve started", "listening on :8080"]);
+    /// assert!(lines[3].starts_with("[1/1] Serve [ok]"));
+    /// ```
+    pub fn handle(&self) -> ProgressHandle {
+        ProgressHandle::new(self.renderer.clone(), self.output_format())
+    }
+
+    /// Symbols and colors used by every unit that does not set its own theme.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{Color, ColorMode, ExecutionUnit, Marker, ProgressManager, TaskGroup, TextStyle, Theme};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// fn run(theme: Theme) -> String {
+    ///     let buffer = Buffer::default();
+    ///     let mut manager = ProgressManager::new();
+    ///     manager.writer(buffer.clone());
+    /// #     manager.ascii(false);
+    ///     manager.color(ColorMode::Always);
+    ///     manager.quiet(true);
+    ///     manager.theme(theme);
+    ///     let mut group = TaskGroup::new();
+    ///     group.add_unit(ExecutionUnit::new("Lint".to_string()).run_if(|| false));
+    ///     group.add_unit(ExecutionUnit::new("Deploy".to_string()).on_try_execute(|_status| Err("denied")));
+    ///     manager.add_group(group);
+    ///     let _ = manager.start();
+    ///     let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    ///     // Drop the elapsed time, it changes from run to run.
+    ///     output.lines().map(|line| line.split(" — ").next().unwrap().to_string()).collect::<Vec<_>>().join("\n")
+    /// }
+    ///
+    /// assert_eq!(run(Theme::default()), "\x1b[2m[1/1] Lint ↷ skipped\x1b[0m\n\x1b[31m[1/1] Deploy ✘\n    \x1b[2mdenied\x1b[0m");
+    /// assert_eq!(run(Theme::monochrome()), "[1/1] Lint ↷ skipped\n[1/1] Deploy ✘\n    \x1b[2mdenied\x1b[0m");
+    ///
+    /// let mut custom = Theme::default();
+    /// custom.failure = Marker::new("FAIL", TextStyle::Bold);
+    /// custom.counter = Some(TextStyle::Color(Color::Blue));
+    /// assert_eq!(
+    ///     run(custom),
+    ///     "\x1b[34m[1/1]\x1b[0m\x1b[2m Lint ↷ skipped\x1b[0m\n\x1b[34m[1/1]\x1b[0m\x1b[1m Deploy FAIL\n    \x1b[2mdenied\x1b[0m"
+    /// );
+    /// ```
+    pub fn theme(&mut self, theme: Theme) {
+        self.theme = Some(theme);
+    }
+
+    /// Prints ASCII characters only: selects [`Theme::ascii`] and
+    /// [`SpinnerStyle::ascii`], unless a theme or spinner was set explicitly.
+    ///
+    /// By default ASCII is used when the locale is set but not UTF-8, or on
+    /// a Windows console outside of Windows Terminal.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ColorMode, ExecutionUnit, ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(buffer.clone());
+    /// manager.color(ColorMode::Always);
+    /// manager.ascii(true);
+    ///
+    /// let mut group = TaskGroup::new_named("Release");
+    /// group.add_unit(ExecutionUnit::new("Tag".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    /// group.add_unit(ExecutionUnit::new("Upload".to_string()).run_if(|| false));
+    /// group.add_unit(ExecutionUnit::new("Announce".to_string()).on_try_execute(|_status| Err("no token")));
+    /// manager.add_group(group);
+    /// let _ = manager.start();
+    ///
+    /// let output = buffer.0.lock().unwrap().clone();
+    /// assert!(output.is_ascii());
+    /// let output = String::from_utf8(output).unwrap();
+    /// assert!(output.contains("[1/3] Tag OK - "));
+    /// assert!(output.contains("[3/3] Announce x - "));
+    /// ```
+    pub fn ascii(&mut self, enabled: bool) {
+        self.ascii = Some(enabled);
+    }
+
+    /// Draws no spinner at all, even on a terminal: each unit prints only
+    /// its final line, failure reason included, without `\r` or clear codes.
+    ///
+    /// Only the rendering changes, units run exactly as they would otherwise.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ExecutionUnit, ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(buffer.clone());
+    /// manager.quiet(true);
+    ///
+    /// let mut group = TaskGroup::new();
+    /// group.add_unit(ExecutionUnit::new("Rotate logs".to_string()).on_try_execute(|_status| {
+    ///     std::thread::sleep(std::time::Duration::from_millis(250));
+    ///     Ok::<(), String>(())
+    /// }));
+    /// group.add_unit(ExecutionUnit::new("Vacuum".to_string()).on_try_execute(|_status| Err("database is locked")));
+    /// manager.add_group(group);
+    /// assert!(manager.start().is_err());
+    ///
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// assert_eq!(output.lines().count(), 3);
+    /// assert_eq!(output.lines().nth(2), Some("    database is locked"));
+    /// assert!(!output.contains('\r') && !output.contains("\x1b[2K"));
+    /// ```
+    pub fn quiet(&mut self, enabled: bool) {
+        self.quiet = enabled;
+    }
+
+    /// Lays out the lines of every unit with `template` instead of the
+    /// default `[1/3] description spinner — elapsed`, see [`LineTemplate`]
+    /// for the placeholders.
+    ///
+    /// Final lines use the same template unless
+    /// [`success_template`](Self::success_template) or
+    /// [`failure_template`](Self::failure_template) is set; `{spinner}` is
+    /// empty on them and `{status}` holds the marker instead.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ExecutionUnit, LineTemplate, ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(buffer.clone());
+    /// manager.line_template(LineTemplate::new("{spinner}|{status:>6}|{description:<8}|{idx}/{total}|{message}|{percent:>4}|{elapsed}").unwrap());
+    ///
+    /// let mut group = TaskGroup::new();
+    /// group.add_unit(ExecutionUnit::new("Index".to_string()).on_try_execute(|status| {
+    ///     status.set_message("42 files");
+    ///     status.set_progress(1, 4);
+    ///     Ok::<(), String>(())
+    /// }));
+    /// manager.add_group(group);
+    /// manager.start().unwrap();
+    ///
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// let lines: Vec<&str> = output.lines().collect();
+    /// // Printed when the unit starts, as the buffer is not a terminal.
+    /// assert!(lines[0].starts_with("|started|Index   |1/1||    |"));
+    /// assert!(lines[1].starts_with("|  [ok]|Index   |1/1|42 files| 25%|"));
+    /// assert!(lines[1].ends_with('s'));
+    /// ```
+    pub fn line_template(&mut self, template: LineTemplate) {
+        self.templates.running = Some(template);
+    }
+
+    /// Layout of the lines of completed, skipped and warned units, see
+    /// [`line_template`](Self::line_template).
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ExecutionUnit, LineTemplate, ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(buffer.clone());
+    /// manager.quiet(true);
+    /// manager.success_template(LineTemplate::new("{{{status}}} {description}").unwrap());
+    /// manager.failure_template(LineTemplate::new("{status} {description} after {elapsed}").unwrap());
+    ///
+    /// let mut group = TaskGroup::new();
+    /// group.add_unit(ExecutionUnit::new("Lint".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    /// group.add_unit(ExecutionUnit::new("Test".to_string()).on_try_execute(|_status| Err("1 failed")));
+    /// manager.add_group(group);
+    /// assert!(manager.start().is_err());
+    ///
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// let lines: Vec<&str> = output.lines().collect();
+    /// assert_eq!(lines[0], "{[ok]} Lint");
+    /// assert!(lines[1].starts_with("[fail] Test after "));
+    /// assert_eq!(lines[2], "    1 failed");
+    /// ```
+    pub fn success_template(&mut self, template: LineTemplate) {
+        self.templates.success = Some(template);
+    }
+
+    /// Layout of the lines of failed, timed out and cancelled units, see
+    /// [`line_template`](Self::line_template). The failure reason is still
+    /// printed underneath.
+    pub fn failure_template(&mut self, template: LineTemplate) {
+        self.templates.failure = Some(template);
+    }
+
+    /// Prints one line when a unit starts and one when it finishes, without
+    /// redrawing anything in place, even on a terminal.
+    ///
+    /// This is what happens anyway when the output is not a terminal, e.g.
+    /// under CI or when piped to a file: no `\r` or clear codes end up in
+    /// the log and nothing is printed while units run.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ExecutionUnit, ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(buffer.clone());
+    /// manager.append_only(true);
+    ///
+    /// let mut group = TaskGroup::new();
+    /// group.add_unit(ExecutionUnit::new("Compile".to_string()).on_try_execute(|status| {
+    ///     status.set_progress(1, 2);
+    ///     std::thread::sleep(std::time::Duration::from_millis(250));
+    ///     Ok::<(), String>(())
+    /// }));
+    /// group.add_unit(ExecutionUnit::new("Link".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    /// manager.add_group(group);
+    /// manager.start().unwrap();
+    ///
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// assert_eq!(output.matches('\n').count(), 4);
+    /// assert!(output.ends_with('\n'));
+    /// let lines: Vec<&str> = output.lines().collect();
+    /// assert_eq!(lines[0], "[1/1] Compile started");
+    /// assert!(lines[1].starts_with("[1/1] Compile [ok]"));
+    /// assert_eq!(lines[2], "[1/1] Link started");
+    /// assert!(lines[3].starts_with("[1/1] Link [ok]"));
+    /// assert!(!output.contains('\r') && !output.contains('\x1b'));
+    /// ```
+    pub fn append_only(&mut self, enabled: bool) {
+        self.append_only = enabled;
+    }
+
+    /// Prints the plan of the run instead of running it: the group headers,
+    /// then every unit with `(dry-run) would run`, or `(dry-run) skipped`
+    /// when its [`run_if`](ExecutionUnit::run_if) predicate returns false.
+    /// No other closure is called and [`start`](Self::start) succeeds,
+    /// leaving every unit pending.
+    ///
+    /// Lines are only appended, as with [`append_only`](Self::append_only),
+    /// so the plan can be reviewed or piped.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ExecutionStatus, ExecutionUnit, ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let mut cleanup = TaskGroup::new_named("Cleanup");
+    /// cleanup.add_unit(ExecutionUnit::new("Drop old tables".to_string()).on_execute(|_status| unreachable!()));
+    /// cleanup.add_unit(ExecutionUnit::new("Vacuum".to_string()).run_if(|| false).on_execute(|_status| unreachable!()));
+    ///
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(buffer.clone());
+    /// manager.dry_run(true);
+    /// manager.add_group(cleanup);
+    /// manager.start().unwrap();
+    ///
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// assert_eq!(output, "Group 1/1: Cleanup\n  [1/2] Drop old tables (dry-run) would run\n  [2/2] Vacuum (dry-run) skipped\n");
+    /// assert_eq!(manager.groups()[0].units()[0].status(), ExecutionStatus::Pending);
+    /// ```
+    pub fn dry_run(&mut self, enabled: bool) {
+        self.dry_run = enabled;
+    }
+
+    /// Leaves the units filtered out by [`start_filtered`](Self::start_filtered)
+    /// out of the output, instead of printing a dimmed `↷ filtered out` line for each.
+    pub fn hide_filtered(&mut self, hide: bool) {
+        self.hide_filtered = hide;
+    }
+
+    /// Erases the lines of the run once it is over and prints a single
+    /// summary line instead, e.g. `✔ 12 tasks completed in 34s`, followed by
+    /// one line per named group such as `db-setup: 4 ok, 1 failed`. Lines of
+    /// failed units are printed again above the summary so they are never
+    /// lost.
+    ///
+    /// Lines that scrolled out of the terminal, or were written while a
+    /// unit was paused, cannot be erased: they are left as they are, as when
+    /// the output is not a terminal, and the summary is printed below them.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ExecutionUnit, ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.terminal_writer(buffer.clone());
+    /// manager.clear_on_finish(true);
+    /// # manager.ascii(false);
+    /// # manager.color(hflow::ColorMode::Never);
+    /// let mut group = TaskGroup::new();
+    /// group.add_unit(ExecutionUnit::new("Fetch".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    /// group.add_unit(ExecutionUnit::new("Notify".to_string()).allowed_to_fail(true).on_try_execute(|_status| Err("no webhook")));
+    /// group.add_unit(ExecutionUnit::new("Build".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    /// manager.add_group(group);
+    /// manager.start().unwrap();
+    ///
+    /// // The three lines are erased, the failure is printed again above the summary.
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// let (_, after) = output.rsplit_once("\x1b[4A\r\x1b[J").unwrap();
+    /// let lines: Vec<&str> = after.lines().collect();
+    /// assert!(lines[0].starts_with("[1/1] Notify [fail] (allowed to fail)"));
+    /// assert_eq!(lines[1], "    no webhook");
+    /// assert!(lines[2].starts_with("[ok] 2 of 3 tasks completed, 1 failed (allowed) in "));
+    /// ```
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::builder()
+    ///     .group("db-setup", |group| {
+    ///         group
+    ///             .task("Create schema", |_status| Ok::<(), String>(()))
+    ///             .task("Seed data", |_status| Err("duplicate key"))
+    ///             .task("Grant roles", |_status| Ok::<(), String>(()))
+    ///             .continue_on_error(true);
+    ///     })
+    ///     .group("app", |group| {
+    ///         group.task("Start server", |_status| Ok::<(), String>(()));
+    ///     })
+    ///     .build();
+    /// manager.terminal_writer(buffer.clone());
+    /// manager.clear_on_finish(true);
+    /// # manager.color(hflow::ColorMode::Never);
+    /// assert!(manager.start().is_err());
+    ///
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// let lines: Vec<&str> = output.lines().collect();
+    /// assert_eq!(lines[lines.len() - 2], "  db-setup: 2 ok, 1 failed");
+    /// assert_eq!(lines[lines.len() - 1], "  app: 0 ok, 1 not run");
+    /// ```
+    pub fn clear_on_finish(&mut self, enabled: bool) {
+        self.clear_on_finish = enabled;
+    }
+
+    /// Rings the terminal bell as soon as a unit fails, once per failed unit,
+    /// for runs left unattended. Units allowed to fail stay silent.
+    ///
+    /// The bell is only rung when units are redrawn in place on a terminal,
+    /// never into a file, a pipe or append-only output. It is written on its
+    /// own, outside of any colored line, so it rings without colors too.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ColorMode, ExecutionUnit, ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let run = |writer: fn(&mut ProgressManager, Buffer)| {
+    ///     let buffer = Buffer::default();
+    ///     let mut manager = ProgressManager::new();
+    ///     writer(&mut manager, buffer.clone());
+    ///     manager.color(ColorMode::Never);
+    ///     manager.bell_on_failure(true);
+    ///     let mut group = TaskGroup::new();
+    ///     group.add_unit(ExecutionUnit::new("Build".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    ///     group.add_unit(ExecutionUnit::new("Test".to_string()).on_try_execute(|_status| Err("2 failed")));
+    ///     manager.add_group(group);
+    ///     assert!(manager.start().is_err());
+    ///     let output = buffer.0.lock().unwrap().clone();
+    ///     output.iter().filter(|&&byte| byte == b'\x07').count()
+    /// };
+    ///
+    /// assert_eq!(run(|manager, buffer| manager.terminal_writer(buffer)), 1);
+    /// assert_eq!(run(|manager, buffer| manager.writer(buffer)), 0);
+    /// ```
+    pub fn bell_on_failure(&mut self, enabled: bool) {
+        self.bell_on_failure = enabled;
+    }
+
+    /// Rings the terminal bell once when the whole run is over, under the
+    /// same conditions as [`bell_on_failure`](Self::bell_on_failure).
+    pub fn bell_on_finish(&mut self, enabled: bool) {
+        self.bell_on_finish = enabled;
+    }
+
+    /// Catches Ctrl+C during the run instead of letting it kill the process
+    /// halfway through a line: the running units are cancelled, which they
+    /// see through [`StatusHandle::is_cancelled`], the remaining ones are
+    /// skipped, then the [`on_interrupt`](Self::on_interrupt) hook runs, an
+    /// `✗ interrupted` line is printed and the process exits with code 130.
+    ///
+    /// A second Ctrl+C while this is under way exits at once. Off by default.
+    ///
+    /// ```rust
+    /// # use hflow::ProgressManager;
+    /// let mut manager = ProgressManager::new();
+    /// manager.handle_interrupts(true);
+    /// manager.on_interrupt(|| {
+    ///     let _ = std::fs::remove_dir_all("target/tmp-deploy");
+    /// });
+    /// ```
+    ///
+    /// The interrupted run still ends like any other before exiting: the
+    /// [`after_all`](Self::after_all) hook, the summary of
+    /// [`clear_on_finish`](Self::clear_on_finish) or of
+    /// [`OutputFormat::JsonLines`], the bell and
+    /// [`on_finish`](Self::on_finish).
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ExecutionUnit, OutputFormat, ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// # #[cfg(unix)] {
+    /// # unsafe extern "C" { fn raise(signum: i32) -> i32; }
+    /// # const SIGINT: i32 = 2;
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(buffer.clone());
+    /// manager.format(OutputFormat::JsonLines);
+    /// manager.handle_interrupts(true);
+    /// manager.add_group(TaskGroup::new().with_unit(ExecutionUnit::new("Serve".to_string()).on_execute(|status| {
+    ///     // As if Ctrl+C was pressed.
+    ///     unsafe { raise(SIGINT) };
+    ///     while !status.is_cancelled() {}
+    /// })));
+    /// let output = buffer.clone();
+    /// manager.on_finish(move |_summary| {
+    ///     let output = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+    ///     let events: Vec<&str> = output.lines().collect();
+    ///     let interrupted = events.iter().position(|event| event.starts_with(r#"{"event":"interrupted""#)).unwrap();
+    ///     assert!(events[interrupted + 1..].iter().any(|event| event.starts_with(r#"{"event":"summary","status":"failed""#)), "{:?}", events);
+    ///     // Instead of the exit code 130 of an interrupted run.
+    ///     std::process::exit(0);
+    /// });
+    /// let _ = manager.start();
+    /// unreachable!("an interrupted run exits");
+    /// # }
+    /// ```
+    ///
+    /// Ctrl+C is only caught while a run is going: the handler in place
+    /// before is put back at the end of every run, and caught again by the
+    /// next one.
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionUnit, ProgressManager, TaskGroup};
+    /// # #[cfg(unix)] {
+    /// # unsafe extern "C" { fn signal(signum: i32, handler: usize) -> usize; }
+    /// # const SIGINT: i32 = 2;
+    /// # const SIG_IGN: usize = 1;
+    /// // The program ignores Ctrl+C before the runs.
+    /// unsafe { signal(SIGINT, SIG_IGN) };
+    ///
+    /// for _ in 0..2 {
+    ///     let mut manager = ProgressManager::new();
+    ///     manager.handle_interrupts(true);
+    ///     manager.add_group(TaskGroup::new().with_unit(ExecutionUnit::new("Build".to_string()).on_execute(|status| {
+    ///         let during = unsafe { signal(SIGINT, SIG_IGN) };
+    ///         unsafe { signal(SIGINT, during) };
+    ///         assert_ne!(during, SIG_IGN);
+    ///         status.complete();
+    ///     })));
+    ///     assert!(manager.start().is_ok());
+    ///
+    ///     assert_eq!(unsafe { signal(SIGINT, SIG_IGN) }, SIG_IGN);
+    /// }
+    /// # }
+    /// ```
+    pub fn handle_interrupts(&mut self, enabled: bool) {
+        self.handle_interrupts = enabled;
+    }
+
+    /// Cleanup to run after Ctrl+C once the units are cancelled, before the
+    /// process exits, see [`handle_interrupts`](Self::handle_interrupts).
+    pub fn on_interrupt<F>(&mut self, hook: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.on_interrupt = Some(Box::new(hook));
+    }
+
+    /// Limits how long the whole run may take, from the call to
+    /// [`start`](Self::start). Past the limit the running units time out,
+    /// as with [`ExecutionUnit::timeout`], the ones that did not start yet
+    /// are skipped, the [`on_timeout`](Self::on_timeout) hook runs and
+    /// [`HflowError::RunTimedOut`] tells how many units completed.
+    ///
+    /// Running lines show the time left once it drops under a minute.
+    ///
+    /// ```rust
+    /// # use std::time::Duration;
+    /// # use hflow::{ExecutionStatus, ExecutionUnit, HflowError, ProgressManager, TaskGroup};
+    /// let mut manager = ProgressManager::new();
+    /// # manager.writer(std::io::sink());
+    /// manager.total_timeout(Duration::from_millis(300));
+    /// manager.on_timeout(|| {
+    ///     let _ = std::fs::remove_dir_all("target/tmp-deploy");
+    /// });
+    /// let mut deploy = TaskGroup::new();
+    /// deploy.add_unit(ExecutionUnit::new("Upload".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    /// deploy.add_unit(ExecutionUnit::new("Migrate".to_string()).on_execute(|status| status.cancelled()));
+    /// deploy.add_unit(ExecutionUnit::new("Restart".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    /// manager.add_group(deploy);
+    ///
+    /// let err = manager.start().unwrap_err();
+    /// assert_eq!(err.error, HflowError::RunTimedOut { timeout: Duration::from_millis(300), completed: 1, total: 3 });
+    /// let statuses: Vec<_> = manager.groups()[0].units().iter().map(|unit| unit.status()).collect();
+    /// assert_eq!(statuses, [ExecutionStatus::Completed, ExecutionStatus::TimedOut, ExecutionStatus::Skipped]);
+    /// ```
+    pub fn total_timeout(&mut self, timeout: Duration) {
+        self.total_timeout = Some(timeout);
+    }
+
+    /// Gives the units tagged `tag` of every group a timeout, see
+    /// [`TaskGroup::tag_timeout`], which takes precedence.
+    pub fn tag_timeout(&mut self, tag: impl Into<String>, timeout: Duration) {
+        self.tag_timeouts.push((tag.into(), timeout));
+    }
+
+    /// Cleanup to run once the whole run timed out, e.g. a rollback, before
+    /// the error is returned, see [`total_timeout`](Self::total_timeout).
+    pub fn on_timeout<F>(&mut self, hook: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.on_timeout = Some(Box::new(hook));
+    }
+
+    /// Handle stopping the run from another thread, e.g. when the service
+    /// embedding it is asked to shut down.
+    ///
+    /// Once [`ShutdownHandle::request_shutdown`] is called, the running units
+    /// see [`StatusHandle::is_cancelled`] and report
+    /// [`ExecutionStatus::Cancelled`] when their closure returns, or once the
+    /// [`shutdown_grace`](Self::shutdown_grace) is over. The units left are
+    /// skipped, the [`on_shutdown`](Self::on_shutdown) hook runs and the
+    /// [`RunSummary`] returned by [`start`](Self::start) has the
+    /// [`RunOutcome::ShutdownRequested`] outcome.
+    /// The manager stays shut down: a later run skips every unit.
+    ///
+    /// ```rust
+    /// # use std::time::Duration;
+    /// # use hflow::{ExecutionStatus, ExecutionUnit, ProgressManager, RunOutcome, TaskGroup};
+    /// let mut manager = ProgressManager::new();
+    /// # manager.writer(std::io::sink());
+    /// let shutdown = manager.shutdown_handle();
+    /// let mut service = TaskGroup::new();
+    /// service.add_unit(ExecutionUnit::new("Drain queue".to_string()).on_execute(move |status| {
+    ///     shutdown.request_shutdown();
+    ///     status.cancelled();
+    /// }));
+    /// service.add_unit(ExecutionUnit::new("Compact storage".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    /// manager.add_group(service);
+    ///
+    /// assert_eq!(manager.start().unwrap().outcome(), RunOutcome::ShutdownRequested);
+    /// let statuses: Vec<_> = manager.groups()[0].units().iter().map(|unit| unit.status()).collect();
+    /// assert_eq!(statuses, [ExecutionStatus::Cancelled, ExecutionStatus::Skipped]);
+    /// ```
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        self.shutdown.clone()
+    }
+
+    /// How long the running units are given to return once a shutdown is
+    /// requested, instead of their own [`ExecutionUnit::cancel_grace`].
+    pub fn shutdown_grace(&mut self, grace: Duration) {
+        self.shutdown_grace = Some(grace);
+    }
+
+    /// Cleanup to run once a shutdown stopped the run, before
+    /// [`start`](Self::start) returns, see [`shutdown_handle`](Self::shutdown_handle).
+    pub fn on_shutdown<F>(&mut self, hook: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.on_shutdown = Some(Box::new(hook));
+    }
+
+    /// Setup to run before the first group, e.g. to acquire a lock the run
+    /// needs, on a `Setup` line of its own. When it fails the run stops
+    /// there: every group is skipped and [`start`](Self::start) returns the
+    /// error as [`HflowError::UnitFailed`].
+    ///
+    /// ```rust
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ExecutionStatus, ExecutionUnit, HflowError, ProgressManager, TaskGroup};
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(std::io::sink());
+    /// manager.before_all(|| Err("lock held by ci-runner-7"));
+    /// let released = Arc::new(Mutex::new(None));
+    /// let summary = released.clone();
+    /// manager.after_all(move |run| *summary.lock().unwrap() = Some(run.count(ExecutionStatus::Skipped)));
+    /// let mut deploy = TaskGroup::new_named("Deploy");
+    /// deploy.add_unit(ExecutionUnit::new("Push image".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    /// manager.add_group(deploy);
+    ///
+    /// assert!(matches!(manager.start().unwrap_err().error, HflowError::UnitFailed { .. }));
+    /// assert_eq!(manager.groups()[0].units()[0].status(), ExecutionStatus::Skipped);
+    /// assert_eq!(*released.lock().unwrap(), Some(1));
+    /// ```
+    pub fn before_all<F, E>(&mut self, hook: F)
+    where
+        F: FnOnce() -> Result<(), E> + Send + 'static,
+        E: Display,
+    {
+        self.before_all = Some(Box::new(move || hook().map_err(|err| err.to_string())));
+    }
+
+    /// Teardown to run after the last group, e.g. to release the lock taken
+    /// by [`before_all`](Self::before_all), on a `Teardown` line of its own.
+    /// It runs however the run ended: after a failure, a cancellation, a
+    /// shutdown, the [`total_timeout`](Self::total_timeout), Ctrl+C, or
+    /// before [`ExitPolicy::ExitProcess`] ends the process. A panic inside
+    /// it fails its line, the result of the run stays as it was.
+    pub fn after_all<F>(&mut self, hook: F)
+    where
+        F: FnOnce(&RunSummary) + Send + 'static,
+    {
+        self.after_all = Some(Box::new(hook));
+    }
+
+    /// Invoked once the first run is over, however it ended, with its
+    /// [`RunSummary`]: the wall time, how every unit of every group ended
+    /// and the first failure, e.g. to feed a report of your own. It runs
+    /// after the `on_finish` callbacks of the groups and units, after the
+    /// [`on_interrupt`](Self::on_interrupt), [`on_timeout`](Self::on_timeout)
+    /// or [`on_shutdown`](Self::on_shutdown) hook and the
+    /// [`after_all`](Self::after_all) teardown, and also before the process
+    /// exits, on Ctrl+C or under [`ExitPolicy::ExitProcess`]. The same
+    /// summary is returned by [`start`](Self::start) and kept by
+    /// [`run_summary`](Self::run_summary).
+    ///
+    /// A panic inside the callback is caught and printed, the result of the
+    /// run stays as it was.
+    ///
+    /// ```rust
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ExecutionStatus, ExecutionUnit, HflowError, ProgressManager, TaskGroup};
+    /// let report = Arc::new(Mutex::new(None));
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(std::io::sink());
+    /// let written = report.clone();
+    /// manager.on_finish(move |summary| {
+    ///     let failed = summary.error.as_ref().map(ToString::to_string);
+    ///     *written.lock().unwrap() = Some((summary.count(ExecutionStatus::Completed), summary.groups.len(), failed));
+    /// });
+    /// let mut build = TaskGroup::new_named("build");
+    /// build.add_unit(ExecutionUnit::new("Compile".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    /// let mut deploy = TaskGroup::new_named("deploy");
+    /// deploy.add_unit(ExecutionUnit::new("Upload".to_string()).on_try_execute(|_status| Err("403 Forbidden")));
+    /// manager.add_group(build);
+    /// manager.add_group(deploy);
+    ///
+    /// let err = manager.start().unwrap_err();
+    /// assert!(matches!(err.error, HflowError::UnitFailed { .. }));
+    /// assert_eq!(manager.run_summary(), Some(&*err.summary));
+    /// let (completed, groups, failed) = report.lock().unwrap().clone().unwrap();
+    /// assert_eq!((completed, groups), (1, 2));
+    /// assert!(failed.unwrap().contains("403 Forbidden"));
+    /// ```
+    pub fn on_finish<F>(&mut self, callback: F)
+    where
+        F: FnOnce(&RunSummary) + Send + 'static,
+    {
+        self.on_finish = Some(Box::new(callback));
+    }
+
+    /// Whether the terminal interprets escape sequences, for cursor movement
+    /// and colors.
+    ///
+    /// Detected when the manager is created: on Windows, virtual terminal
+    /// processing is turned on for the console, and consoles that do not
+    /// support it get append-only lines without colors, as when the output
+    /// is not a terminal. Colors forced with [`ColorMode::Always`] are kept.
+    /// Always true on other platforms.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ExecutionUnit, ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.terminal_writer(buffer.clone());
+    /// manager.escape_sequences(false);
+    /// let mut group = TaskGroup::new();
+    /// group.add_unit(ExecutionUnit::new("Restore".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    /// manager.add_group(group);
+    /// manager.start().unwrap();
+    ///
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// assert!(output.starts_with("[1/1] Restore started\n[1/1] Restore [ok]"));
+    /// assert!(!output.contains('\x1b'));
+    /// ```
+    pub fn escape_sequences(&mut self, supported: bool) {
+        self.escape_sequences = supported;
+    }
+
+    /// Lines up the durations of finished units against the right edge of
+    /// the terminal, like a table:
+    ///
+    /// ```text
+    /// [1/2] Compile workspace ✔ .......................... 41.2s
+    /// [1/2] Run tests ✘ .................................. 2m 03s
+    /// ```
+    ///
+    /// Descriptions too long for the terminal are cut with an ellipsis. When
+    /// the output is not a terminal, or its width cannot be determined, the
+    /// duration simply follows the marker.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ExecutionUnit, ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(buffer.clone());
+    /// manager.quiet(true);
+    /// manager.aligned_durations(true);
+    /// # manager.ascii(false);
+    /// let mut group = TaskGroup::new();
+    /// group.add_unit(ExecutionUnit::new("Compile workspace".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    /// manager.add_group(group);
+    /// manager.start().unwrap();
+    ///
+    /// // Not a terminal: the width is unknown.
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// assert!(output.starts_with("[1/1] Compile workspace [ok] — "));
+    /// ```
+    pub fn aligned_durations(&mut self, enabled: bool) {
+        self.aligned_durations = enabled;
+    }
+
+    /// Fits lines to `columns` instead of the width of the terminal, e.g.
+    /// for a pseudo-terminal whose size is known to the caller only.
+    ///
+    /// Lines are measured in terminal columns: CJK characters and most emoji
+    /// take two, combining marks none. Descriptions are only ever cut between
+    /// characters, before the ellipsis.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ColorMode, ExecutionUnit, ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// # fn columns(line: &str) -> usize {
+    /// #     line.chars()
+    /// #         .map(|c| match c {
+    /// #             '\u{300}'..='\u{36f}' => 0,
+    /// #             '\u{3040}'..='\u{9fff}' | '\u{1f300}'..='\u{1f6ff}' => 2,
+    /// #             _ => 1,
+    /// #         })
+    /// #         .sum()
+    /// # }
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.terminal_writer(buffer.clone());
+    /// # manager.ascii(false);
+    /// manager.color(ColorMode::Never);
+    /// manager.width(30);
+    /// manager.aligned_durations(true);
+    /// let mut group = TaskGroup::new();
+    /// for description in ["日本語のドキュメントを生成する", "Cafe\u{301} cre\u{300}me bru\u{302}le\u{301}e", "🚀 Deploy 🚀 to 🌍 production"] {
+    ///     group.add_unit(ExecutionUnit::new(description.to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    /// }
+    /// manager.add_group(group);
+    /// manager.start().unwrap();
+    ///
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// let lines: Vec<&str> = output.lines().filter(|line| line.contains("[ok]")).collect();
+    /// assert_eq!(lines.len(), 3);
+    /// // How much of a description fits depends on how long its duration is.
+    /// assert!(lines[0].starts_with("[1/1] 日本語"));
+    /// assert!(lines[1].starts_with("[1/1] Cafe\u{301} cre"));
+    /// assert!(lines[2].starts_with("[1/1] 🚀 Deploy"));
+    /// assert!(lines.iter().all(|line| line.contains("… [ok] ")));
+    /// assert!(lines.iter().all(|line| columns(line) == 29));
+    /// ```
+    ///
+    /// Running lines are cut to the width too, never in the middle of a
+    /// wide character:
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use std::time::Duration;
+    /// # use hflow::{ColorMode, ExecutionUnit, ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// # fn columns(line: &str) -> usize {
+    /// #     line.chars().map(|c| if ('\u{3040}'..='\u{9fff}').contains(&c) { 2 } else { 1 }).sum()
+    /// # }
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.terminal_writer(buffer.clone());
+    /// # manager.ascii(false);
+    /// manager.color(ColorMode::Never);
+    /// manager.width(20);
+    /// let render = ExecutionUnit::new("日本語のドキュメントを生成する".to_string()).on_try_execute(|_status| {
+    ///     std::thread::sleep(Duration::from_millis(250));
+    ///     Ok::<(), String>(())
+    /// });
+    /// manager.add_group(TaskGroup::new().with_unit(render));
+    /// manager.start().unwrap();
+    ///
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// let output = output.trim_start_matches("\x1b[?25l");
+    /// let running: Vec<&str> = output.split("\r\x1b[J").filter(|frame| !frame.contains("[ok]")).collect();
+    /// assert!(!running.is_empty());
+    /// for frame in running {
+    ///     assert!(columns(frame) <= 20, "{:?}", frame);
+    ///     assert!(frame.starts_with("[1/1] 日…") || frame.starts_with("[1/1] 日本…"), "{:?}", frame);
+    /// }
+    /// ```
+    pub fn width(&mut self, columns: usize) {
+        self.width = Some(columns).filter(|&columns| columns > 0);
+    }
+
+    /// Prefixes every permanent line, final lines and group headers, with a
+    /// timestamp in the [`timestamp_format`](Self::timestamp_format). The
+    /// live spinner line is transient and never carries one. Lines are
+    /// stamped the same way when the output is not a terminal.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ExecutionUnit, ProgressManager, TaskGroup, TimestampFormat};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// fn run(format: TimestampFormat) -> Vec<String> {
+    ///     let buffer = Buffer::default();
+    ///     let mut manager = ProgressManager::new();
+    ///     manager.writer(buffer.clone());
+    ///     manager.timestamps(true);
+    ///     manager.timestamp_format(format);
+    ///     let mut group = TaskGroup::new_named("Backup");
+    ///     group.add_unit(ExecutionUnit::new("Dump".to_string()).on_try_execute(|_status| Err("disk full")));
+    ///     manager.add_group(group);
+    ///     let _ = manager.start();
+    ///     let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    ///     output.lines().map(str::to_string).collect()
+    /// }
+    ///
+    /// let lines = run(TimestampFormat::Utc);
+    /// // 2024-05-01T13:45:07.120Z Group 1/1: Backup
+    /// let (stamp, header) = lines[0].split_once(' ').unwrap();
+    /// assert_eq!(header, "Group 1/1: Backup");
+    /// assert_eq!(stamp.len(), 24);
+    /// assert!(stamp.ends_with('Z') && &stamp[10..11] == "T");
+    /// assert_eq!(lines[1].split_once(' ').unwrap().1, "  [1/1] Dump started");
+    /// assert!(lines[2].split_once(' ').unwrap().1.starts_with("  [1/1] Dump [fail]"));
+    /// // The failure reason continues the stamped line.
+    /// assert_eq!(lines[3], "      disk full");
+    ///
+    /// let lines = run(TimestampFormat::Elapsed);
+    /// assert!(lines[0].starts_with("+0.0"));
+    /// assert!(lines[0].ends_with("s Group 1/1: Backup"));
+    /// ```
+    pub fn timestamps(&mut self, enabled: bool) {
+        self.timestamps = enabled;
+    }
+
+    /// Format of the timestamps enabled with [`timestamps`](Self::timestamps), UTC by default.
+    pub fn timestamp_format(&mut self, format: TimestampFormat) {
+        self.timestamp_format = format;
+    }
+
+    /// Whether the output is colored, see [`ColorMode`].
+    ///
+    /// By default colors are used only when the output is a terminal and
+    /// `NO_COLOR` is not set. Without colors, final lines end with plain
+    /// markers such as `[ok]` and `[fail]`; when the output is not a terminal
+    /// the spinner frames are not printed either, see [`append_only`](Self::append_only).
+    ///
+    /// ```rust
+    /// # use hflow::{ColorMode, ProgressManager};
+    /// let mut manager = ProgressManager::new();
+    /// manager.color(ColorMode::Never);
+    /// ```
+    pub fn color(&mut self, mode: ColorMode) {
+        self.color = mode;
+    }
+
+    /// How the run is reported, see [`OutputFormat`] for the default.
+    ///
+    /// [`OutputFormat::JsonLines`] reports events instead of spinners and
+    /// status lines, for a program reading the output. Every line is one JSON object whose `event` field is one of
+    /// `group_started`, `unit_started`, `unit_message`, `unit_progress`,
+    /// `unit_finished`, `group_finished`, `log` or `summary`. Unit events
+    /// carry the `group` and `unit` positions, starting at 1, and the
+    /// `description`; finished units add their `status`, `elapsed_ms` and,
+    /// for failures, the `reason`. Messages and progress are sampled by the
+    /// display loop, so quick successive updates may be reported only once.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use std::time::Duration;
+    /// # use hflow::{ExecutionUnit, OutputFormat, ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(buffer.clone());
+    /// manager.format(OutputFormat::JsonLines);
+    ///
+    /// let mut group = TaskGroup::new_named("Release");
+    /// group.add_unit(ExecutionUnit::new("Upload".to_string()).on_try_execute(|status| {
+    ///     status.set_message("v1.2.0");
+    ///     status.set_progress(3, 4);
+    ///     std::thread::sleep(Duration::from_millis(250));
+    ///     Ok::<(), String>(())
+    /// }));
+    /// group.add_unit(ExecutionUnit::new("Tag \"latest\"".to_string()).on_try_execute(|_status| Err("tag exists")));
+    /// manager.add_group(group);
+    /// assert!(manager.start().is_err());
+    ///
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// let lines: Vec<&str> = output.lines().collect();
+    /// assert_eq!(lines[0], r#"{"event":"group_started","group":1,"name":"Release","units":2}"#);
+    /// assert_eq!(lines[1], r#"{"event":"unit_started","group":1,"group_name":"Release","unit":1,"description":"Upload"}"#);
+    /// assert_eq!(lines[2], r#"{"event":"unit_message","group":1,"group_name":"Release","unit":1,"description":"Upload","message":"v1.2.0"}"#);
+    /// assert_eq!(lines[3], r#"{"event":"unit_progress","group":1,"group_name":"Release","unit":1,"description":"Upload","current":3,"total":4}"#);
+    /// assert!(lines[4].starts_with(r#"{"event":"unit_finished","group":1,"group_name":"Release","unit":1,"description":"Upload","status":"completed","elapsed_ms":"#));
+    /// assert_eq!(lines[5], r#"{"event":"unit_started","group":1,"group_name":"Release","unit":2,"description":"Tag \"latest\""}"#);
+    /// assert!(lines[6].ends_with(r#","reason":"tag exists"}"#));
+    /// assert!(lines[7].starts_with(r#"{"event":"group_finished","group":1,"name":"Release","status":"failed","elapsed_ms":"#));
+    /// assert!(lines[8].starts_with(r#"{"event":"summary","status":"failed","elapsed_ms":"#));
+    /// assert!(lines[8].ends_with(r#","completed":1,"failed":1,"skipped":0,"warning":0,"cancelled":0,"timed_out":0,"pending":0}"#));
+    /// assert_eq!(lines.len(), 9);
+    /// ```
+    ///
+    /// [`OutputFormat::GithubActions`] folds every group in the log of the
+    /// step and annotates failures; allowed failures are only warnings.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ExecutionUnit, OutputFormat, ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(buffer.clone());
+    /// manager.format(OutputFormat::GithubActions);
+    ///
+    /// let mut checks = TaskGroup::new_named("Checks");
+    /// checks.add_unit(ExecutionUnit::new("Format".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    /// checks.add_unit(ExecutionUnit::new("Audit".to_string()).allowed_to_fail(true).on_try_execute(|_status| Err("1 advisory")));
+    /// let mut tests = TaskGroup::new();
+    /// tests.add_unit(ExecutionUnit::new("Unit tests".to_string()).on_try_execute(|_status| Err("2 failed\n1 ignored")));
+    /// manager.add_group(checks);
+    /// manager.add_group(tests);
+    /// assert!(manager.start().is_err());
+    ///
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// let commands: Vec<&str> = output.lines().filter(|line| line.starts_with("::")).collect();
+    /// assert_eq!(
+    ///     commands,
+    ///     [
+    ///         "::group::Group 1/2: Checks",
+    ///         "::warning::Audit: 1 advisory",
+    ///         "::endgroup::",
+    ///         "::group::Group 2/2",
+    ///         "::error::Unit tests: 2 failed%0A1 ignored",
+    ///         "::endgroup::",
+    ///     ]
+    /// );
+    /// assert!(!output.contains('\r'));
+    /// ```
+    pub fn format(&mut self, format: OutputFormat) {
+        self.format = Some(format);
+    }
+
+    fn output_format(&self) -> OutputFormat {
+        match self.format {
+            Some(format) => format,
+            None if self.standard_stream => OutputFormat::detect(),
+            None => OutputFormat::Human,
+        }
+    }
+
+    /// Sends every line printed by the units to `writer` instead of stdout.
+    ///
+    /// Standard streams and files are checked for a terminal, any other
+    /// writer is treated as redirected output.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ColorMode, ExecutionStatus, ExecutionUnit, ProgressManager, TaskGroup};
+    /// #[derive(Clone, Default)]
+    /// struct Buffer(Arc<Mutex<Vec<u8>>>);
+    ///
+    /// impl Write for Buffer {
+    ///     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    ///         self.0.lock().unwrap().write(buf)
+    ///     }
+    ///     fn flush(&mut self) -> io::Result<()> {
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(buffer.clone());
+    /// # manager.ascii(false);
+    ///
+    /// let mut group = TaskGroup::new();
+    /// group.add_unit(ExecutionUnit::new("Build".to_string()).on_execute(|status| {
+    ///     status.complete();
+    /// }));
+    /// group.add_unit(ExecutionUnit::new("Test".to_string()).on_try_execute(|_status| Err("2 failed")));
+    /// manager.add_group(group);
+    /// assert!(manager.start().is_err());
+    ///
+    /// // Not a terminal: no spinner frames, colors or cursor movement.
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// let lines: Vec<&str> = output.lines().collect();
+    /// assert_eq!(lines.len(), 5);
+    /// assert_eq!(lines[0], "[1/1] Build started");
+    /// assert!(lines[1].starts_with("[1/1] Build [ok] — "));
+    /// assert_eq!(lines[2], "[1/1] Test started");
+    /// assert!(lines[3].starts_with("[1/1] Test [fail] — "));
+    /// assert_eq!(lines[4], "    2 failed");
+    /// assert!(!output.contains('\x1b') && !output.contains('\r'));
+    ///
+    /// // Colors can still be forced.
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(buffer.clone());
+    /// # manager.ascii(false);
+    /// manager.color(ColorMode::Always);
+    /// let mut group = TaskGroup::new();
+    /// group.add_unit(ExecutionUnit::new("Build".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    /// manager.add_group(group);
+    /// manager.start().unwrap();
+    ///
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// assert!(output.lines().nth(1).unwrap().starts_with("\x1b[32m[1/1] Build ✔ — "));
+    /// assert!(output.ends_with("\x1b[0m\n"));
+    /// ```
+    pub fn writer(&mut self, writer: impl Write + Send + 'static) {
+        let live = render::is_terminal(&writer);
+        self.standard_stream = render::is_standard_stream(&writer);
+        self.renderer = Renderer::new(writer, live);
+    }
+
+    /// Like [`writer`](Self::writer), but `writer` is always treated as a
+    /// terminal and running units are redrawn in place, e.g. for a
+    /// pseudo-terminal that is not one of the standard streams.
+    ///
+    /// The cursor is hidden while running units are drawn and shown again
+    /// once the last of them finishes, when the manager is dropped, or when
+    /// any thread panics.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use std::time::Duration;
+    /// # use hflow::{ExecutionUnit, ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.terminal_writer(buffer.clone());
+    /// let mut group = TaskGroup::new();
+    /// group.add_unit(ExecutionUnit::new("Build".to_string()).on_try_execute(|_status| {
+    ///     std::thread::sleep(Duration::from_millis(200));
+    ///     Ok::<(), String>(())
+    /// }));
+    /// manager.add_group(group);
+    /// manager.start().unwrap();
+    ///
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// assert!(output.starts_with("\x1b[?25l"));
+    /// assert!(output.ends_with("\x1b[?25h"));
+    /// ```
+    ///
+    /// Units running at the same time each get a line of a block redrawn in
+    /// place, the lines that do not fit the terminal being summed up on the
+    /// last one:
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use std::time::Duration;
+    /// # use hflow::{ColorMode, ExecutionUnit, ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// // A terminal of 4 rows, keeping one for the cursor.
+    /// unsafe { std::env::set_var("LINES", "4") };
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.terminal_writer(buffer.clone());
+    /// manager.color(ColorMode::Never);
+    /// let mut group = TaskGroup::new();
+    /// group.parallel(4);
+    /// for step in ["Lint", "Test", "Docs", "Bench"] {
+    ///     group.add_unit(ExecutionUnit::new(step.to_string()).on_try_execute(|_status| {
+    ///         std::thread::sleep(Duration::from_millis(300));
+    ///         Ok::<(), String>(())
+    ///     }));
+    /// }
+    /// manager.add_group(group);
+    /// manager.start().unwrap();
+    ///
+    /// // The cursor goes back up over both running lines to redraw them.
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// let redrawn: Vec<&str> = output.split("\x1b[2A\r\x1b[J").skip(1).collect();
+    /// assert!(redrawn.iter().any(|frame| frame.starts_with("[1/1] Lint ") && frame.contains("\n[1/1] Test ")));
+    /// assert!(output.contains("\n+2 more"));
+    /// ```
+    pub fn terminal_writer(&mut self, writer: impl Write + Send + 'static) {
+        self.standard_stream = render::is_standard_stream(&writer);
+        self.renderer = Renderer::new(writer, true);
+    }
+
+    /// How often the lines of running units are redrawn on a terminal, 50ms
+    /// by default.
+    ///
+    /// Lines are only written when their text changed since the last frame,
+    /// and final lines are printed as soon as the unit finishes, whatever the
+    /// interval.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use std::time::Duration;
+    /// # use hflow::{ColorMode, ExecutionUnit, LineTemplate, ProgressManager, SpinnerStyle, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// fn output(interval: Duration) -> String {
+    ///     let buffer = Buffer::default();
+    ///     let mut manager = ProgressManager::new();
+    ///     manager.terminal_writer(buffer.clone());
+    ///     manager.color(ColorMode::Never);
+    ///     manager.tick_interval(interval);
+    ///     // A single spinner frame and no elapsed time: the line never changes.
+    ///     manager.spinner(SpinnerStyle::new(["*"], Duration::from_secs(1)));
+    ///     manager.line_template(LineTemplate::new("{spinner} {description}").unwrap());
+    ///     let mut group = TaskGroup::new();
+    ///     group.add_unit(ExecutionUnit::new("Idle".to_string()).on_try_execute(|_status| {
+    ///         std::thread::sleep(Duration::from_millis(300));
+    ///         Ok::<(), String>(())
+    ///     }));
+    ///     manager.add_group(group);
+    ///     manager.start().unwrap();
+    ///     String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap()
+    /// }
+    ///
+    /// // Many ticks go by, but only the first frame writes the line.
+    /// let fast = output(Duration::from_millis(10));
+    /// assert_eq!(fast.matches("* Idle").count(), 1, "{:?}", fast);
+    ///
+    /// // The final line does not wait for a frame that is still far off.
+    /// let slow = output(Duration::from_secs(3600));
+    /// assert_eq!(slow, " Idle\n");
+    /// ```
+    pub fn tick_interval(&mut self, interval: Duration) {
+        self.tick_interval = interval;
+    }
+
+    /// Default [`ExecutionUnit::stall_warning`] of every unit that does not
+    /// set its own.
+    pub fn stall_warning(&mut self, window: Duration) {
+        self.stall_warning = Some(window);
+    }
+
+    /// Sets what happens when a unit fails, groups may override it.
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionStatus, ExecutionUnit, ExitPolicy, ProgressManager, TaskGroup};
+    /// fn plan(policy: ExitPolicy) -> ProgressManager {
+    ///     let mut manager = ProgressManager::new();
+    ///     manager.exit_policy(policy);
+    ///     let mut group = TaskGroup::new();
+    ///     group.add_unit(ExecutionUnit::new("Ping a".to_string()).on_try_execute(|_| Err("unreachable")));
+    ///     group.add_unit(ExecutionUnit::new("Ping b".to_string()).on_try_execute(|_| Ok::<(), &str>(())));
+    ///     manager.add_group(group);
+    ///     manager
+    /// }
+    ///
+    /// let mut stop = plan(ExitPolicy::StopAndReturn);
+    /// assert!(stop.start().is_err());
+    /// assert_eq!(stop.groups()[0].units()[1].status(), ExecutionStatus::Skipped);
+    ///
+    /// let mut all = plan(ExitPolicy::ContinueAll);
+    /// assert!(all.start().is_err());
+    /// assert!(all.groups()[0].units()[0].is_failed());
+    /// assert!(all.groups()[0].units()[1].is_completed());
+    /// ```
+    pub fn exit_policy(&mut self, policy: ExitPolicy) {
+        self.exit_policy = policy;
+    }
+
+    /// Stops at the first failure or runs everything and reports all the
+    /// failures at the end, in place of the [`exit_policy`](Self::exit_policy);
+    /// groups that set their own policy keep it.
+    ///
+    /// Under [`FailureMode::RunAll`] the run ends with a list of every unit
+    /// that failed, with its group and reason, and [`start`](Self::start)
+    /// returns them all as [`HflowError::Failures`].
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ExecutionStatus, ExecutionUnit, FailureMode, HflowError, ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// fn plan(mode: FailureMode, buffer: Buffer) -> ProgressManager {
+    ///     let mut manager = ProgressManager::new();
+    ///     manager.writer(buffer);
+    ///     manager.failure_mode(mode);
+    ///     for (name, reason) in [("Build", Some("linker not found")), ("Test", None), ("Upload", Some("bucket not found"))] {
+    ///         let mut group = TaskGroup::new_named(name);
+    ///         group.add_unit(ExecutionUnit::new(format!("{} app", name)).on_try_execute(move |_status| match reason {
+    ///             Some(reason) => Err(reason),
+    ///             None => Ok(()),
+    ///         }));
+    ///         manager.add_group(group);
+    ///     }
+    ///     manager
+    /// }
+    ///
+    /// let mut fail_fast = plan(FailureMode::FailFast, Buffer::default());
+    /// assert!(matches!(fail_fast.start().unwrap_err().error, HflowError::UnitFailed { .. }));
+    /// assert_eq!(fail_fast.groups()[2].units()[0].status(), ExecutionStatus::Pending);
+    ///
+    /// let buffer = Buffer::default();
+    /// let mut run_all = plan(FailureMode::RunAll, buffer.clone());
+    /// let HflowError::Failures(errors) = run_all.start().unwrap_err().error else { panic!() };
+    /// assert_eq!(errors.len(), 2);
+    /// assert!(run_all.groups()[1].units()[0].is_completed());
+    ///
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// assert!(output.ends_with("2 failures:\n  [fail] Build / Build app: linker not found\n  [fail] Upload / Upload app: bucket not found\n"));
+    /// ```
+    pub fn failure_mode(&mut self, mode: FailureMode) {
+        self.failure_mode = Some(mode);
+    }
+
+    /// Registers an observer notified of every status transition of every unit.
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionStatus, ExecutionUnit, ProgressManager, StatusObserver, TaskGroup, UnitId};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use std::time::SystemTime;
+    /// struct Recorder(Arc<Mutex<Vec<ExecutionStatus>>>);
+    ///
+    /// impl StatusObserver for Recorder {
+    ///     fn on_transition(&self, _: UnitId, _: ExecutionStatus, to: ExecutionStatus, _: SystemTime) {
+    ///         self.0.lock().unwrap().push(to);
+    ///     }
+    /// }
+    ///
+    /// let seen = Arc::new(Mutex::new(Vec::new()));
+    /// let mut manager = ProgressManager::new();
+    /// manager.add_observer(Box::new(Recorder(seen.clone())));
+    ///
+    /// let mut group = TaskGroup::new();
+    /// group.add_unit(ExecutionUnit::new("Build".to_string()).on_try_execute(|_| Ok::<(), String>(())));
+    /// manager.add_group(group);
+    /// manager.start().unwrap();
+    ///
+    /// assert_eq!(*seen.lock().unwrap(), [ExecutionStatus::InProgress, ExecutionStatus::Completed]);
+    /// ```
+    pub fn add_observer(&mut self, observer: Box<dyn StatusObserver + Send + Sync>) {
+        self.observers.push(Arc::from(observer));
+    }
+
+    /// Token covering the whole run, cancelling it stops every group.
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionStatus, ExecutionUnit, ProgressManager, TaskGroup};
+    /// let mut manager = ProgressManager::new();
+    /// let token = manager.cancellation_token();
+    ///
+    /// let mut group = TaskGroup::new();
+    /// group.add_unit(ExecutionUnit::new("Long task".to_string()).on_execute(move |status| {
+    ///     token.cancel();
+    ///     while !status.is_cancelled() {}
+    /// }));
+    /// group.add_unit(ExecutionUnit::new("Never runs".to_string()).on_execute(|_| unreachable!()));
+    /// manager.add_group(group);
+    ///
+    /// manager.start().unwrap();
+    /// ```
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancellation.clone()
+    }
+
+    /// Adds `group` after the groups added so far, returning the manager
+    /// to chain calls, up to [`start`](Self::start).
+    pub fn add_group(&mut self, group: TaskGroup) -> &mut Self {
+        self.groups.push(group);
+        self
+    }
+
+    /// Like [`add_group`](Self::add_group), taking and returning the
+    /// manager for one-liners. Named apart from
+    /// [`with_group`](Self::with_group), which builds the group in a closure.
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionUnit, ProgressManager, RunOutcome, TaskGroup};
+    /// let compile = ExecutionUnit::new("Compile".to_string()).on_try_execute(|_status| Ok::<(), String>(()));
+    /// let link = ExecutionUnit::new("Link".to_string()).on_try_execute(|_status| Ok::<(), String>(()));
+    /// let publish = ExecutionUnit::new("Publish".to_string()).on_try_execute(|_status| Ok::<(), String>(()));
+    ///
+    /// let outcome = ProgressManager::new()
+    ///     .with_task_group(TaskGroup::new_named("Build").with_unit(compile).with_unit(link))
+    ///     .with_task_group(TaskGroup::new().with_unit(publish))
+    ///     .start();
+    /// assert_eq!(outcome.unwrap().outcome(), RunOutcome::Finished);
+    /// ```
+    pub fn with_task_group(mut self, group: TaskGroup) -> Self {
+        self.add_group(group);
+        self
+    }
+
+    /// Adds a group named `name` whose units are added by `build`, and
+    /// returns the manager to chain calls, up to [`start`](Self::start).
+    /// The group is added even if `build` adds no units; such an empty
+    /// group is skipped without a header, and noted in the summary.
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionUnit, ProgressManager, RunOutcome};
+    /// let mut manager = ProgressManager::new();
+    /// # manager.writer(std::io::sink());
+    /// let outcome = manager
+    ///     .with_group("Build", |group| {
+    ///         group.add_unit(ExecutionUnit::new("Compile".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    ///         group.add_unit(ExecutionUnit::new("Link".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    ///     })
+    ///     .with_group("Deploy", |_group| {})
+    ///     .start();
+    ///
+    /// assert_eq!(outcome.unwrap().outcome(), RunOutcome::Finished);
+    /// assert_eq!(manager.groups().len(), 2);
+    /// assert!(manager.groups()[0].units().iter().all(|unit| unit.is_completed()));
+    /// ```
+    pub fn with_group<F>(&mut self, name: impl Into<String>, build: F) -> &mut Self
+    where
+        F: FnOnce(&mut TaskGroup),
+    {
+        let mut group = TaskGroup::new_named(name);
+        build(&mut group);
+        self.add_group(group);
+        self
+    }
+
+    pub fn groups(&self) -> &[TaskGroup] {
+        &self.groups
+    }
+
+    /// How the last run ended, as passed to [`on_finish`](Self::on_finish),
+    /// for callers that only look at what [`start`](Self::start) returned.
+    /// Unset before the first run.
+    ///
+    /// ```rust
+    /// # use std::time::Duration;
+    /// # use hflow::{ExecutionStatus, ExecutionUnit, ProgressManager, TaskGroup};
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(std::io::sink());
+    /// assert!(manager.run_summary().is_none());
+    /// let mut checks = TaskGroup::new_named("checks");
+    /// checks.add_unit(ExecutionUnit::new("Lint".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    /// checks.add_unit(ExecutionUnit::new("Audit".to_string()).allowed_to_fail(true).on_try_execute(|_status| Err("2 advisories")));
+    /// manager.add_group(checks);
+    /// manager.start().unwrap();
+    ///
+    /// let summary = manager.run_summary().unwrap();
+    /// assert!(summary.succeeded);
+    /// assert_eq!(summary.error, None);
+    /// assert_eq!(summary.count(ExecutionStatus::Failed), 1);
+    /// assert_eq!(summary.groups[0].name.as_deref(), Some("checks"));
+    /// assert_eq!(summary.groups[0].units[1].failure_reason.as_deref(), Some("2 advisories"));
+    /// ```
+    pub fn run_summary(&self) -> Option<&RunSummary> {
+        self.run_summary.as_ref()
+    }
+
+    /// Number of groups added so far.
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// Starts the execution of all registered task groups.
+    ///
+    /// Returns the [`RunSummary`] of the run, the one
+    /// [`on_finish`](Self::on_finish) receives, or a [`RunError`] with the
+    /// first error along with that summary. Failures are handled according
+    /// to the [`ExitPolicy`]; unless it is [`ExitPolicy::ExitProcess`], the
+    /// process is never terminated. Under it, the run winds down as with
+    /// [`ExitPolicy::StopAndReturn`] and the process exits once
+    /// [`after_all`](Self::after_all) and `on_finish` have run.
+    ///
+    /// ```rust
+    /// # use hflow::{ExecutionStatus, ExecutionUnit, HflowError, ProgressManager, RunOutcome, TaskGroup};
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(std::io::sink());
+    /// let mut deploy = TaskGroup::new();
+    /// deploy.add_unit(ExecutionUnit::new("Push image".to_string()).on_try_execute(|_status| Err("registry down")));
+    /// manager.add_group(deploy);
+    ///
+    /// // The failure comes back as an error, the process keeps running.
+    /// let err = manager.start().unwrap_err();
+    /// assert!(matches!(err.error, HflowError::UnitFailed { .. }));
+    /// assert!(!err.summary.succeeded);
+    /// assert_eq!(err.summary.count(ExecutionStatus::Failed), 1);
+    /// assert_eq!(manager.groups()[0].units()[0].status(), ExecutionStatus::Failed);
+    ///
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(std::io::sink());
+    /// let mut build = TaskGroup::new();
+    /// build.add_unit(ExecutionUnit::new("Compile".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    /// manager.add_group(build);
+    ///
+    /// let summary = manager.start().unwrap();
+    /// assert!(summary.succeeded);
+    /// assert_eq!(summary.outcome(), RunOutcome::Finished);
+    /// assert_eq!(summary.statuses, [ExecutionStatus::Completed]);
+    /// ```
+    pub fn start(&mut self) -> Result<RunSummary, RunError> {
+        self.run(1)
+    }
+
+    /// Like [`start`](Self::start), but runs up to `max_groups` groups at the
+    /// same time, e.g. a frontend and a backend build that do not depend on
+    /// each other. The units of each group still run as the group says, one
+    /// after another unless it is [`parallel`](TaskGroup::parallel).
+    ///
+    /// Running units of every group share the live block. Groups are not
+    /// printed under a header, the lines of the units of a named group start
+    /// with its name instead. When a group fails and its [`ExitPolicy`] is
+    /// not [`ExitPolicy::ContinueAll`], the groups still running are
+    /// cancelled and no other is started. The run only ends once every
+    /// started group has finished.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use std::sync::atomic::{AtomicUsize, Ordering};
+    /// # use std::time::Duration;
+    /// # use hflow::{ExecutionUnit, ProgressManager, StatusHandle, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// // Counts the units running at once and keeps the highest count.
+    /// let running = Arc::new([AtomicUsize::new(0), AtomicUsize::new(0)]);
+    /// let slow = |running: &Arc<[AtomicUsize; 2]>| {
+    ///     let running = running.clone();
+    ///     move |_status: StatusHandle| {
+    ///         let now = running[0].fetch_add(1, Ordering::SeqCst) + 1;
+    ///         running[1].fetch_max(now, Ordering::SeqCst);
+    ///         std::thread::sleep(Duration::from_millis(200));
+    ///         running[0].fetch_sub(1, Ordering::SeqCst);
+    ///         Ok::<(), String>(())
+    ///     }
+    /// };
+    /// let mut frontend = TaskGroup::new_named("Frontend");
+    /// frontend.add_unit(ExecutionUnit::new("Bundle".to_string()).on_try_execute(slow(&running)));
+    /// frontend.add_unit(ExecutionUnit::new("Minify".to_string()).on_try_execute(slow(&running)));
+    /// let mut backend = TaskGroup::new_named("Backend");
+    /// backend.add_unit(ExecutionUnit::new("Compile".to_string()).on_try_execute(slow(&running)));
+    /// backend.add_unit(ExecutionUnit::new("Link".to_string()).on_try_execute(slow(&running)));
+    ///
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(buffer.clone());
+    /// # manager.ascii(false);
+    /// manager.add_group(frontend);
+    /// manager.add_group(backend);
+    /// manager.start_parallel(2).unwrap();
+    /// // One unit of each group at a time.
+    /// assert_eq!(running[1].load(Ordering::SeqCst), 2);
+    ///
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// assert!(!output.contains("Group 1/2"));
+    /// assert!(output.lines().any(|line| line.starts_with("Frontend: [2/2] Minify [ok]")));
+    /// assert!(output.lines().any(|line| line.starts_with("Backend: [2/2] Link [ok]")));
+    /// ```
+    pub fn start_parallel(&mut self, max_groups: usize) -> Result<RunSummary, RunError> {
+        self.run(max_groups.max(1))
+    }
+
+    /// Like [`start`](Self::start), for async code: the run goes on in the
+    /// background while the returned future polls the futures of the units
+    /// set with [`ExecutionUnit::on_execute_async`], so they run on the
+    /// runtime of the caller.
+    ///
+    /// The groups are moved out of the manager until the future completes.
+    /// Dropping the future early cancels the run and leaves the manager
+    /// without groups.
+    ///
+    /// Only available with the `async` feature.
+    ///
+    /// ```rust
+    /// # use std::future::Future;
+    /// # use std::pin::pin;
+    /// # use std::sync::Arc;
+    /// # use std::sync::atomic::{AtomicBool, Ordering};
+    /// # use std::task::{Context, Poll, Wake, Waker};
+    /// # use std::thread::{self, Thread};
+    /// # use std::time::Duration;
+    /// # use hflow::{ExecutionStatus, ExecutionUnit, ProgressManager, TaskGroup};
+    /// # struct Unpark(Thread);
+    /// # impl Wake for Unpark {
+    /// #     fn wake(self: Arc<Self>) { self.0.unpark(); }
+    /// # }
+    /// # fn block_on<F: Future>(future: F) -> F::Output {
+    /// #     let waker = Waker::from(Arc::new(Unpark(thread::current())));
+    /// #     let mut future = pin!(future);
+    /// #     loop {
+    /// #         if let Poll::Ready(output) = future.as_mut().poll(&mut Context::from_waker(&waker)) {
+    /// #             return output;
+    /// #         }
+    /// #         thread::park();
+    /// #     }
+    /// # }
+    /// // Cancelling the unit drops its future.
+    /// struct SetOnDrop(Arc<AtomicBool>);
+    /// impl Drop for SetOnDrop {
+    ///     fn drop(&mut self) {
+    ///         self.0.store(true, Ordering::SeqCst);
+    ///     }
+    /// }
+    /// let dropped = Arc::new(AtomicBool::new(false));
+    /// let flag = dropped.clone();
+    ///
+    /// let mut group = TaskGroup::new();
+    /// group.add_unit(ExecutionUnit::new("Wait for peer".to_string()).on_execute_async(move |_status| {
+    ///     let guard = SetOnDrop(flag.clone());
+    ///     async move {
+    ///         std::future::pending::<()>().await;
+    ///         drop(guard);
+    ///     }
+    /// }));
+    /// let mut manager = ProgressManager::new();
+    /// manager.add_group(group);
+    /// let token = manager.cancellation_token();
+    /// thread::spawn(move || {
+    ///     thread::sleep(Duration::from_millis(100));
+    ///     token.cancel();
+    /// });
+    ///
+    /// block_on(manager.start_async()).unwrap();
+    /// assert_eq!(manager.groups()[0].units()[0].status(), ExecutionStatus::Cancelled);
+    /// assert!(dropped.load(Ordering::SeqCst));
+    /// ```
+    #[cfg(feature = "async")]
+    pub fn start_async(&mut self) -> impl Future<Output = Result<RunSummary, RunError>> + Send + '_ {
+        let cancellation = self.cancellation.clone();
+        future::drive(self, ProgressManager::new(), cancellation, |manager, driver| {
+            manager.driver = Some(driver);
+            let result = manager.start();
+            manager.driver = None;
+            result
+        })
+    }
+
+    /// Runs again, with the same settings, only the units that failed or
+    /// timed out in the last run, along with the units skipped because one
+    /// of them failed. The others keep their status and are not printed
+    /// again, a group left with nothing to run is left out as a whole.
+    ///
+    /// Meant for flaky steps, such as integration tests, that deserve a
+    /// second chance once everything else has run. The summary of
+    /// [`clear_on_finish`](Self::clear_on_finish) counts the units that
+    /// completed this time as recovered on retry, the `summary` event as `recovered`.
+    /// `on_success` and `on_failure` callbacks only ever run once, they are
+    /// not invoked again.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::atomic::{AtomicU32, Ordering};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ExecutionStatus, ExecutionUnit, ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(buffer.clone());
+    /// let mut tests = TaskGroup::new();
+    /// tests.continue_on_error(true);
+    /// let attempts = Arc::new(AtomicU32::new(0));
+    /// let counter = attempts.clone();
+    /// tests.add_unit(ExecutionUnit::new("Test login".to_string()).on_try_execute(move |_status| {
+    ///     match counter.fetch_add(1, Ordering::SeqCst) {
+    ///         0 => Err("connection reset"),
+    ///         _ => Ok(()),
+    ///     }
+    /// }));
+    /// tests.add_unit(ExecutionUnit::new("Test search".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    /// manager.add_group(tests);
+    ///
+    /// assert!(manager.start().is_err());
+    /// manager.rerun_failed().unwrap();
+    /// assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    ///
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// let search = output.lines().filter(|line| line.contains("Test search")).count();
+    /// assert_eq!(search, 2, "started and finished once, not run again");
+    /// assert!(output.lines().last().unwrap().starts_with("[1/1] Test login [ok]"));
+    /// ```
+    pub fn rerun_failed(&mut self) -> Result<RunSummary, RunError> {
+        let selected: usize = self.groups.iter_mut().map(TaskGroup::select_failed).sum();
+        let result = if selected == 0 { Ok(self.summarize(Instant::now(), None, false)) } else { self.run(1) };
+        for group in &mut self.groups {
+            group.clear_selection();
+        }
+        result
+    }
+
+    /// Runs the plan, then runs it again from the start every time a file
+    /// under `paths` is created, modified or removed, like `cargo watch`.
+    ///
+    /// The paths are polled for changes, directories recursively, leaving
+    /// out hidden entries and the `target` directories inside them. Changes
+    /// are debounced so that saving several files starts a single run,
+    /// announced with a `— change detected in src/lib.rs, re-running —` line.
+    /// Every unit is set back to pending before the run: a failure only
+    /// affects the run it happened in, and
+    /// [`rerun_failed`](Self::rerun_failed) does not carry over.
+    ///
+    /// Ctrl+C, or a [`ShutdownHandle`], stops the run in progress if there is
+    /// one and ends the watch, which then returns what the last run
+    /// returned: its [`RunError`] if it failed, otherwise its summary with
+    /// the [`RunOutcome::ShutdownRequested`] outcome. A second Ctrl+C exits
+    /// at once.
+    /// [`handle_interrupts`](Self::handle_interrupts) is ignored meanwhile.
+    /// A failing run does not end the watch, unless the [`ExitPolicy`]
+    /// exits the process.
+    ///
+    /// ```rust,no_run
+    /// # use std::path::PathBuf;
+    /// # use std::process::Command;
+    /// # use hflow::{ExecutionUnit, ProgressManager, TaskGroup};
+    /// let mut manager = ProgressManager::new();
+    /// let mut build = TaskGroup::new();
+    /// build.add_unit(ExecutionUnit::new("Test".to_string()).on_try_execute(|_status| {
+    ///     let status = Command::new("cargo").arg("test").status().map_err(|error| error.to_string())?;
+    ///     if status.success() { Ok(()) } else { Err(format!("cargo test: {}", status)) }
+    /// }));
+    /// manager.add_group(build);
+    /// manager.watch(&[PathBuf::from("src"), PathBuf::from("Cargo.toml")]).unwrap();
+    /// ```
+    #[cfg(feature = "watch")]
+    pub fn watch(&mut self, paths: &[std::path::PathBuf]) -> Result<RunSummary, RunError> {
+        let _installed = signal::install();
+        let finished = CancellationToken::new();
+        let interrupts = {
+            let (finished, shutdown) = (finished.clone(), self.shutdown.clone());
+            thread::spawn(move || {
+                while !finished.is_cancelled() {
+                    if signal::interrupted() {
+                        shutdown.request_shutdown();
+                        break;
+                    }
+                    thread::sleep(MAX_POLL_INTERVAL);
+                }
+            })
+        };
+        let handle_interrupts = std::mem::replace(&mut self.handle_interrupts, false);
+
+        let mut result;
+        loop {
+            let mut snapshot = watch::Snapshot::take(paths);
+            result = self.start();
+            if self.shutdown.is_requested() {
+                break;
+            }
+            let Some(changed) = self.wait_for_change(paths, &mut snapshot) else {
+                if let Some(hook) = self.on_shutdown.take() {
+                    hook();
+                }
+                break;
+            };
+            self.announce_change(&changed);
+            for group in &mut self.groups {
+                group.reset();
+            }
+        }
+
+        self.handle_interrupts = handle_interrupts;
+        finished.cancel();
+        let _ = interrupts.join();
+        result.map(|summary| RunSummary { shutdown_requested: true, ..summary })
+    }
+
+    /// Blocks until a file under `paths` changed since `snapshot` and then
+    /// stayed unchanged for a moment, returning it, or until a shutdown is requested.
+    #[cfg(feature = "watch")]
+    fn wait_for_change(&self, paths: &[std::path::PathBuf], snapshot: &mut watch::Snapshot) -> Option<std::path::PathBuf> {
+        let changed = loop {
+            if self.shutdown.is_requested() {
+                return None;
+            }
+            thread::sleep(watch::POLL_INTERVAL);
+            let current = watch::Snapshot::take(paths);
+            if let Some(changed) = current.changed_since(snapshot) {
+                *snapshot = current;
+                break changed;
+            }
+        };
+        loop {
+            thread::sleep(watch::DEBOUNCE);
+            let current = watch::Snapshot::take(paths);
+            if current == *snapshot {
+                return Some(changed);
+            }
+            *snapshot = current;
+        }
+    }
+
+    /// Separates the next run of [`watch`](Self::watch) from the last one.
+    #[cfg(feature = "watch")]
+    fn announce_change(&self, changed: &std::path::Path) {
+        let current_dir = std::env::current_dir().unwrap_or_default();
+        let relative = changed.strip_prefix(".").or_else(|_| changed.strip_prefix(current_dir));
+        let path = relative.unwrap_or(changed).display().to_string();
+        if self.output_format() == OutputFormat::JsonLines {
+            self.renderer.println(&Event::new("change_detected").text("path", &path).into_line());
+        } else {
+            let dash = if self.ascii.unwrap_or_else(render::prefers_ascii) { "-" } else { "—" };
+            self.renderer.println(&format!("{} change detected in {}, re-running {}", dash, path, dash));
+        }
+    }
+
+    /// Like [`start`](Self::start), but only runs the units with one of the
+    /// tags in `include`, or any unit when it is empty, and none of the tags
+    /// in `exclude`: a unit matching both is left out.
+    ///
+    /// The others are skipped, with a dimmed `↷ filtered out` line in
+    /// their place unless [`hide_filtered`](Self::hide_filtered) is set. A
+    /// group left with nothing to run is skipped as a whole, header included.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ExecutionStatus, ExecutionUnit, ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(buffer.clone());
+    /// let mut db = TaskGroup::new_named("Database");
+    /// db.add_unit(ExecutionUnit::new("Migrate".to_string()).tag("db").on_try_execute(|_status| Ok::<(), String>(())));
+    /// db.add_unit(ExecutionUnit::new("Load fixtures".to_string()).tag("db").tag("slow").on_try_execute(|_status| Ok::<(), String>(())));
+    /// db.add_unit(ExecutionUnit::new("Lint".to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    /// let mut api = TaskGroup::new_named("API");
+    /// api.add_unit(ExecutionUnit::new("Serve".to_string()).tag("api").on_try_execute(|_status| Ok::<(), String>(())));
+    /// manager.add_group(db);
+    /// manager.add_group(api);
+    ///
+    /// manager.start_filtered(&["db"], &["slow"]).unwrap();
+    ///
+    /// let statuses: Vec<_> = manager.groups()[0].units().iter().map(|unit| unit.status()).collect();
+    /// assert_eq!(statuses, [ExecutionStatus::Completed, ExecutionStatus::Skipped, ExecutionStatus::Skipped]);
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// let lines: Vec<&str> = output.lines().collect();
+    /// assert_eq!(lines[0], "Group 1/2: Database");
+    /// assert!(lines[2].starts_with("  [1/3] Migrate [ok]"));
+    /// assert_eq!(lines[3], "  [2/3] Load fixtures [skipped] filtered out");
+    /// assert_eq!(lines[4], "  [3/3] Lint [skipped] filtered out");
+    /// assert_eq!(lines.len(), 5, "the API group is left out");
+    /// ```
+    pub fn start_filtered(&mut self, include: &[&str], exclude: &[&str]) -> Result<RunSummary, RunError> {
+        for group in &mut self.groups {
+            group.filter(include, exclude, self.hide_filtered);
+        }
+        let result = self.run(1);
+        for group in &mut self.groups {
+            group.clear_selection();
+        }
+        result
+    }
+
+    /// Like [`start`](Self::start), but starts at the group at `group_index`,
+    /// counting from 0 as in [`groups`](Self::groups), e.g. to resume a run
+    /// once the cause of its failure in a later group is fixed without
+    /// running the slow groups before it again.
+    ///
+    /// The earlier groups are printed as `Group 1/6: Build ↷ skipped (resume)`,
+    /// so that the numbering stays the same, and their units are
+    /// [`ExecutionStatus::Skipped`]. The summary of
+    /// [`clear_on_finish`](Self::clear_on_finish) says the run was resumed,
+    /// the `summary` event has a `resumed_from` field. An index past the
+    /// last group returns [`HflowError::GroupOutOfRange`] without running anything.
+    ///
+    /// ```rust
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// # use hflow::{ExecutionStatus, ExecutionUnit, HflowError, ProgressManager, TaskGroup};
+    /// # #[derive(Clone, Default)]
+    /// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+    /// # impl Write for Buffer {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+    /// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// # }
+    /// let buffer = Buffer::default();
+    /// let mut manager = ProgressManager::new();
+    /// manager.writer(buffer.clone());
+    /// for name in ["Download", "Build", "Deploy"] {
+    ///     let mut group = TaskGroup::new_named(name);
+    ///     group.add_unit(ExecutionUnit::new(name.to_string()).on_try_execute(|_status| Ok::<(), String>(())));
+    ///     manager.add_group(group);
+    /// }
+    ///
+    /// assert_eq!(manager.start_from(3).unwrap_err().error, HflowError::GroupOutOfRange { index: 3, groups: 3 });
+    /// manager.start_from_group("Deploy").unwrap();
+    ///
+    /// let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// let lines: Vec<&str> = output.lines().collect();
+    /// assert_eq!(lines[0], "Group 1/3: Download [skipped] (resume)");
+    /// assert_eq!(lines[1], "Group 2/3: Build [skipped] (resume)");
+    /// assert_eq!(lines[2], "Group 3/3: Deploy");
+    /// assert_eq!(manager.groups()[0].units()[0].status(), ExecutionStatus::Skipped);
+    /// assert_eq!(manager.groups()[2].units()[0].status(), ExecutionStatus::Completed);
+    /// ```
+    pub fn start_from(&mut self, group_index: usize) -> Result<RunSummary, RunError> {
+        if group_index >= self.groups.len() {
+            return Err(self.refuse(HflowError::GroupOutOfRange {
+                index: group_index,
+                groups: self.groups.len(),
+            }));
+        }
+        self.resumed_from = Some(group_index);
+        let result = self.run(1);
+        self.resumed_from = None;
+        result
+    }
+
+    /// Like [`start_from`](Self::start_from), starting at the first group
+    /// named `name`, or returning [`HflowError::UnknownGroup`] when there is none.
+    pub fn start_from_group(&mut self, name: &str) -> Result<RunSummary, RunError> {
+        match self.groups.iter().position(|group| group.name() == Some(name)) {
+            Some(index) => self.start_from(index),
+            None => Err(self.refuse(HflowError::UnknownGroup { name: name.to_string() })),
+        }
+    }
+
+    /// Runs the groups, up to `max_groups` at the same time.
+    fn run(&mut self, max_groups: usize) -> Result<RunSummary, RunError> {
+        let observers: Observers = Arc::new(self.observers.clone());
+        let ascii = self.ascii.unwrap_or_else(render::prefers_ascii);
+        let (default_spinner, default_theme) = if ascii {
+            (SpinnerStyle::ascii(), Theme::ascii())
+        } else {
+            (SpinnerStyle::default(), Theme::default())
+        };
+        let mut render = RenderSettings::new(
+            self.renderer.clone(),
+            self.spinner.clone().unwrap_or(default_spinner),
+            self.theme.clone().unwrap_or(default_theme),
+            self.color,
+            ascii,
+        );
+        render.progress_bar = self.progress_bar.clone();
+        render.context = self.context.clone();
+        render.quiet = self.quiet;
+        render.append_only = self.append_only || self.dry_run;
+        render.dry_run = self.dry_run;
+        render.aligned_durations = self.aligned_durations;
+        render.bell_on_failure = self.bell_on_failure;
+        if !self.escape_sequences {
+            render.append_only = true;
+            render.colors &= self.color == ColorMode::Always;
+        }
+        render.timestamps = self.timestamps.then_some(self.timestamp_format);
+        render.deadline = self.total_timeout.map(|timeout| render.started + timeout);
+        render.shutdown = Some(self.shutdown.clone());
+        render.shutdown_grace = self.shutdown_grace;
+        render.stall_warning = self.stall_warning;
+        render.tag_timeouts = self.tag_timeouts.clone();
+        render.format = self.output_format();
+        render.templates = self.templates.clone();
+        render.concurrent_groups = max_groups > 1;
+        render.defer_exit = true;
+        #[cfg(feature = "async")]
+        {
+            render.driver = self.driver.clone();
+        }
+        self.renderer.set_interval(self.tick_interval);
+        self.renderer.set_width(self.width);
+        self.warn_duplicate_names(&render);
+        let clear = self.clear_on_finish && render.is_human();
+        if clear {
+            render.renderer.start_transcript();
+        }
+        let finished = CancellationToken::new();
+        let watchdog = render.deadline.map(|deadline| watch_deadline(deadline, self.cancellation.clone(), finished.clone()));
+        // Ctrl+C stays caught until the run has wound down, and the previous
+        // handler is put back however this returns.
+        let installed = self.handle_interrupts.then(signal::install);
+        let mut result = match self.set_up(&render) {
+            Err(err) => Err(err),
+            Ok(()) if self.handle_interrupts => self.run_interruptible(&render, observers, max_groups),
+            Ok(()) => self.run_groups(&render, observers, max_groups),
+        };
+        finished.cancel();
+        if let Some(watchdog) = watchdog {
+            let _ = watchdog.join();
+        }
+        if result.is_err() && render.past_deadline() {
+            result = Err(self.timed_out(&render));
+        } else if self.shutdown.is_requested() {
+            self.shut_down(&render);
+        }
+        self.tear_down(&render, result.as_ref().err());
+        let interrupted = installed.is_some() && signal::interrupted();
+        let succeeded = result.is_ok() && !interrupted;
+        if clear {
+            let summary = self.summary_line(&render, succeeded, render.started.elapsed());
+            render.renderer.erase_transcript(&render.stamp(&summary));
+        }
+        if !render.is_human() {
+            render.emit(self.summary(succeeded, render.started.elapsed()));
+        }
+        let result = match result {
+            Err(HflowError::RunTimedOut { .. }) => result,
+            Err(_) if self.failure_mode == Some(FailureMode::RunAll) => self.report_failures(&render, result),
+            result => result,
+        };
+        if self.bell_on_finish {
+            render.bell();
+        }
+        let summary = self.finish(&render, result.as_ref().err());
+        if interrupted {
+            self.renderer.restore_cursor();
+            std::process::exit(signal::INTERRUPTED_EXIT_CODE);
+        }
+        if let Some(code) = self.exit_code.take() {
+            self.renderer.restore_cursor();
+            std::process::exit(code);
+        }
+        match result {
+            Ok(()) => Ok(summary),
+            Err(error) => Err(RunError { error, summary: Box::new(summary) }),
+        }
+    }
+
+    /// How the run started at `started` ended, `error` being what it returns, if anything.
+    fn summarize(&self, started: Instant, error: Option<&HflowError>, interrupted: bool) -> RunSummary {
+        let groups: Vec<GroupResult> = self.groups.iter().map(TaskGroup::outcome).collect();
+        let succeeded = error.is_none() && !interrupted;
+        let first_failure = groups.iter().find_map(|group| group.error.clone());
+        RunSummary {
+            succeeded,
+            elapsed: started.elapsed(),
+            statuses: self.groups.iter().flat_map(TaskGroup::units).map(ExecutionUnit::status).collect(),
+            groups,
+            error: if succeeded { None } else { first_failure.or_else(|| error.cloned()) },
+            shutdown_requested: self.shutdown.is_requested(),
+        }
+    }
+
+    /// Fails without running anything, e.g. when the group to resume from does not exist.
+    fn refuse(&self, error: HflowError) -> RunError {
+        let mut summary = self.summarize(Instant::now(), Some(&error), false);
+        summary.error = Some(error.clone());
+        RunError { error, summary: Box::new(summary) }
+    }
+
+    /// Keeps the [`RunSummary`] of the run and hands it to the
+    /// [`on_finish`](Self::on_finish) callback.
+    fn finish(&mut self, render: &RenderSettings, error: Option<&HflowError>) -> RunSummary {
+        let summary = self.summarize(render.started, error, signal::interrupted());
+        if let Some(callback) = self.on_finish.take() {
+            run_finish_callback(render, "on_finish", || callback(&summary));
+        }
+        self.run_summary = Some(summary.clone());
+        summary
+    }
+
+    /// Runs the [`before_all`](Self::before_all) hook on a line of its own,
+    /// skipping every group when it fails.
+    fn set_up(&mut self, render: &RenderSettings) -> Result<(), HflowError> {
+        let Some(hook) = self.before_all.take() else {
+            return Ok(());
+        };
+        let hook = Mutex::new(Some(hook));
+        let mut unit = ExecutionUnit::new("Setup".to_string()).on_try_execute(move |_status| {
+            match hook.lock().unwrap_or_else(PoisonError::into_inner).take() {
+                Some(hook) => hook(),
+                None => Ok(()),
+            }
+        });
+        unit.inherit_render(render.clone());
+        let result = unit.execute().map(|_| ());
+        if result.is_err() {
+            let total = self.groups.len();
+            for (idx, group) in self.groups.iter_mut().enumerate() {
+                Self::skip_group(render, group, idx, total, "before_all failed");
+            }
+        }
+        result
+    }
+
+    /// Runs the [`after_all`](Self::after_all) hook on a line of its own,
+    /// past the time limit of the run or a shutdown alike.
+    fn tear_down(&mut self, render: &RenderSettings, error: Option<&HflowError>) {
+        let Some(hook) = self.after_all.take() else {
+            return;
+        };
+        let summary = self.summarize(render.started, error, signal::interrupted());
+        let hook = Mutex::new(Some(hook));
+        let mut unit = ExecutionUnit::new("Teardown".to_string()).on_try_execute(move |_status| {
+            if let Some(hook) = hook.lock().unwrap_or_else(PoisonError::into_inner).take() {
+                hook(&summary);
+            }
+            Ok::<(), String>(())
+        });
+        let mut render = render.clone();
+        render.deadline = None;
+        unit.inherit_render(render);
+        let _ = unit.execute();
+    }
+
+    /// Runs the [`on_shutdown`](Self::on_shutdown) hook once the run was
+    /// stopped by a [`ShutdownHandle`] and says so.
+    fn shut_down(&mut self, render: &RenderSettings) {
+        if let Some(hook) = self.on_shutdown.take() {
+            hook();
+        }
+        if render.is_human() {
+            let cancelled = &render.theme.cancelled;
+            let line = format!("{} (shutdown requested)", render.marker(cancelled, "[cancelled]"));
+            render.renderer.println(&render.stamp(&render.paint(&line, cancelled.style)));
+        } else {
+            render.emit(Event::new("shutdown"));
+        }
+    }
+
+    /// [`run_groups`](Self::run_groups), cancelling the run on Ctrl+C and
+    /// saying so; [`run`](Self::run) exits once the run has wound down.
+    fn run_interruptible(&mut self, render: &RenderSettings, observers: Observers, max_groups: usize) -> Result<(), HflowError> {
+        let finished = CancellationToken::new();
+        let watcher = {
+            let (finished, cancellation) = (finished.clone(), self.cancellation.clone());
+            thread::spawn(move || {
+                while !finished.is_cancelled() {
+                    if signal::interrupted() {
+                        cancellation.cancel();
+                        break;
+                    }
+                    thread::sleep(MAX_POLL_INTERVAL);
+                }
+            })
+        };
+        let result = self.run_groups(render, observers, max_groups);
+        finished.cancel();
+        let _ = watcher.join();
+        if signal::interrupted() {
+            if let Some(hook) = self.on_interrupt.take() {
+                hook();
+            }
+            if render.is_human() {
+                let failure = &render.theme.failure;
+                let line = format!("{} interrupted", render.marker(failure, "[fail]"));
+                render.renderer.println(&render.stamp(&render.paint(&line, failure.style)));
+            } else {
+                render.emit(Event::new("interrupted"));
+            }
+        }
+        result
+    }
+
+    /// Runs the [`on_timeout`](Self::on_timeout) hook once the run went past
+    /// its time limit and tells how far it got.
+    fn timed_out(&mut self, render: &RenderSettings) -> HflowError {
+        if let Some(hook) = self.on_timeout.take() {
+            hook();
+        }
+        let units = self.groups.iter().flat_map(TaskGroup::units);
+        let error = HflowError::RunTimedOut {
+            timeout: self.total_timeout.unwrap_or_default(),
+            completed: units.clone().filter(|unit| unit.status() == ExecutionStatus::Completed).count(),
+            total: units.count(),
+        };
+        if render.is_human() {
+            let failure = &render.theme.failure;
+            let line = format!("{} {}", render.marker(failure, "[fail]"), error);
+            render.renderer.println(&render.stamp(&render.paint(&line, failure.style)));
+        } else if let HflowError::RunTimedOut { completed, total, .. } = &error {
+            render.emit(Event::new("run_timed_out").value("completed", completed).value("total", total));
+        }
+        error
+    }
+
+    /// Lists every unit that failed, not allowed to, and returns them all
+    /// instead of `result`; `result` is kept when the run failed otherwise,
+    /// e.g. on a dependency cycle.
+    fn report_failures(&self, render: &RenderSettings, result: Result<(), HflowError>) -> Result<(), HflowError> {
+        let mut failures = Vec::new();
+        for (idx, group) in self.groups.iter().enumerate() {
+            let label = group.name().map_or_else(|| format!("Group {}", idx + 1), str::to_string);
+            for unit in group.units().iter().filter(|unit| !unit.is_allowed_to_fail()) {
+                if let Some(err) = unit.error() {
+                    failures.push((label.clone(), unit.description().to_string(), unit.finish_reason(), err));
+                }
+            }
+        }
+        if failures.is_empty() {
+            return result;
+        }
+
+        if render.is_human() {
+            let failure = &render.theme.failure;
+            let marker = render.paint(render.marker(failure, "[fail]"), failure.style);
+            let title = if failures.len() == 1 { "failure" } else { "failures" };
+            let mut report = format!("{} {}:", failures.len(), title);
+            for (group, description, reason, _) in &failures {
+                report.push_str(&format!("\n{}{} {} / {}", GROUP_INDENT, marker, group, description));
+                if let Some(reason) = reason {
+                    report.push_str(&format!(": {}", reason));
+                }
+            }
+            render.renderer.println(&render.stamp(&report));
+        }
+        Err(HflowError::Failures(failures.into_iter().map(|(.., err)| err).collect()))
+    }
+
+    /// Runs the groups in order, each under a header when it is named, or
+    /// up to `max_groups` of them at the same time.
+    fn run_groups(&mut self, render: &RenderSettings, observers: Observers, max_groups: usize) -> Result<(), HflowError> {
+        let total = self.groups.len();
+        // Fails before anything runs rather than in the middle of the run.
+        for group in &mut self.groups {
+            group.resolve_dependencies()?;
+        }
+        // Cancels the groups still running after a failure.
+        let siblings = self.cancellation.child_token();
+        let root = self.cancellation.clone();
+        for group in &mut self.groups {
+            group.set_cancellation_token(siblings.child_token());
+            group.set_observers(observers.clone());
+            group.inherit_exit_policy(self.failure_mode.map_or(self.exit_policy, FailureMode::exit_policy));
+            group.inherit_render(render.clone());
+        }
+        if max_groups <= 1 {
+            let mut first_error = None;
+            let resumed_from = self.resumed_from.unwrap_or(0);
+            for (idx, group) in self.groups.iter_mut().enumerate() {
+                if idx < resumed_from {
+                    Self::skip_group(render, group, idx, total, "resume");
+                    continue;
+                }
+                if let Err(err) = Self::run_group(render, group, idx, total) {
+                    match group.effective_exit_policy() {
+                        ExitPolicy::ExitProcess => {
+                            self.exit_code = Some(err.exit_code());
+                            return Err(err);
+                        }
+                        // Once the run timed out, the groups left are run to skip their units.
+                        ExitPolicy::StopAndReturn if !render.past_deadline() => return Err(err),
+                        ExitPolicy::StopAndReturn | ExitPolicy::ContinueAll => {
+                            first_error.get_or_insert(err);
+                        }
+                    }
+                }
+            }
+            return match first_error {
+                Some(err) => Err(err),
+                None => Ok(()),
+            };
+        }
+
+        let queue = Mutex::new(self.groups.iter_mut().enumerate());
+        let first_error = Mutex::new(None);
+        let exit_code = OnceLock::new();
+        thread::scope(|scope| {
+            for _ in 0..max_groups.min(total) {
+                scope.spawn(|| {
+                    loop {
+                        let Some((idx, group)) = queue.lock().unwrap_or_else(PoisonError::into_inner).next() else {
+                            break;
+                        };
+                        // Groups stopped by a failure are left out, the units of the others are skipped.
+                        if siblings.is_cancelled() && !root.is_cancelled() && !render.past_deadline() {
+                            continue;
+                        }
+                        if let Err(err) = Self::run_group(render, group, idx, total) {
+                            match group.effective_exit_policy() {
+                                ExitPolicy::ExitProcess => {
+                                    let _ = exit_code.set(err.exit_code());
+                                    siblings.cancel();
+                                }
+                                ExitPolicy::StopAndReturn => siblings.cancel(),
+                                ExitPolicy::ContinueAll => {}
+                            }
+                            first_error.lock().unwrap_or_else(PoisonError::into_inner).get_or_insert(err);
+                        }
+                    }
+                });
+            }
+        });
+        self.exit_code = exit_code.into_inner();
+        match first_error.into_inner().unwrap_or_else(PoisonError::into_inner) {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Runs the group at `idx` out of `total`, between its start and finish events.
+    fn run_group(render: &RenderSettings, group: &mut TaskGroup, idx: usize, total: usize) -> Result<(), HflowError> {
+        if group.is_excluded() {
+            return Ok(());
+        }
+        if group.has_nothing_to_run() {
+            render.emit(
+                Event::new("group_skipped")
+                    .value("group", idx + 1)
+                    .optional_text("name", group.name())
+                    .text("reason", "no units"),
+            );
+            return Ok(());
+        }
+        let mut started = Event::new("group_started").value("group", idx + 1);
+        if let Some(name) = group.name() {
+            started = started.text("name", name);
+        }
+        let title = Self::group_title(group, idx, total);
+        // Folded groups and headers would mix up the lines of groups running together.
+        let folded = !render.concurrent_groups && render.format == OutputFormat::GithubActions;
+        if folded {
+            render.workflow_command("group", &title);
+        } else if render.is_human() && !render.concurrent_groups && group.name().is_some() {
+            render.renderer.println(&render.stamp(&render.paint(&title, TextStyle::Bold)));
+        }
+        render.emit(started.value("units", group.units().len()));
+        let group_started = Instant::now();
+        let result = group.run(total as u32, (idx + 1) as u32);
+        render.emit(
+            Event::new("group_finished")
+                .value("group", idx + 1)
+                .optional_text("name", group.name())
+                .text("status", if result.is_ok() { "completed" } else { "failed" })
+                .value("elapsed_ms", group_started.elapsed().as_millis()),
+        );
+        if folded {
+            render.workflow_command("endgroup", "");
+        }
+        result.map(drop)
+    }
+
+    /// `Group 2/3: Build`, or `Group 2/3` for a group without a name.
+    fn group_title(group: &TaskGroup, idx: usize, total: usize) -> String {
+        match group.name() {
+            Some(name) => format!("Group {}/{}: {}", idx + 1, total, name),
+            None => format!("Group {}/{}", idx + 1, total),
+        }
+    }
+
+    /// Skips the group at `idx` without running it, e.g. before the one
+    /// [`start_from`](Self::start_from) resumed at.
+    fn skip_group(render: &RenderSettings, group: &mut TaskGroup, idx: usize, total: usize, reason: &str) {
+        group.skip_all(reason);
+        if render.is_human() {
+            let skipped = &render.theme.skipped;
+            let line = format!("{} {} ({})", Self::group_title(group, idx, total), render.marker(skipped, "[skipped]"), reason);
+            render.renderer.println(&render.stamp(&render.paint(&line, skipped.style)));
+        } else {
+            render.emit(
+                Event::new("group_skipped")
+                    .value("group", idx + 1)
+                    .optional_text("name", group.name())
+                    .text("reason", reason),
+            );
+        }
+    }
+
+    /// One line summing up the run, e.g. `✔ 12 tasks completed in 34s`.
+    fn summary_line(&self, render: &RenderSettings, succeeded: bool, elapsed: Duration) -> String {
+        let statuses: Vec<(ExecutionStatus, bool)> = self
+            .groups
+            .iter()
+            .flat_map(TaskGroup::units)
+            .map(|unit| (unit.status(), unit.is_allowed_to_fail()))
+            .collect();
+        let count = |matches: fn(ExecutionStatus, bool) -> bool| {
+            statuses.iter().filter(|&&(status, allowed)| matches(status, allowed)).count()
+        };
+        fn failed(status: ExecutionStatus) -> bool {
+            matches!(status, ExecutionStatus::Failed | ExecutionStatus::TimedOut)
+        }
+        let completed = count(|status, _| status == ExecutionStatus::Completed);
+        let recovered = self.recovered();
+        let tasks = if statuses.len() == 1 { "task" } else { "tasks" };
+        let mut text = if completed == statuses.len() {
+            format!("{} {} completed", statuses.len(), tasks)
+        } else {
+            format!("{} of {} {} completed", completed, statuses.len(), tasks)
+        };
+        let others = [
+            (count(|status, allowed| failed(status) && !allowed), "failed"),
+            (count(|status, allowed| failed(status) && allowed), "failed (allowed)"),
+            (count(|status, _| status == ExecutionStatus::Warning), "with warnings"),
+            (count(|status, _| status == ExecutionStatus::Cancelled), "cancelled"),
+            (count(|status, _| status == ExecutionStatus::Skipped), "skipped"),
+            (count(|status, _| status == ExecutionStatus::Pending), "not run"),
+            (recovered, "recovered on retry"),
+        ];
+        for (count, label) in others.iter().filter(|(count, _)| *count > 0) {
+            text.push_str(&format!(", {} {}", count, label));
+        }
+        let marker = if succeeded { &render.theme.success } else { &render.theme.failure };
+        let symbol = render.marker(marker, if succeeded { "[ok]" } else { "[fail]" });
+        let mut line = format!("{} {} in {}", symbol, text, format_duration(elapsed));
+        if let Some(idx) = self.resumed_from {
+            line.push_str(&format!(" (resumed from group {}/{})", idx + 1, self.groups.len()));
+        }
+        match self.empty_groups() {
+            0 => {}
+            1 => line.push_str(" (1 empty group skipped)"),
+            empty => line.push_str(&format!(" ({} empty groups skipped)", empty)),
+        }
+        let mut line = render.paint(&line, marker.style);
+        for group in self.groups.iter().filter(|group| group.name().is_some() && !group.is_excluded() && !group.has_nothing_to_run()) {
+            line.push_str(&format!("\n{}{}", GROUP_INDENT, Self::group_summary(group)));
+        }
+        line
+    }
+
+    /// The units of a named group by final status, e.g. `db-setup: 4 ok, 1 failed`.
+    fn group_summary(group: &TaskGroup) -> String {
+        let count = |matches: fn(ExecutionStatus) -> bool| group.units().iter().filter(|unit| matches(unit.status())).count();
+        let counts = [
+            (count(|status| status == ExecutionStatus::Completed), "ok"),
+            (count(|status| matches!(status, ExecutionStatus::Failed | ExecutionStatus::TimedOut)), "failed"),
+            (count(|status| status == ExecutionStatus::Warning), "with warnings"),
+            (count(|status| status == ExecutionStatus::Cancelled), "cancelled"),
+            (count(|status| status == ExecutionStatus::Skipped), "skipped"),
+            (count(|status| status == ExecutionStatus::Pending), "not run"),
+        ];
+        let counts: Vec<String> = counts
+            .iter()
+            .filter(|&&(count, label)| count > 0 || label == "ok")
+            .map(|(count, label)| format!("{} {}", count, label))
+            .collect();
+        format!("{}: {}", group.name().unwrap_or_default(), counts.join(", "))
+    }
+
+    /// Warns about groups sharing a name, as lookups by name such as
+    /// [`start_from_group`](Self::start_from_group) only find the first one.
+    fn warn_duplicate_names(&self, render: &RenderSettings) {
+        let names: Vec<&str> = self.groups.iter().filter_map(TaskGroup::name).collect();
+        for (idx, name) in names.iter().enumerate() {
+            let count = names.iter().filter(|other| *other == name).count();
+            if count < 2 || names[..idx].contains(name) {
+                continue;
+            }
+            let message = format!("warning: {} groups are named \"{}\", lookups by name only find the first one", count, name);
+            if render.is_human() {
+                render.renderer.println(&render.stamp(&render.paint(&message, render.theme.warning.style)));
+            } else {
+                render.emit(Event::new("log").text("message", &message));
+            }
+        }
+    }
+
+    /// Last event of a run, counting the units by final status.
+    fn summary(&self, succeeded: bool, elapsed: Duration) -> Event {
+        let mut event = Event::new("summary")
+            .text("status", if succeeded { "completed" } else { "failed" })
+            .value("elapsed_ms", elapsed.as_millis());
+        let statuses = [
+            ExecutionStatus::Completed,
+            ExecutionStatus::Failed,
+            ExecutionStatus::Skipped,
+            ExecutionStatus::Warning,
+            ExecutionStatus::Cancelled,
+            ExecutionStatus::TimedOut,
+            ExecutionStatus::Pending,
+        ];
+        for status in statuses {
+            let count = self
+                .groups
+                .iter()
+                .flat_map(TaskGroup::units)
+                .filter(|unit| unit.status() == status)
+                .count();
+            event = event.value(status.as_str(), count);
+        }
+        if let Some(idx) = self.resumed_from {
+            event = event.value("resumed_from", idx + 1);
+        }
+        if self.empty_groups() > 0 {
+            event = event.value("empty_groups", self.empty_groups());
+        }
+        match self.recovered() {
+            0 => event,
+            recovered => event.value("recovered", recovered),
+        }
+    }
+
+    /// Groups skipped for having no units at all.
+    fn empty_groups(&self) -> usize {
+        self.groups.iter().filter(|group| group.has_nothing_to_run()).count()
+    }
+
+    /// Units that completed when run again by [`rerun_failed`](Self::rerun_failed).
+    fn recovered(&self) -> usize {
+        self.groups
+            .iter()
+            .flat_map(TaskGroup::units)
+            .filter(|unit| unit.reran && unit.status() == ExecutionStatus::Completed)
+            .count()
+    }
+
+    /// Like [`start`](Self::start), but exits the process when a unit fails.
+    ///
+    /// The exit code is the one given to [`StatusHandle::fail_with_code`],
+    /// or 1. Meant for CLI tools where a failed step should end the program.
+    pub fn start_or_exit(&mut self) {
+        if let Err(err) = self.start() {
+            std::process::exit(err.exit_code());
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,428 @@
+use std::io::{self, IsTerminal, Write};
+use std::panic;
+use std::sync::{Arc, Mutex, MutexGuard, Once, OnceLock, PoisonError, TryLockError, Weak};
+use std::thread;
+use std::time::Duration;
+
+use crate::event::Event;
+use crate::{OutputFormat, term};
+
+const HIDE_CURSOR: &str = "\x1b[?25l";
+const SHOW_CURSOR: &str = "\x1b[?25h";
+
+/// How often the render thread redraws the live block by default.
+pub(crate) const RENDER_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Prints above the live lines of a run without corrupting them.
+///
+/// Obtained from [`ProgressManager::handle`](crate::ProgressManager::handle)
+/// for any thread of the application, or used through
+/// [`StatusHandle::println`](crate::StatusHandle::println) inside a unit.
+#[derive(Clone)]
+pub struct ProgressHandle {
+    renderer: Arc<Renderer>,
+    format: OutputFormat,
+}
+
+impl ProgressHandle {
+    pub(crate) fn new(renderer: Arc<Renderer>, format: OutputFormat) -> Self {
+        Self { renderer, format }
+    }
+
+    /// Clears the live lines, prints `message` with a trailing newline and
+    /// redraws the live lines underneath. When nothing is live, e.g. in
+    /// quiet mode or when the output is not a terminal, the message is
+    /// simply written out.
+    ///
+    /// With [`OutputFormat::JsonLines`] the message is wrapped in a `log` event.
+    pub fn println(&self, message: impl AsRef<str>) {
+        match self.format {
+            OutputFormat::Human | OutputFormat::GithubActions => self.renderer.println(message.as_ref()),
+            OutputFormat::JsonLines => {
+                self.renderer.println(&Event::new("log").text("message", message.as_ref()).into_line())
+            }
+        }
+    }
+}
+
+/// A line of the live block, owned by one unit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct LineId(u64);
+
+/// Draws the line of every running unit in a block at the bottom of the output.
+///
+/// A single render thread redraws the block by moving the cursor back to
+/// its first line. Finished units are retired: their final line is printed
+/// above the block and stays there. Every write goes through the state
+/// lock, so units running on different threads never garble each other.
+///
+/// When the output is not a terminal the block is never drawn and retired
+/// lines are simply appended.
+pub(crate) struct Renderer {
+    live: bool,
+    state: Mutex<State>,
+}
+
+struct State {
+    out: Box<dyn Write + Send>,
+    lines: Vec<(LineId, String)>,
+    /// Line kept at the bottom of the block, below the ones added after it.
+    footer: Option<LineId>,
+    next_id: u64,
+    /// Lines of the block on screen, the cursor sits at the end of the last one.
+    drawn: usize,
+    dirty: bool,
+    /// Number of paused units, the block is hidden while any is paused.
+    suspended: usize,
+    thread_running: bool,
+    /// Time between two redraws of the block.
+    interval: Duration,
+    /// Columns lines are fitted to instead of the width of the terminal.
+    width: Option<usize>,
+    /// Lines printed for good since [`Renderer::start_transcript`].
+    transcript: Option<Transcript>,
+    /// The cursor is hidden while the block is on screen.
+    cursor_hidden: bool,
+}
+
+/// What was printed above the block during a run, so it can be erased at the end.
+struct Transcript {
+    /// Terminal rows taken by the printed lines, `None` once unknown.
+    rows: Option<usize>,
+    /// Lines printed again after the transcript is erased.
+    kept: Vec<String>,
+}
+
+impl Renderer {
+    pub(crate) fn new(out: impl Write + Send + 'static, live: bool) -> Arc<Self> {
+        Arc::new(Self {
+            live,
+            state: Mutex::new(State {
+                out: Box::new(out),
+                lines: Vec::new(),
+                footer: None,
+                next_id: 0,
+                drawn: 0,
+                dirty: false,
+                suspended: 0,
+                thread_running: false,
+                interval: RENDER_INTERVAL,
+                width: None,
+                transcript: None,
+                cursor_hidden: false,
+            }),
+        })
+    }
+
+    /// The renderer shared by everything printing to stdout.
+    pub(crate) fn stdout() -> Arc<Self> {
+        static STDOUT: OnceLock<Arc<Renderer>> = OnceLock::new();
+        STDOUT
+            .get_or_init(|| Renderer::new(io::stdout(), io::stdout().is_terminal()))
+            .clone()
+    }
+
+    /// Whether the output is a terminal and lines are redrawn in place.
+    pub(crate) fn is_live(&self) -> bool {
+        self.live
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Adds a line at the bottom of the block, starting the render thread if needed.
+    pub(crate) fn add(self: &Arc<Self>, text: String) -> LineId {
+        let mut state = self.lock();
+        let id = LineId(state.next_id);
+        state.next_id += 1;
+        if !self.live {
+            return id;
+        }
+        let at = state.lines.iter().position(|(line, _)| Some(*line) == state.footer).unwrap_or(state.lines.len());
+        state.lines.insert(at, (id, text));
+        state.dirty = true;
+        restore_on_panic(self);
+        if !state.thread_running {
+            state.thread_running = true;
+            let renderer = self.clone();
+            thread::spawn(move || renderer.run());
+        }
+        id
+    }
+
+    /// Like [`add`](Self::add), for a line that stays at the bottom of the block.
+    pub(crate) fn add_footer(self: &Arc<Self>, text: String) -> LineId {
+        let id = self.add(text);
+        self.lock().footer = Some(id);
+        id
+    }
+
+    /// Sets how often the block is redrawn. It is only written when one of
+    /// its lines changed, and lines printed for good are written at once.
+    pub(crate) fn set_interval(&self, interval: Duration) {
+        self.lock().interval = interval;
+    }
+
+    /// Fits lines to `width` columns instead of the width of the terminal.
+    pub(crate) fn set_width(&self, width: Option<usize>) {
+        self.lock().width = width;
+    }
+
+    /// Number of columns of the output, see [`term::terminal_width`].
+    pub(crate) fn width(&self) -> usize {
+        self.known_width().unwrap_or_else(term::terminal_width)
+    }
+
+    /// Number of columns of the output, `None` when unknown.
+    pub(crate) fn known_width(&self) -> Option<usize> {
+        self.lock().width.or_else(term::known_width)
+    }
+
+    /// Replaces the text of a line, drawn on the next tick.
+    pub(crate) fn update(&self, id: LineId, text: String) {
+        if !self.live {
+            return;
+        }
+        let mut state = self.lock();
+        if let Some(line) = state.lines.iter_mut().find(|(line, _)| *line == id)
+            && line.1 != text
+        {
+            line.1 = text;
+            state.dirty = true;
+        }
+    }
+
+    /// Removes the line from the block, if any, and prints `text` above it for good.
+    pub(crate) fn finish(&self, id: Option<LineId>, text: &str) {
+        let mut state = self.lock();
+        if let Some(id) = id {
+            state.lines.retain(|(line, _)| *line != id);
+        }
+        self.redraw(&mut state, Some(text));
+    }
+
+    /// Removes the line from the block without printing anything for good.
+    pub(crate) fn remove(&self, id: LineId) {
+        let mut state = self.lock();
+        state.lines.retain(|(line, _)| *line != id);
+        self.redraw(&mut state, None);
+    }
+
+    /// Prints `text` for good above the block.
+    pub(crate) fn println(&self, text: &str) {
+        self.finish(None, text);
+    }
+
+    /// Rings the terminal bell, which leaves the cursor and the block alone.
+    pub(crate) fn bell(&self) {
+        let mut state = self.lock();
+        let _ = state.out.write_all(b"\x07");
+        let _ = state.out.flush();
+    }
+
+    /// Prints `text` for good and hides the block, leaving the cursor on a fresh line.
+    pub(crate) fn suspend(&self, text: &str) {
+        let mut state = self.lock();
+        state.suspended += 1;
+        self.redraw(&mut state, Some(text));
+        // Whatever is written while suspended is out of sight of the renderer.
+        if let Some(transcript) = &mut state.transcript {
+            transcript.rows = None;
+        }
+    }
+
+    /// Starts recording the lines printed for good, see [`erase_transcript`](Self::erase_transcript).
+    pub(crate) fn start_transcript(&self) {
+        self.lock().transcript = Some(Transcript {
+            rows: Some(0),
+            kept: Vec::new(),
+        });
+    }
+
+    /// Marks `text`, already printed, to be printed again when the transcript is erased.
+    pub(crate) fn keep(&self, text: &str) {
+        if let Some(transcript) = &mut self.lock().transcript {
+            transcript.kept.push(text.to_string());
+        }
+    }
+
+    /// Erases the lines printed since [`start_transcript`](Self::start_transcript),
+    /// except the kept ones, and prints `text` for good.
+    ///
+    /// Lines that scrolled out of the terminal cannot be erased; the
+    /// transcript is left as is then, as it is when the output is not a terminal.
+    pub(crate) fn erase_transcript(&self, text: &str) {
+        let mut state = self.lock();
+        let Some(transcript) = state.transcript.take() else {
+            return self.redraw(&mut state, Some(text));
+        };
+        let erasable = transcript
+            .rows
+            .filter(|&rows| self.live && state.drawn == 0 && rows > 0 && rows < term::terminal_height());
+        let Some(rows) = erasable else {
+            return self.redraw(&mut state, Some(text));
+        };
+        let mut frame = format!("\x1b[{}A\r\x1b[J", rows);
+        for line in &transcript.kept {
+            frame.push_str(line);
+            frame.push('\n');
+        }
+        frame.push_str(text);
+        frame.push('\n');
+        let _ = state.out.write_all(frame.as_bytes());
+        let _ = state.out.flush();
+    }
+
+    /// Shows the cursor again if the block hid it, leaving the block where it
+    /// is and the cursor on a fresh line below it.
+    pub(crate) fn restore_cursor(&self) {
+        let mut state = self.lock();
+        restore(&mut state);
+    }
+
+    /// Shows the block again after [`suspend`](Self::suspend).
+    pub(crate) fn resume(&self) {
+        let mut state = self.lock();
+        state.suspended = state.suspended.saturating_sub(1);
+        state.dirty = true;
+    }
+
+    fn run(&self) {
+        loop {
+            let interval = self.lock().interval;
+            thread::sleep(interval);
+            let mut state = self.lock();
+            if state.lines.is_empty() {
+                state.thread_running = false;
+                return;
+            }
+            if state.dirty {
+                self.redraw(&mut state, None);
+            }
+        }
+    }
+
+    /// Clears the block, prints the retired text and draws the block again.
+    fn redraw(&self, state: &mut State, retired: Option<&str>) {
+        let mut frame = String::new();
+        if state.drawn > 1 {
+            frame.push_str(&format!("\x1b[{}A", state.drawn - 1));
+        }
+        if state.drawn > 0 {
+            frame.push_str("\r\x1b[J");
+        }
+        if let Some(text) = retired {
+            frame.push_str(text);
+            frame.push('\n');
+            if let Some(rows) = state.transcript.as_mut().and_then(|transcript| transcript.rows.as_mut()) {
+                *rows += physical_rows(text, state.width.unwrap_or_else(term::terminal_width));
+            }
+        }
+        state.drawn = 0;
+        if self.live && state.suspended == 0 && !state.lines.is_empty() {
+            if !state.cursor_hidden {
+                frame.insert_str(0, HIDE_CURSOR);
+                state.cursor_hidden = true;
+            }
+            let visible = visible_lines(&state.lines, term::terminal_height());
+            frame.push_str(&visible.join("\n"));
+            state.drawn = visible.len();
+        } else if state.cursor_hidden {
+            frame.push_str(SHOW_CURSOR);
+            state.cursor_hidden = false;
+        }
+        state.dirty = false;
+        let _ = state.out.write_all(frame.as_bytes());
+        let _ = state.out.flush();
+    }
+}
+
+/// Shows the cursor and moves below the block, which is drawn again from
+/// scratch on the next frame.
+fn restore(state: &mut State) {
+    if !state.cursor_hidden {
+        return;
+    }
+    let newline = if state.drawn > 0 { "\n" } else { "" };
+    let _ = write!(state.out, "{}{}", SHOW_CURSOR, newline);
+    let _ = state.out.flush();
+    state.cursor_hidden = false;
+    state.drawn = 0;
+    state.dirty = true;
+}
+
+/// Registers `renderer` to get its cursor back when any thread panics.
+///
+/// The panic hook is installed with the first live renderer and calls the
+/// hook that was in place before it. A renderer locked by the panicking
+/// thread itself is left alone rather than deadlocking.
+fn restore_on_panic(renderer: &Arc<Renderer>) {
+    static LIVE: Mutex<Vec<Weak<Renderer>>> = Mutex::new(Vec::new());
+    static HOOK: Once = Once::new();
+    let mut live = LIVE.lock().unwrap_or_else(PoisonError::into_inner);
+    live.retain(|weak| weak.strong_count() > 0);
+    if !live.iter().any(|weak| weak.as_ptr() == Arc::as_ptr(renderer)) {
+        live.push(Arc::downgrade(renderer));
+    }
+    drop(live);
+    HOOK.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let live = match LIVE.try_lock() {
+                Ok(live) => live.clone(),
+                Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner().clone(),
+                Err(TryLockError::WouldBlock) => Vec::new(),
+            };
+            for renderer in live.iter().filter_map(Weak::upgrade) {
+                match renderer.state.try_lock() {
+                    Ok(mut state) => restore(&mut state),
+                    Err(TryLockError::Poisoned(poisoned)) => restore(&mut poisoned.into_inner()),
+                    Err(TryLockError::WouldBlock) => {}
+                }
+            }
+            previous(info);
+        }));
+    });
+}
+
+/// Rows `text` takes on a terminal of `width` columns, counting wrapped
+/// lines and ignoring escape sequences.
+fn physical_rows(text: &str, width: usize) -> usize {
+    let width = width.max(1);
+    text.split('\n')
+        .map(|line| visible_len(line).div_ceil(width).max(1))
+        .sum()
+}
+
+/// Number of columns `line` takes on screen.
+fn visible_len(line: &str) -> usize {
+    let mut len = 0;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // CSI sequences end with a letter.
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            len += term::char_width(c);
+        }
+    }
+    len
+}
+
+/// Lines of the block that fit on a terminal of `height` rows, keeping a
+/// row free for the cursor; the ones left out are summarized on the last line.
+fn visible_lines(lines: &[(LineId, String)], height: usize) -> Vec<String> {
+    let rows = height.saturating_sub(1).max(1);
+    if lines.len() <= rows {
+        return lines.iter().map(|(_, text)| text.clone()).collect();
+    }
+    let shown = rows - 1;
+    let mut visible: Vec<String> = lines[..shown].iter().map(|(_, text)| text.clone()).collect();
+    visible.push(format!("+{} more", lines.len() - shown));
+    visible
+}
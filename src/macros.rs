@@ -0,0 +1,122 @@
+/// Declares a plan of named groups and their tasks, expanding to the
+/// [`ProgressManager::builder`](crate::ProgressManager::builder) calls and
+/// returning the built [`ProgressManager`](crate::ProgressManager).
+///
+/// Every `task` gets a description and a closure run as with
+/// [`ExecutionUnit::on_try_execute`](crate::ExecutionUnit::on_try_execute),
+/// its error boxed so that `?` works on any error type. Modifiers between
+/// the description and `=>` configure the unit:
+///
+/// * `retries(3)`, see [`ExecutionUnit::retries`](crate::ExecutionUnit::retries)
+/// * `timeout(Duration::from_secs(30))`, see [`ExecutionUnit::timeout`](crate::ExecutionUnit::timeout)
+/// * `allowed_to_fail`, or `allowed_to_fail(false)`, see [`ExecutionUnit::allowed_to_fail`](crate::ExecutionUnit::allowed_to_fail)
+/// * `tags("slow", "network")`, see [`ExecutionUnit::tag`](crate::ExecutionUnit::tag)
+/// * `id("schema")` and `depends_on("schema")`, see [`ExecutionUnit::depends_on`](crate::ExecutionUnit::depends_on)
+///
+/// ```rust
+/// # use std::time::Duration;
+/// # use hflow::ExecutionStatus;
+/// let mut manager = hflow::hflow! {
+///     group "database" {
+///         task "create schema" id("schema") => |_status| Ok(()),
+///         task "seed data" retries(3) timeout(Duration::from_secs(5)) depends_on("schema") => |status| {
+///             let rows: u32 = "42".parse()?;
+///             status.set_message(format!("{} rows", rows));
+///             Ok(())
+///         },
+///     }
+///     group "app" {
+///         task "notify" allowed_to_fail tags("network") => |_status| Err("no webhook".into()),
+///     }
+/// };
+/// # manager.writer(std::io::sink());
+/// manager.start().unwrap();
+///
+/// let groups = manager.groups();
+/// assert_eq!(groups[0].name(), Some("database"));
+/// assert!(groups[0].units().iter().all(|unit| unit.is_completed()));
+/// assert_eq!(groups[1].units()[0].status(), ExecutionStatus::Failed);
+/// assert_eq!(groups[1].units()[0].tags(), ["network"]);
+/// ```
+///
+/// An unknown modifier is a compile error naming it:
+///
+/// ```rust,compile_fail
+/// let manager = hflow::hflow! {
+///     group "app" {
+///         task "build" retry(3) => |_status| Ok(()),
+///     }
+/// };
+/// ```
+#[macro_export]
+macro_rules! hflow {
+    ($(group $name:literal { $($tasks:tt)* })*) => {
+        $crate::ProgressManager::builder()
+            $(.group($name, |group| {
+                $crate::__hflow_tasks!(group; $($tasks)*);
+            }))*
+            .build()
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __hflow_tasks {
+    ($group:ident;) => {};
+    ($group:ident; task $description:literal $($modifier:ident $(($($args:tt)*))?)* => $callback:expr $(, $($rest:tt)*)?) => {
+        let unit = $crate::__private::task($description, $callback);
+        $(let unit = $crate::__hflow_modifier!(unit, $modifier $(($($args)*))?);)*
+        $group.add_unit(unit);
+        $crate::__hflow_tasks!($group; $($($rest)*)?);
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __hflow_modifier {
+    ($unit:ident, retries($retries:expr)) => {
+        $unit.retries($retries)
+    };
+    ($unit:ident, timeout($timeout:expr)) => {
+        $unit.timeout($timeout)
+    };
+    ($unit:ident, allowed_to_fail) => {
+        $unit.allowed_to_fail(true)
+    };
+    ($unit:ident, allowed_to_fail($allowed:expr)) => {
+        $unit.allowed_to_fail($allowed)
+    };
+    ($unit:ident, tags($($tag:expr),* $(,)?)) => {
+        $unit$(.tag($tag))*
+    };
+    ($unit:ident, id($id:expr)) => {
+        $unit.id($id)
+    };
+    ($unit:ident, depends_on($($id:expr),* $(,)?)) => {
+        $unit.depends_on([$($id),*])
+    };
+    ($unit:ident, $modifier:ident $($args:tt)*) => {
+        compile_error!(concat!(
+            "unknown task modifier `",
+            stringify!($modifier),
+            "`, expected `retries`, `timeout`, `allowed_to_fail`, `tags`, `id` or `depends_on`"
+        ))
+    };
+}
+
+/// Helpers of the macros, not part of the public API.
+#[doc(hidden)]
+pub mod private {
+    use std::error::Error;
+
+    use crate::{ExecutionUnit, StatusHandle};
+
+    /// A unit running `callback`, which fixes its error type for closures
+    /// that only return `Ok(())` and lets `?` convert any error.
+    pub fn task<F>(description: &str, callback: F) -> ExecutionUnit
+    where
+        F: Fn(StatusHandle) -> Result<(), Box<dyn Error + Send + Sync>> + Send + 'static,
+    {
+        ExecutionUnit::new(description.to_string()).on_try_execute(callback)
+    }
+}
@@ -0,0 +1,65 @@
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+
+use crate::ExecutionStatus;
+
+/// Identifies an execution unit in observer notifications.
+///
+/// Ids are assigned when the unit is created and are unique within the process.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct UnitId(u64);
+
+impl UnitId {
+    pub(crate) fn next() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+        UnitId(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl fmt::Display for UnitId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// A unit about to run, passed to [`ExecutionUnit::on_start`](crate::ExecutionUnit::on_start)
+/// and to the [`TaskGroup::before_each`](crate::TaskGroup::before_each) and
+/// [`TaskGroup::after_each`](crate::TaskGroup::after_each) hooks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnitInfo {
+    pub description: String,
+    pub unit_id: UnitId,
+    /// Set with [`ExecutionUnit::id`](crate::ExecutionUnit::id).
+    pub id: Option<String>,
+    /// Set with [`ExecutionUnit::tag`](crate::ExecutionUnit::tag).
+    pub tags: Vec<String>,
+    /// Position of the group in the run and of the unit in its group,
+    /// starting at 1, or 0 for a unit executed on its own.
+    pub group: usize,
+    pub unit: usize,
+    /// Name of the group, see [`TaskGroup::new_named`](crate::TaskGroup::new_named).
+    pub group_name: Option<String>,
+    pub started_at: SystemTime,
+}
+
+/// Receives every status change of the units in a run.
+///
+/// Observers are called synchronously from whichever thread performs the
+/// transition (the calling thread, the worker thread or the display loop),
+/// after the status lock has been released, so they may read the status of
+/// the unit without deadlocking.
+pub trait StatusObserver {
+    fn on_transition(&self, unit: UnitId, from: ExecutionStatus, to: ExecutionStatus, at: SystemTime);
+
+    /// Called when the group at position `group` of the run starts, with
+    /// its name and the units it holds, so that transitions can be told
+    /// apart by group. Does nothing by default.
+    fn on_group_started(&self, group: usize, name: Option<&str>, units: &[UnitId]) {
+        let _ = (group, name, units);
+    }
+}
+
+/// Observers shared by every unit of a run.
+pub(crate) type Observers = Arc<Vec<Arc<dyn StatusObserver + Send + Sync>>>;
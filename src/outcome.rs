@@ -0,0 +1,99 @@
+use std::time::Duration;
+
+use crate::{ExecutionStatus, HflowError};
+
+/// How a unit of [`TaskGroup::run_collect`](crate::TaskGroup::run_collect) ended.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnitOutcome<T> {
+    pub description: String,
+    pub status: ExecutionStatus,
+    /// Set with [`ExecutionUnit::tag`](crate::ExecutionUnit::tag).
+    pub tags: Vec<String>,
+    /// How long the unit ran, unset for units that never started.
+    pub elapsed: Option<Duration>,
+    /// The value returned by the unit, only for completed units.
+    pub output: Option<T>,
+    /// Why the unit failed or timed out.
+    pub failure_reason: Option<String>,
+}
+
+/// How an item of [`TaskGroup::parallel_map`](crate::TaskGroup::parallel_map) ended.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MapOutcome<R, E> {
+    pub description: String,
+    pub status: ExecutionStatus,
+    /// What the closure returned for the item, `None` when it did not
+    /// return: the unit timed out, was cancelled or skipped.
+    pub result: Option<Result<R, E>>,
+}
+
+/// How a run of [`ProgressManager::start`](crate::ProgressManager::start)
+/// ended without failing, see [`RunSummary::outcome`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RunOutcome {
+    /// Every group ran to the end.
+    Finished,
+    /// A [`ShutdownHandle`](crate::ShutdownHandle) stopped the run.
+    ShutdownRequested,
+}
+
+/// How a whole run ended, passed to [`ProgressManager::after_all`](crate::ProgressManager::after_all)
+/// and [`ProgressManager::on_finish`](crate::ProgressManager::on_finish),
+/// returned by [`ProgressManager::start`](crate::ProgressManager::start), in
+/// the [`RunError`](crate::RunError) when the run failed, and kept by
+/// [`ProgressManager::run_summary`](crate::ProgressManager::run_summary).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RunSummary {
+    /// No unit failed, except those allowed to, and the run was neither
+    /// interrupted nor timed out.
+    pub succeeded: bool,
+    /// Wall time of the whole run, setup and teardown included.
+    pub elapsed: Duration,
+    /// Final status of every unit, group after group.
+    pub statuses: Vec<ExecutionStatus>,
+    /// How every unit of every group ended, in the order of the groups.
+    pub groups: Vec<GroupResult>,
+    /// The error of the first unit that failed, allowed failures aside, or
+    /// why the run stopped otherwise, e.g. [`HflowError::RunTimedOut`].
+    /// Unset when the run succeeded, and after Ctrl+C when no unit failed.
+    pub error: Option<HflowError>,
+    /// A [`ShutdownHandle`](crate::ShutdownHandle) stopped the run.
+    pub shutdown_requested: bool,
+}
+
+impl RunSummary {
+    /// Number of units that ended with `status`.
+    pub fn count(&self, status: ExecutionStatus) -> usize {
+        self.statuses.iter().filter(|&&ended| ended == status).count()
+    }
+
+    /// Whether every group ran to the end or a shutdown stopped the run.
+    pub fn outcome(&self) -> RunOutcome {
+        if self.shutdown_requested { RunOutcome::ShutdownRequested } else { RunOutcome::Finished }
+    }
+}
+
+/// How every unit of a group ended, see [`TaskGroup::run_report`](crate::TaskGroup::run_report).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GroupResult {
+    pub name: Option<String>,
+    /// One per unit, in the order they were added. Outputs stay with the
+    /// units, see [`TaskGroup::run_collect`](crate::TaskGroup::run_collect).
+    pub units: Vec<UnitOutcome<()>>,
+    /// The error of the first unit that failed, allowed failures aside.
+    pub error: Option<HflowError>,
+}
+
+impl GroupResult {
+    /// No unit failed or timed out, except those allowed to.
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// The units that failed or timed out.
+    pub fn failures(&self) -> impl Iterator<Item = &UnitOutcome<()>> {
+        self.units
+            .iter()
+            .filter(|unit| matches!(unit.status, ExecutionStatus::Failed | ExecutionStatus::TimedOut))
+    }
+}
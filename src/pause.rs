@@ -0,0 +1,92 @@
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+/// Upper bound for [`PauseHandle::pause`] to wait for the display loop.
+const PAUSE_ACK_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Suspends and restores the spinner line of a unit, e.g. around an interactive prompt.
+///
+/// Obtained from [`ExecutionUnit::pause_handle`](crate::ExecutionUnit::pause_handle)
+/// or, inside the closure, from [`StatusHandle::pause_handle`](crate::StatusHandle::pause_handle).
+#[derive(Clone, Default)]
+pub struct PauseHandle {
+    inner: Arc<PauseInner>,
+}
+
+#[derive(Default)]
+struct PauseInner {
+    state: Mutex<PauseState>,
+    changed: Condvar,
+}
+
+#[derive(Default)]
+struct PauseState {
+    paused: bool,
+    /// The paused line has been printed and the cursor sits on a fresh line.
+    drawn: bool,
+    /// A display loop is currently rendering the unit.
+    active: bool,
+}
+
+impl PauseHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stops the spinner from printing.
+    ///
+    /// Returns once the display loop has printed the paused line and left the
+    /// cursor on a fresh line, so the caller may write to the terminal safely.
+    pub fn pause(&self) {
+        let mut state = self.inner.state.lock().unwrap();
+        state.paused = true;
+        if state.active {
+            let (_state, _timeout) = self
+                .inner
+                .changed
+                .wait_timeout_while(state, PAUSE_ACK_TIMEOUT, |state| state.active && !state.drawn)
+                .unwrap();
+        }
+    }
+
+    /// Lets the spinner line be redrawn on the next frame.
+    pub fn resume(&self) {
+        self.inner.state.lock().unwrap().paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.inner.state.lock().unwrap().paused
+    }
+
+    pub(crate) fn set_active(&self, active: bool) {
+        let mut state = self.inner.state.lock().unwrap();
+        state.active = active;
+        if !active {
+            state.paused = false;
+            state.drawn = false;
+        }
+        self.inner.changed.notify_all();
+    }
+
+    /// Called by the display loop on every frame, `draw_paused` prints the
+    /// paused line and `resumed` runs on the first frame after a resume.
+    /// Returns whether the frame should be skipped.
+    pub(crate) fn frame(&self, draw_paused: impl FnOnce(), resumed: impl FnOnce()) -> bool {
+        let mut state = self.inner.state.lock().unwrap();
+        match (state.paused, state.drawn) {
+            (true, false) => {
+                draw_paused();
+                state.drawn = true;
+                self.inner.changed.notify_all();
+                true
+            }
+            (true, true) => true,
+            (false, true) => {
+                state.drawn = false;
+                resumed();
+                false
+            }
+            (false, false) => false,
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use std::marker::PhantomData;
+use std::sync::PoisonError;
+
+use crate::{ExecutionUnit, StatusHandle};
+
+/// Units run one after the other, each one receiving the value returned by
+/// the one before it, such as fetching a manifest, computing a plan from
+/// it, then applying the plan. Added to a group with [`TaskGroup::add_pipeline`](crate::TaskGroup::add_pipeline).
+///
+/// `T` is the type returned by the last stage, so every stage is checked
+/// at compile time against the one it follows. The value of each stage
+/// stays with its unit, the last one can be collected with
+/// [`TaskGroup::run_collect`](crate::TaskGroup::run_collect).
+///
+/// A stage only runs once the previous one completed or finished with a
+/// warning. Otherwise it is skipped, with `dependency failed` and the
+/// description of that stage as its reason; with
+/// [`ExitPolicy::StopAndReturn`](crate::ExitPolicy::StopAndReturn) the
+/// stages after a failure are skipped as the group stops.
+///
+/// ```rust
+/// # use hflow::{ExecutionStatus, Pipeline, TaskGroup};
+/// let deploy = Pipeline::new("Fetch manifest".to_string(), |_status| vec!["api", "worker"])
+///     .then("Compute plan".to_string(), |services: &Vec<&str>, _status| services.len() as u32)
+///     .then("Apply plan".to_string(), |changes: &u32, status| {
+///         status.set_message(format!("{} changes", changes));
+///         format!("applied {} changes", changes)
+///     });
+///
+/// let mut group = TaskGroup::new();
+/// group.add_pipeline(deploy);
+/// let outcomes = group.run_collect::<String>(1, 1);
+/// assert!(outcomes.iter().all(|outcome| outcome.status == ExecutionStatus::Completed));
+/// assert_eq!(outcomes[2].output.as_deref(), Some("applied 2 changes"));
+/// ```
+///
+/// A stage that fails skips the rest of the pipeline:
+///
+/// ```rust
+/// # use hflow::{ExecutionStatus, ExecutionUnit, Pipeline, TaskGroup};
+/// let deploy = Pipeline::new("Fetch manifest".to_string(), |_status| "api")
+///     .then("Compute plan".to_string(), |_service: &&str, status| {
+///         status.fail_with("cluster unreachable");
+///     })
+///     .then("Apply plan".to_string(), |_plan: &(), _status| unreachable!());
+///
+/// let mut group = TaskGroup::new();
+/// group.add_pipeline(deploy);
+/// assert!(group.run(1, 1).is_err());
+///
+/// let units = group.units();
+/// assert!(units[0].is_completed());
+/// assert_eq!(units[1].status(), ExecutionStatus::Failed);
+/// assert_eq!(units[2].status(), ExecutionStatus::Skipped);
+/// assert_eq!(units[2].failure_reason().as_deref(), Some("dependency failed: Compute plan"));
+/// ```
+pub struct Pipeline<T> {
+    units: Vec<ExecutionUnit>,
+    output: PhantomData<fn() -> T>,
+}
+
+impl<T: Send + 'static> Pipeline<T> {
+    /// Starts a pipeline with a stage computing a value from nothing, as
+    /// with [`ExecutionUnit::on_execute_returning`].
+    pub fn new<F>(description: String, callback: F) -> Self
+    where
+        F: Fn(StatusHandle) -> T + Send + 'static,
+    {
+        Self {
+            units: vec![ExecutionUnit::new(description).on_execute_returning(callback)],
+            output: PhantomData,
+        }
+    }
+
+    /// Adds a stage computing a value from the one returned by the last stage.
+    ///
+    /// The stage completes when the closure returns, unless it set another
+    /// status through the handle.
+    pub fn then<U, F>(mut self, description: String, callback: F) -> Pipeline<U>
+    where
+        F: Fn(&T, StatusHandle) -> U + Send + 'static,
+        U: Send + 'static,
+    {
+        let previous = self.units.last().expect("a pipeline has at least one stage");
+        let input = previous.output.clone();
+        let mut unit = ExecutionUnit::new(description).on_execute_returning(move |status| {
+            let input = input.lock().unwrap_or_else(PoisonError::into_inner);
+            // The previous stage completed, so its value is there.
+            let input = input.as_ref().and_then(|value| value.downcast_ref::<T>()).expect("output of the previous stage");
+            callback(input, status)
+        });
+        unit.previous_stage = Some(previous.view());
+        self.units.push(unit);
+        Pipeline { units: self.units, output: PhantomData }
+    }
+}
+
+impl<T> Pipeline<T> {
+    pub(crate) fn into_units(self) -> Vec<ExecutionUnit> {
+        self.units
+    }
+}
@@ -0,0 +1,43 @@
+/// What happens to the rest of the run when a unit fails.
+///
+/// Set on [`ProgressManager`](crate::ProgressManager) and optionally
+/// overridden per [`TaskGroup`](crate::TaskGroup); the policy of the group
+/// the failing unit belongs to is the one applied.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExitPolicy {
+    /// Terminates the process with the exit code of the failure. Under a
+    /// [`ProgressManager`](crate::ProgressManager), the run first stops as
+    /// with [`StopAndReturn`](Self::StopAndReturn) and runs its
+    /// [`after_all`](crate::ProgressManager::after_all) and
+    /// [`on_finish`](crate::ProgressManager::on_finish) hooks.
+    ExitProcess,
+    /// Stops scheduling units and returns the error to the caller.
+    #[default]
+    StopAndReturn,
+    /// Keeps running every remaining unit and group, the first error is
+    /// returned once everything has run.
+    ContinueAll,
+}
+
+/// How a whole run reacts to failures, set on
+/// [`ProgressManager::failure_mode`](crate::ProgressManager::failure_mode).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FailureMode {
+    /// Schedules nothing new after the first failure and returns its
+    /// error, as [`ExitPolicy::StopAndReturn`] does.
+    #[default]
+    FailFast,
+    /// Runs every unit of every group, then lists all the failures at the
+    /// end and returns them together as [`HflowError::Failures`](crate::HflowError::Failures).
+    RunAll,
+}
+
+impl FailureMode {
+    /// The exit policy of the groups that do not set their own.
+    pub(crate) fn exit_policy(self) -> ExitPolicy {
+        match self {
+            FailureMode::FailFast => ExitPolicy::StopAndReturn,
+            FailureMode::RunAll => ExitPolicy::ContinueAll,
+        }
+    }
+}
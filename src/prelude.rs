@@ -0,0 +1,13 @@
+//! The types most closures and runs need, and the [`StatusExt`] trait.
+//!
+//! ```rust
+//! use hflow::prelude::*;
+//!
+//! let mut manager = ProgressManager::new();
+//! manager.add_group(TaskGroup::new().with_unit(ExecutionUnit::new("Build".to_string()).on_execute(|status| status.complete())));
+//!
+//! assert!(manager.start().is_ok());
+//! assert_eq!(manager.groups()[0].units()[0].status(), ExecutionStatus::Completed);
+//! ```
+
+pub use crate::{ExecutionStatus, ExecutionUnit, ProgressManager, StatusExt, StatusHandle, TaskGroup};
@@ -0,0 +1,273 @@
+use std::time::{Duration, Instant};
+
+/// Number of cells of the default progress bar.
+const BAR_WIDTH: usize = 10;
+
+/// Fewest cells drawn by a [`BarWidth::Remaining`] bar, even when the line is full.
+pub(crate) const MIN_BAR_WIDTH: usize = 5;
+
+/// Without a new report for this long the unit goes back to the spinner.
+const STALE_AFTER: Duration = Duration::from_secs(2);
+
+/// Reports closer together than this are merged into one rate sample.
+const MIN_SAMPLE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Weight of the newest sample, an EMA over roughly the last 5 samples.
+const SMOOTHING: f64 = 2.0 / (5.0 + 1.0);
+
+/// Samples needed before an ETA is shown.
+const MIN_SAMPLES: u32 = 3;
+
+/// Smoothed throughput of a unit, used to estimate the time remaining.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct RateEstimator {
+    /// Progress and time of the start of the current sample.
+    last: Option<(u64, Instant)>,
+    /// Units of progress per second.
+    rate: f64,
+    samples: u32,
+}
+
+impl RateEstimator {
+    pub(crate) fn new() -> Self {
+        Self { last: None, rate: 0.0, samples: 0 }
+    }
+
+    pub(crate) fn record(&mut self, current: u64, at: Instant) {
+        let Some((last_current, last_at)) = self.last else {
+            self.last = Some((current, at));
+            return;
+        };
+        if current < last_current {
+            *self = Self::new();
+            self.last = Some((current, at));
+            return;
+        }
+        let elapsed = at.saturating_duration_since(last_at);
+        if elapsed < MIN_SAMPLE_INTERVAL {
+            return;
+        }
+        let rate = (current - last_current) as f64 / elapsed.as_secs_f64();
+        self.rate = if self.samples == 0 { rate } else { SMOOTHING * rate + (1.0 - SMOOTHING) * self.rate };
+        self.samples += 1;
+        self.last = Some((current, at));
+    }
+
+    /// Time left to reach `total` from `current`, `None` until enough samples exist
+    /// or while no progress is being made.
+    pub(crate) fn eta(&self, current: u64, total: u64) -> Option<Duration> {
+        if self.samples < MIN_SAMPLES || self.rate <= 0.0 {
+            return None;
+        }
+        Some(Duration::from_secs_f64(total.saturating_sub(current) as f64 / self.rate))
+    }
+}
+
+/// How many cells a progress bar takes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BarWidth {
+    /// Always this many cells.
+    Fixed(usize),
+    /// Whatever is left of the terminal width after the rest of the line,
+    /// and at least a few cells.
+    Remaining,
+}
+
+/// Characters and width of the progress bar drawn once a unit reports
+/// progress with [`StatusHandle::set_progress`](crate::StatusHandle::set_progress).
+///
+/// Set for a whole run with [`ProgressManager::progress_bar`](crate::ProgressManager::progress_bar)
+/// or for a single unit with [`ExecutionUnit::progress_bar`](crate::ExecutionUnit::progress_bar).
+/// The filled part only ever grows with the progress: a cell is drawn as
+/// filled, or partly filled, once the progress reaches it and not before.
+///
+/// ```rust
+/// # use std::io::{self, Write};
+/// # use std::sync::{Arc, Mutex};
+/// # use std::time::Duration;
+/// # use hflow::{BarWidth, ExecutionStatus, ExecutionUnit, ProgressBarStyle, ProgressManager, TaskGroup};
+/// # #[derive(Clone, Default)]
+/// # struct Buffer(Arc<Mutex<Vec<u8>>>);
+/// # impl Write for Buffer {
+/// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+/// #     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+/// # }
+/// let run = |style: ProgressBarStyle| {
+///     let buffer = Buffer::default();
+///     let mut manager = ProgressManager::new();
+///     manager.terminal_writer(buffer.clone());
+///     manager.progress_bar(style);
+///     let mut group = TaskGroup::new();
+///     group.add_unit(ExecutionUnit::new("Copy".to_string()).on_execute(|status| {
+///         for copied in [0, 1, 3] {
+///             status.set_progress(copied, 3);
+///             std::thread::sleep(Duration::from_millis(150));
+///         }
+///         status.complete();
+///     }));
+///     manager.add_group(group);
+///     manager.start().unwrap();
+///     String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap()
+/// };
+///
+/// let output = run(ProgressBarStyle::blocks().width(BarWidth::Fixed(6)));
+/// assert!(output.contains("[▱▱▱▱▱▱] 0%"));
+/// assert!(output.contains("[▰▰▱▱▱▱] 33%"));
+/// assert!(output.contains("[▰▰▰▰▰▰] 100%"));
+///
+/// let output = run(ProgressBarStyle::hashes().width(BarWidth::Fixed(12)));
+/// assert!(output.contains("[............] 0%"));
+/// assert!(output.contains("[####........] 33%"));
+/// assert!(output.contains("[############] 100%"));
+///
+/// // A third of 4 cells is 1 cell and a third, drawn as 2 of 6 partial steps.
+/// let output = run(ProgressBarStyle::smooth().width(BarWidth::Fixed(4)));
+/// assert!(output.contains("[⣿⡄  ] 33%"));
+/// assert!(output.contains("[⣿⣿⣿⣿] 100%"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProgressBarStyle {
+    /// A filled cell.
+    pub fill: char,
+    /// The last filled cell, while the bar is not full.
+    pub head: char,
+    /// A cell not reached yet.
+    pub empty: char,
+    /// Partly filled cells, emptiest first, drawn instead of `head` when
+    /// not empty for a bar that moves by fractions of a cell.
+    pub partial: Vec<char>,
+    pub width: BarWidth,
+}
+
+impl ProgressBarStyle {
+    pub fn new(fill: char, head: char, empty: char, width: BarWidth) -> Self {
+        Self {
+            fill,
+            head,
+            empty,
+            partial: Vec::new(),
+            width,
+        }
+    }
+
+    /// The default bar, `[#####.....]` over 10 cells.
+    pub fn hashes() -> Self {
+        Self::new('#', '#', '.', BarWidth::Fixed(BAR_WIDTH))
+    }
+
+    /// `[▰▰▰▰▰▱▱▱▱▱]` over 10 cells.
+    pub fn blocks() -> Self {
+        Self::new('▰', '▰', '▱', BarWidth::Fixed(BAR_WIDTH))
+    }
+
+    /// Braille dots filling each cell in 8 steps, `[⣿⣿⣿⣿⣧     ]` over 10 cells.
+    pub fn smooth() -> Self {
+        Self {
+            partial: vec!['⡀', '⡄', '⡆', '⡇', '⣇', '⣧', '⣷'],
+            ..Self::new('⣿', '⣿', ' ', BarWidth::Fixed(BAR_WIDTH))
+        }
+    }
+
+    /// Same style with another width.
+    pub fn width(mut self, width: BarWidth) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// The cells of a bar of `cells` cells filled to `current` out of `total`.
+    ///
+    /// Fills are rounded down, in steps of a whole cell or of a fraction of
+    /// one with `partial`, so a growing progress never draws a shorter bar.
+    fn cells(&self, current: u64, total: u64, cells: usize) -> String {
+        let steps_per_cell = self.partial.len() as u128 + 1;
+        let steps = if total == 0 {
+            0
+        } else {
+            (current.min(total) as u128 * cells as u128 * steps_per_cell / total as u128) as usize
+        };
+        let full = steps / steps_per_cell as usize;
+        let rest = steps % steps_per_cell as usize;
+        let mut bar = String::new();
+        if full == cells {
+            bar.extend(std::iter::repeat_n(self.fill, cells));
+        } else if !self.partial.is_empty() {
+            bar.extend(std::iter::repeat_n(self.fill, full));
+            let head = if rest > 0 { self.partial[rest - 1] } else { self.empty };
+            bar.push(head);
+            bar.extend(std::iter::repeat_n(self.empty, cells - full - 1));
+        } else if full > 0 {
+            bar.extend(std::iter::repeat_n(self.fill, full - 1));
+            bar.push(self.head);
+            bar.extend(std::iter::repeat_n(self.empty, cells - full));
+        } else {
+            bar.extend(std::iter::repeat_n(self.empty, cells));
+        }
+        bar
+    }
+}
+
+impl Default for ProgressBarStyle {
+    fn default() -> Self {
+        Self::hashes()
+    }
+}
+
+/// Last progress reported by a unit through [`StatusHandle::set_progress`](crate::StatusHandle::set_progress).
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Progress {
+    pub(crate) current: u64,
+    pub(crate) total: u64,
+    pub(crate) updated_at: Instant,
+    rate: RateEstimator,
+}
+
+impl Progress {
+    pub(crate) fn new(current: u64, total: u64) -> Self {
+        let mut progress = Self {
+            current: 0,
+            total,
+            updated_at: Instant::now(),
+            rate: RateEstimator::new(),
+        };
+        progress.report(current, total);
+        progress
+    }
+
+    /// Records a new report; changing the total starts the estimate over.
+    pub(crate) fn report(&mut self, current: u64, total: u64) {
+        if total != self.total {
+            self.total = total;
+            self.rate = RateEstimator::new();
+        }
+        self.current = current.min(total);
+        self.updated_at = Instant::now();
+        self.rate.record(self.current, self.updated_at);
+    }
+
+    /// Whether the bar should be drawn instead of the spinner: the total is
+    /// known and the unit reported recently.
+    pub(crate) fn is_determinate(&self) -> bool {
+        self.total > 0 && self.updated_at.elapsed() < STALE_AFTER
+    }
+
+    pub(crate) fn percent(&self) -> u64 {
+        if self.total == 0 {
+            return 0;
+        }
+        (self.current as u128 * 100 / self.total as u128) as u64
+    }
+
+    /// `[#####.....] 52% (eta 1m 10s)` with a bar of `cells` cells, the ETA
+    /// shows `--` until the rate is known and is left out once the unit
+    /// reached 100%.
+    pub(crate) fn bar(&self, style: &ProgressBarStyle, cells: usize) -> String {
+        let bar = format!("[{}] {}%", style.cells(self.current, self.total, cells), self.percent());
+        if self.current >= self.total {
+            return bar;
+        }
+        match self.rate.eta(self.current, self.total) {
+            Some(eta) => format!("{} (eta {})", bar, crate::format_duration(eta)),
+            None => format!("{} (eta --)", bar),
+        }
+    }
+}
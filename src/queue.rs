@@ -0,0 +1,121 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use crate::{CancellationToken, ExecutionUnit};
+
+/// How often a group waiting for units to be pushed checks whether it is done.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Units pushed to a group while it runs, see [`TaskGroup::queue`](crate::TaskGroup::queue).
+#[derive(Default)]
+pub(crate) struct Queue {
+    state: Mutex<QueueState>,
+    changed: Condvar,
+    /// Units of the group, run or still waiting, shown in the `[i/n]` counters.
+    pub(crate) total: Arc<AtomicUsize>,
+}
+
+#[derive(Default)]
+struct QueueState {
+    units: VecDeque<ExecutionUnit>,
+    /// Live [`QueueHandle`]s, which may still push units.
+    producers: usize,
+    closed: bool,
+}
+
+impl Queue {
+    fn state(&self) -> MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Units pushed and not taken yet.
+    pub(crate) fn len(&self) -> usize {
+        self.state().units.len()
+    }
+
+    /// A [`QueueHandle`] is alive, so units may still be pushed.
+    pub(crate) fn has_producers(&self) -> bool {
+        self.state().producers > 0
+    }
+
+    /// Takes the next unit pushed, waiting for one as long as a handle may
+    /// still push it or `busy` says a unit of the group is running. Returns
+    /// `None` once the group is done, or as soon as the queue is empty if it
+    /// was closed or `cancellation` fired.
+    pub(crate) fn pop(&self, busy: impl Fn() -> bool, cancellation: &CancellationToken) -> Option<ExecutionUnit> {
+        let mut state = self.state();
+        loop {
+            if let Some(unit) = state.units.pop_front() {
+                return Some(unit);
+            }
+            if state.closed || cancellation.is_cancelled() || (state.producers == 0 && !busy()) {
+                return None;
+            }
+            state = self.changed.wait_timeout(state, POLL_INTERVAL).unwrap_or_else(PoisonError::into_inner).0;
+        }
+    }
+
+    /// Puts `units` before the ones pushed so far, in order. They are
+    /// already counted in the `total`.
+    pub(crate) fn push_front(&self, units: Vec<ExecutionUnit>) {
+        let mut state = self.state();
+        for unit in units.into_iter().rev() {
+            state.units.push_front(unit);
+        }
+        drop(state);
+        self.changed.notify_all();
+    }
+
+    /// Takes every unit pushed so far, without waiting for more.
+    pub(crate) fn drain(&self) -> Vec<ExecutionUnit> {
+        self.state().units.drain(..).collect()
+    }
+}
+
+/// Pushes units to a group while it runs, returned by
+/// [`TaskGroup::queue`](crate::TaskGroup::queue) and
+/// [`StatusHandle::queue`](crate::StatusHandle::queue).
+///
+/// The group keeps waiting for units once it ran the others as long as a
+/// handle is alive, until every handle was dropped or one of them
+/// [closed](Self::close) the queue. Cheap to clone, each clone counts as
+/// a handle of its own.
+pub struct QueueHandle {
+    queue: Arc<Queue>,
+}
+
+impl QueueHandle {
+    pub(crate) fn new(queue: Arc<Queue>) -> Self {
+        queue.state().producers += 1;
+        Self { queue }
+    }
+
+    /// Adds `unit` to the end of the group, to run once the units before it did.
+    pub fn push(&self, unit: ExecutionUnit) {
+        self.queue.state().units.push_back(unit);
+        self.queue.total.fetch_add(1, Ordering::SeqCst);
+        self.queue.changed.notify_all();
+    }
+
+    /// Stops the group from waiting for more units, even while other
+    /// handles are alive. The units pushed so far still run.
+    pub fn close(&self) {
+        self.queue.state().closed = true;
+        self.queue.changed.notify_all();
+    }
+}
+
+impl Clone for QueueHandle {
+    fn clone(&self) -> Self {
+        Self::new(self.queue.clone())
+    }
+}
+
+impl Drop for QueueHandle {
+    fn drop(&mut self) {
+        self.queue.state().producers -= 1;
+        self.queue.changed.notify_all();
+    }
+}
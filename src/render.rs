@@ -0,0 +1,278 @@
+use std::any::Any;
+use std::env;
+use std::fs::File;
+use std::io::{self, IsTerminal};
+use std::sync::{Arc, OnceLock};
+use std::sync::atomic::AtomicUsize;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::event::Event;
+use crate::github;
+use crate::live::Renderer;
+use crate::queue::Queue;
+use crate::template::LineTemplates;
+use crate::{Marker, OutputFormat, ProgressBarStyle, ShutdownHandle, SpinnerStyle, TextStyle, Theme, TimestampFormat};
+
+/// Whether progress lines are colored.
+///
+/// Set on [`ProgressManager::color`](crate::ProgressManager::color).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Colors only when the output is a terminal and `NO_COLOR` is not set.
+    #[default]
+    Auto,
+    /// Always colors, even when the output is redirected.
+    Always,
+    /// Never colors, final lines use textual markers such as `[ok]` and `[fail]`.
+    Never,
+}
+
+impl ColorMode {
+    fn enabled(self, terminal: bool) -> bool {
+        match self {
+            ColorMode::Auto => terminal && env::var_os("NO_COLOR").is_none_or(|value| value.is_empty()),
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+        }
+    }
+}
+
+/// Whether the platform or locale suggests the terminal cannot display
+/// non-ASCII symbols: a locale that is set but not UTF-8, or a Windows
+/// console outside of Windows Terminal and VS Code.
+pub(crate) fn prefers_ascii() -> bool {
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .into_iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty());
+    if let Some(locale) = locale {
+        let locale = locale.to_ascii_lowercase();
+        return !locale.contains("utf-8") && !locale.contains("utf8");
+    }
+    cfg!(windows) && env::var_os("WT_SESSION").is_none() && env::var_os("TERM_PROGRAM").is_none()
+}
+
+/// Whether `writer` is stdout or stderr, which end up in the log of a CI job.
+pub(crate) fn is_standard_stream(writer: &dyn Any) -> bool {
+    writer.is::<io::Stdout>() || writer.is::<io::Stderr>()
+}
+
+/// Whether `writer` is a terminal, only known for the standard streams and files.
+pub(crate) fn is_terminal(writer: &dyn Any) -> bool {
+    if let Some(stdout) = writer.downcast_ref::<io::Stdout>() {
+        stdout.is_terminal()
+    } else if let Some(stderr) = writer.downcast_ref::<io::Stderr>() {
+        stderr.is_terminal()
+    } else if let Some(file) = writer.downcast_ref::<File>() {
+        file.is_terminal()
+    } else {
+        false
+    }
+}
+
+/// Position of a unit in its `[1/3]` counter, unset for units executed on
+/// their own.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct RenderInfo {
+    /// Position of the unit, starting at 1.
+    pub(crate) index: Option<u32>,
+    /// Total of the counter, replaced by [`RenderSettings::unit_total`] in named groups.
+    pub(crate) total: Option<u32>,
+}
+
+/// Value shared with the closures of the units.
+pub(crate) type Context = Arc<dyn Any + Send + Sync>;
+
+/// Output settings resolved by the manager and handed down to every unit.
+#[derive(Clone)]
+pub(crate) struct RenderSettings {
+    pub(crate) renderer: Arc<Renderer>,
+    pub(crate) spinner: SpinnerStyle,
+    pub(crate) progress_bar: ProgressBarStyle,
+    pub(crate) theme: Theme,
+    pub(crate) colors: bool,
+    /// Only ASCII characters are printed.
+    pub(crate) ascii: bool,
+    /// Only final lines are printed, even on a terminal.
+    pub(crate) quiet: bool,
+    /// Lines are only appended, even on a terminal.
+    pub(crate) append_only: bool,
+    /// Units only print what they would do, see [`ProgressManager::dry_run`](crate::ProgressManager::dry_run).
+    pub(crate) dry_run: bool,
+    /// Final lines end with their duration against the right edge of the terminal.
+    pub(crate) aligned_durations: bool,
+    /// The terminal bell rings when a unit fails.
+    pub(crate) bell_on_failure: bool,
+    /// Format of the timestamp prefixed to every permanent line, if any.
+    pub(crate) timestamps: Option<TimestampFormat>,
+    /// Start of the run, for [`TimestampFormat::Elapsed`].
+    pub(crate) started: Instant,
+    /// When the whole run times out, see [`ProgressManager::total_timeout`](crate::ProgressManager::total_timeout).
+    pub(crate) deadline: Option<Instant>,
+    /// Stops the run from another thread, see [`ProgressManager::shutdown_handle`](crate::ProgressManager::shutdown_handle).
+    pub(crate) shutdown: Option<ShutdownHandle>,
+    /// Replaces the grace of the units once a shutdown is requested.
+    pub(crate) shutdown_grace: Option<Duration>,
+    /// Default of [`ExecutionUnit::stall_warning`](crate::ExecutionUnit::stall_warning).
+    pub(crate) stall_warning: Option<Duration>,
+    /// Timeouts of the units with a tag, see [`TaskGroup::tag_timeout`](crate::TaskGroup::tag_timeout).
+    pub(crate) tag_timeouts: Vec<(String, Duration)>,
+    /// Printed before every line of the unit.
+    pub(crate) indent: String,
+    /// Groups run at the same time: the lines of their units are
+    /// interleaved, and named ones print the name of the group instead of
+    /// sitting under a header.
+    pub(crate) concurrent_groups: bool,
+    /// The group runs under a manager, which exits itself once the run
+    /// wound down: [`ExitPolicy::ExitProcess`](crate::ExitPolicy::ExitProcess)
+    /// stops the group as [`ExitPolicy::StopAndReturn`](crate::ExitPolicy::StopAndReturn) does.
+    pub(crate) defer_exit: bool,
+    pub(crate) format: OutputFormat,
+    pub(crate) templates: LineTemplates,
+    /// Position of the group in the run and of the unit in its group,
+    /// starting at 1, as reported in events.
+    pub(crate) group: usize,
+    pub(crate) unit: usize,
+    /// Name of the group of the unit, see [`TaskGroup::new_named`](crate::TaskGroup::new_named).
+    pub(crate) group_name: Option<String>,
+    /// Units pushed to the group while it runs, see [`TaskGroup::queue`](crate::TaskGroup::queue).
+    pub(crate) queue: Option<Arc<Queue>>,
+    /// Replaces the total of the `[i/n]` counters of a named group, as it grows with its queue.
+    pub(crate) unit_total: Option<Arc<AtomicUsize>>,
+    /// Why the group cancelled the units still running, e.g. `sibling failed`.
+    pub(crate) cancel_reason: Option<Arc<OnceLock<&'static str>>>,
+    /// Shared with the closures of the units, see [`ProgressManager::with_context`](crate::ProgressManager::with_context).
+    pub(crate) context: Option<Context>,
+    /// Polls the futures of async units on the task awaiting the run.
+    #[cfg(feature = "async")]
+    pub(crate) driver: Option<crate::future::Driver>,
+}
+
+impl RenderSettings {
+    pub(crate) fn new(renderer: Arc<Renderer>, spinner: SpinnerStyle, theme: Theme, color: ColorMode, ascii: bool) -> Self {
+        Self {
+            colors: color.enabled(renderer.is_live()),
+            ascii,
+            quiet: false,
+            append_only: false,
+            dry_run: false,
+            aligned_durations: false,
+            bell_on_failure: false,
+            timestamps: None,
+            started: Instant::now(),
+            deadline: None,
+            shutdown: None,
+            shutdown_grace: None,
+            stall_warning: None,
+            tag_timeouts: Vec::new(),
+            renderer,
+            spinner,
+            progress_bar: ProgressBarStyle::default(),
+            theme,
+            indent: String::new(),
+            concurrent_groups: false,
+            defer_exit: false,
+            format: OutputFormat::Human,
+            templates: LineTemplates::default(),
+            group: 0,
+            unit: 0,
+            group_name: None,
+            queue: None,
+            unit_total: None,
+            cancel_reason: None,
+            context: None,
+            #[cfg(feature = "async")]
+            driver: None,
+        }
+    }
+
+    /// The output is a terminal and neither quiet nor append-only mode is
+    /// on: running units redraw their line in place. Otherwise lines are only
+    /// appended, without any cursor movement.
+    pub(crate) fn is_live(&self) -> bool {
+        self.format == OutputFormat::Human && self.renderer.is_live() && !self.quiet && !self.append_only
+    }
+
+    /// Units print a line when they start, as lines are only appended and
+    /// quiet mode is off.
+    pub(crate) fn announces_start(&self) -> bool {
+        self.is_human() && !self.is_live() && !self.quiet
+    }
+
+    /// Lines are printed for people, not [`OutputFormat::JsonLines`] events.
+    pub(crate) fn is_human(&self) -> bool {
+        self.format != OutputFormat::JsonLines
+    }
+
+    /// Prints a GitHub Actions workflow command when the run reports
+    /// [`OutputFormat::GithubActions`].
+    pub(crate) fn workflow_command(&self, name: &str, value: &str) {
+        if self.format == OutputFormat::GithubActions {
+            self.renderer.println(&github::command(name, value));
+        }
+    }
+
+    /// The time limit of the whole run has been reached.
+    pub(crate) fn past_deadline(&self) -> bool {
+        self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// Rings the terminal bell, only when lines are redrawn on a terminal.
+    pub(crate) fn bell(&self) {
+        if self.is_live() {
+            self.renderer.bell();
+        }
+    }
+
+    /// Prints `event` when the run reports [`OutputFormat::JsonLines`].
+    pub(crate) fn emit(&self, event: Event) {
+        if !self.is_human() {
+            self.renderer.println(&event.into_line());
+        }
+    }
+
+    pub(crate) fn paint(&self, text: &str, style: TextStyle) -> String {
+        let code = match style {
+            _ if !self.colors => return text.to_string(),
+            TextStyle::Plain => return text.to_string(),
+            TextStyle::Color(color) => color.to_fg_str(),
+            TextStyle::Dimmed => "2".into(),
+            TextStyle::Bold => "1".into(),
+        };
+        format!("\x1b[{}m{}\x1b[0m", code, text)
+    }
+
+    /// Prefixes `line` with the current time when timestamps are enabled.
+    /// Live lines are transient and never stamped.
+    pub(crate) fn stamp(&self, line: &str) -> String {
+        match self.timestamps {
+            Some(format) => format!("{} {}", format.format(SystemTime::now(), Instant::now(), self.started), line),
+            None => line.to_string(),
+        }
+    }
+
+    /// Separates a line from the time the unit has been running.
+    pub(crate) fn separator(&self) -> &'static str {
+        if self.ascii { " - " } else { " — " }
+    }
+
+    /// Marks where a line was cut to fit the terminal.
+    pub(crate) fn ellipsis(&self) -> &'static str {
+        if self.ascii { "..." } else { "…" }
+    }
+
+    /// Picks the symbol of the theme, or its plain textual counterpart without colors.
+    pub(crate) fn marker<'a>(&self, marker: &'a Marker, plain: &'a str) -> &'a str {
+        if self.colors { &marker.symbol } else { plain }
+    }
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        if prefers_ascii() {
+            Self::new(Renderer::stdout(), SpinnerStyle::ascii(), Theme::ascii(), ColorMode::Auto, true)
+        } else {
+            Self::new(Renderer::stdout(), SpinnerStyle::default(), Theme::default(), ColorMode::Auto, false)
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Closure computing the delay after a failed attempt, see [`Backoff::custom`].
+type DelayFn = Arc<dyn Fn(u32) -> Duration + Send + Sync + 'static>;
+
+/// How long a unit waits before running its closure again after a failure.
+///
+/// ```rust
+/// # use hflow::{Backoff, Jitter};
+/// # use std::time::Duration;
+/// let backoff = Backoff::Exponential { base: Duration::from_secs(1), factor: 2, max: Duration::from_secs(5) };
+/// let delays: Vec<_> = backoff.delays(0).take(4).map(|delay| delay.as_secs()).collect();
+/// assert_eq!(delays, [1, 2, 4, 5]);
+///
+/// // The same seed draws the same jitter.
+/// let jittered = backoff.jitter(Jitter::Percent(20));
+/// assert_eq!(jittered.delays(7).take(4).collect::<Vec<_>>(), jittered.delays(7).take(4).collect::<Vec<_>>());
+/// assert!(jittered.delays(7).take(4).all(|delay| delay >= Duration::from_millis(800) && delay <= Duration::from_secs(6)));
+///
+/// let linear = Backoff::custom(|attempt| Duration::from_millis(100) * attempt);
+/// assert_eq!(linear.delay(3), Duration::from_millis(300));
+/// ```
+#[derive(Clone)]
+pub enum Backoff {
+    /// Waits the same amount of time before every attempt.
+    Fixed(Duration),
+    /// Waits `base`, then `base * factor`, `base * factor²`, ... up to `max`.
+    Exponential { base: Duration, factor: u32, max: Duration },
+    /// Waits as long as the closure returns for the failed attempt, starting at 1.
+    Custom(DelayFn),
+    /// Varies the delays of `backoff` at random, so that units failing at
+    /// the same time do not all retry at the same time.
+    Jittered { backoff: Box<Backoff>, jitter: Jitter },
+}
+
+/// Random variation of the delays of a [`Backoff`], see [`Backoff::jitter`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Jitter {
+    /// Anywhere between no wait and the full delay.
+    Full,
+    /// Up to this percentage shorter or longer than the delay.
+    Percent(u32),
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Backoff::Fixed(Duration::from_secs(1))
+    }
+}
+
+impl Backoff {
+    /// Waits as long as `delay` returns for the failed attempt, starting at 1.
+    pub fn custom<F>(delay: F) -> Self
+    where
+        F: Fn(u32) -> Duration + Send + Sync + 'static,
+    {
+        Backoff::Custom(Arc::new(delay))
+    }
+
+    /// Varies every delay at random by `jitter`.
+    pub fn jitter(self, jitter: Jitter) -> Self {
+        Backoff::Jittered { backoff: Box::new(self), jitter }
+    }
+
+    /// Delay before the retry that follows the given failed attempt (starting at 1),
+    /// before any jitter.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        match self {
+            Backoff::Fixed(delay) => *delay,
+            Backoff::Exponential { base, factor, max } => {
+                let multiplier = factor.saturating_pow(attempt.saturating_sub(1));
+                base.saturating_mul(multiplier).min(*max)
+            }
+            Backoff::Custom(delay) => delay(attempt),
+            Backoff::Jittered { backoff, .. } => backoff.delay(attempt),
+        }
+    }
+
+    /// The delays after the failed attempts 1, 2, 3, ... with the jitter
+    /// drawn from a random generator seeded with `seed`.
+    pub fn delays(&self, seed: u64) -> impl Iterator<Item = Duration> + '_ {
+        let mut rng = Rng::new(seed);
+        (1..).map(move |attempt| self.jittered_delay(attempt, &mut rng))
+    }
+
+    /// [`delay`](Self::delay) with the jitter drawn from `rng`.
+    pub(crate) fn jittered_delay(&self, attempt: u32, rng: &mut Rng) -> Duration {
+        let Backoff::Jittered { backoff, jitter } = self else {
+            return self.delay(attempt);
+        };
+        let delay = backoff.jittered_delay(attempt, rng);
+        match *jitter {
+            Jitter::Full => delay.mul_f64(rng.next_f64()),
+            Jitter::Percent(percent) => {
+                let spread = f64::from(percent) / 100.0 * (rng.next_f64() * 2.0 - 1.0);
+                delay.mul_f64((1.0 + spread).max(0.0))
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Backoff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Backoff::Fixed(delay) => f.debug_tuple("Fixed").field(delay).finish(),
+            Backoff::Exponential { base, factor, max } => f
+                .debug_struct("Exponential")
+                .field("base", base)
+                .field("factor", factor)
+                .field("max", max)
+                .finish(),
+            Backoff::Custom(_) => f.write_str("Custom(..)"),
+            Backoff::Jittered { backoff, jitter } => {
+                f.debug_struct("Jittered").field("backoff", backoff).field("jitter", jitter).finish()
+            }
+        }
+    }
+}
+
+/// Custom backoffs are only equal to themselves, or to clones of themselves.
+impl PartialEq for Backoff {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Backoff::Fixed(a), Backoff::Fixed(b)) => a == b,
+            (
+                Backoff::Exponential { base, factor, max },
+                Backoff::Exponential { base: other_base, factor: other_factor, max: other_max },
+            ) => base == other_base && factor == other_factor && max == other_max,
+            (Backoff::Custom(a), Backoff::Custom(b)) => Arc::ptr_eq(a, b),
+            (Backoff::Jittered { backoff, jitter }, Backoff::Jittered { backoff: other, jitter: other_jitter }) => {
+                backoff == other && jitter == other_jitter
+            }
+            _ => false,
+        }
+    }
+}
+
+impl From<Duration> for Backoff {
+    fn from(delay: Duration) -> Self {
+        Backoff::Fixed(delay)
+    }
+}
+
+/// Small random generator for jitter (SplitMix64), not meant for anything
+/// that needs to be unpredictable.
+pub(crate) struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Seeded from the clock and a counter, so that units starting at the
+    /// same time still draw different delays.
+    pub(crate) fn from_entropy() -> Self {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let time = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos() as u64;
+        Self::new(time ^ COUNTER.fetch_add(1, Ordering::Relaxed).rotate_left(32))
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[0, 1)`.
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Retry progress shared between the worker thread and the display loop.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct RetryState {
+    /// Attempt currently running or about to run, starting at 1.
+    pub(crate) attempt: u32,
+    /// When the next attempt starts, while waiting between attempts.
+    pub(crate) resume_at: Option<Instant>,
+    /// The unit was cancelled because its timeout expired.
+    pub(crate) timed_out: bool,
+}
@@ -0,0 +1,242 @@
+use std::cell::RefCell;
+use std::fmt::Display;
+use std::marker::PhantomData;
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::{ExecutionUnit, ProgressManager, RunError, RunSummary, StatusHandle, TaskGroup};
+
+/// Worker threads of units given up on after a cancellation, joined once
+/// the [`scope`] ends as they may still use what their closure borrows.
+pub(crate) type Workers = Arc<Mutex<Vec<JoinHandle<()>>>>;
+
+/// Main body of a scoped unit, borrowing from the stack frame around the scope.
+type ScopedCallback<'env> = Box<dyn Fn(StatusHandle) + Send + 'env>;
+
+/// Creates a scope in which the closures of units may borrow anything that
+/// outlives it, e.g. a configuration parsed in `main`, instead of having to
+/// be `'static` and wrapping everything in an `Arc`.
+///
+/// Groups and units are built on the [`Scope`] and run with
+/// [`Scope::run`], which lends them to a [`ProgressManager`] for the
+/// time of the run. The scope only returns once every worker thread of its
+/// units has returned, including the ones of units given up on after their
+/// [`cancel_grace`](ExecutionUnit::cancel_grace): a closure that never
+/// checks whether it was cancelled keeps the scope from ending.
+///
+/// ```rust
+/// # use std::sync::Mutex;
+/// # use hflow::{ProgressManager, RunOutcome};
+/// struct Config {
+///     hosts: Vec<String>,
+/// }
+///
+/// let config = Config { hosts: vec!["web-1".to_string(), "web-2".to_string()] };
+/// let deployed = Mutex::new(Vec::new());
+///
+/// let outcome = hflow::scope(|s| {
+///     let mut deploy = s.named_group("Deploy");
+///     deploy.parallel(2);
+///     for host in &config.hosts {
+///         deploy.unit(format!("Deploy to {}", host)).on_try_execute(|_status| {
+///             deployed.lock().unwrap().push(host.as_str());
+///             Ok::<(), String>(())
+///         });
+///     }
+///     let mut check = s.group();
+///     check.unit("Check").on_try_execute(|_status| match config.hosts.len() {
+///         2 => Ok(()),
+///         _ => Err("missing hosts"),
+///     });
+///     let mut manager = ProgressManager::new();
+///     manager.writer(std::io::sink());
+///     s.run(&mut manager)
+/// });
+///
+/// assert_eq!(outcome.unwrap().outcome(), RunOutcome::Finished);
+/// let mut deployed = deployed.into_inner().unwrap();
+/// deployed.sort();
+/// assert_eq!(deployed, ["web-1", "web-2"]);
+/// ```
+pub fn scope<'env, F, T>(f: F) -> T
+where
+    F: for<'scope> FnOnce(&'scope Scope<'scope, 'env>) -> T,
+{
+    let scope = Scope {
+        groups: RefCell::new(Vec::new()),
+        workers: Workers::default(),
+        scope: PhantomData,
+        env: PhantomData,
+    };
+    let result = panic::catch_unwind(AssertUnwindSafe(|| f(&scope)));
+    scope.groups.borrow_mut().clear();
+    let workers = mem::take(&mut *scope.workers.lock().unwrap_or_else(PoisonError::into_inner));
+    for worker in workers {
+        let _ = worker.join();
+    }
+    match result {
+        Ok(result) => result,
+        Err(payload) => panic::resume_unwind(payload),
+    }
+}
+
+/// Groups and units whose closures borrow data living at least as long as
+/// `'env`, created by [`scope`].
+pub struct Scope<'scope, 'env: 'scope> {
+    groups: RefCell<Vec<TaskGroup>>,
+    workers: Workers,
+    scope: PhantomData<&'scope mut &'scope ()>,
+    env: PhantomData<&'env mut &'env ()>,
+}
+
+impl<'scope, 'env> Scope<'scope, 'env> {
+    /// Adds a group to the run, after the ones added before it.
+    pub fn group(&'scope self) -> ScopedGroup<'scope, 'env> {
+        self.add_group(TaskGroup::new())
+    }
+
+    /// Like [`group`](Self::group), for a group printed under a header, see
+    /// [`TaskGroup::new_named`].
+    pub fn named_group(&'scope self, name: impl Into<String>) -> ScopedGroup<'scope, 'env> {
+        self.add_group(TaskGroup::new_named(name))
+    }
+
+    fn add_group(&'scope self, group: TaskGroup) -> ScopedGroup<'scope, 'env> {
+        let mut groups = self.groups.borrow_mut();
+        groups.push(group);
+        ScopedGroup {
+            scope: self,
+            index: groups.len() - 1,
+        }
+    }
+
+    /// Runs the groups of the scope with the settings of `manager`, after
+    /// the groups `manager` holds itself, as [`ProgressManager::start`] does.
+    ///
+    /// The groups are handed back to the scope once the run is over, they
+    /// can be run again.
+    pub fn run(&self, manager: &mut ProgressManager) -> Result<RunSummary, RunError> {
+        let first = manager.groups.len();
+        manager.groups.append(&mut self.groups.borrow_mut());
+        let result = panic::catch_unwind(AssertUnwindSafe(|| manager.start()));
+        // The closures must not outlive the scope with the manager.
+        *self.groups.borrow_mut() = manager.groups.split_off(first);
+        match result {
+            Ok(result) => result,
+            Err(payload) => panic::resume_unwind(payload),
+        }
+    }
+}
+
+/// A group of a [`Scope`], returned by [`Scope::group`].
+pub struct ScopedGroup<'scope, 'env> {
+    scope: &'scope Scope<'scope, 'env>,
+    index: usize,
+}
+
+impl<'scope, 'env> ScopedGroup<'scope, 'env> {
+    /// Adds a unit described by `description` to the group, to be given a
+    /// closure with [`ScopedUnit::on_execute`].
+    pub fn unit(&mut self, description: impl Into<String>) -> ScopedUnit<'_, 'scope, 'env> {
+        let mut unit = ExecutionUnit::new(description.into());
+        unit.workers = Some(self.scope.workers.clone());
+        let mut groups = self.scope.groups.borrow_mut();
+        let units = &mut groups[self.index].units;
+        units.push(unit);
+        ScopedUnit {
+            group: self,
+            index: units.len() - 1,
+        }
+    }
+
+    /// Runs up to `max_concurrency` units at the same time, see [`TaskGroup::parallel`].
+    pub fn parallel(&mut self, max_concurrency: usize) {
+        self.update(|group| group.parallel(max_concurrency));
+    }
+
+    /// Keeps running the units after a failure, see [`TaskGroup::continue_on_error`].
+    pub fn continue_on_error(&mut self, enabled: bool) {
+        self.update(|group| group.continue_on_error(enabled));
+    }
+
+    fn update(&mut self, update: impl FnOnce(&mut TaskGroup)) {
+        update(&mut self.scope.groups.borrow_mut()[self.index]);
+    }
+}
+
+/// A unit of a [`ScopedGroup`], returned by [`ScopedGroup::unit`], whose
+/// closure may borrow anything that outlives the [`scope`].
+pub struct ScopedUnit<'a, 'scope, 'env> {
+    group: &'a mut ScopedGroup<'scope, 'env>,
+    index: usize,
+}
+
+impl<'env> ScopedUnit<'_, '_, 'env> {
+    /// Sets the main callback of the unit, see [`ExecutionUnit::on_execute`].
+    pub fn on_execute<F>(self, callback: F) -> Self
+    where
+        F: Fn(StatusHandle) + Send + 'env,
+    {
+        let callback: ScopedCallback<'env> = Box::new(callback);
+        // SAFETY: only the lifetime bound of the trait object changes. The
+        // unit never leaves the scope, which drops it and joins every
+        // worker thread that may still call it before `'env` ends.
+        let callback: ScopedCallback<'static> = unsafe { mem::transmute(callback) };
+        self.update(|unit| unit.on_execute(callback))
+    }
+
+    /// Sets the main callback from a closure returning a `Result`, see
+    /// [`ExecutionUnit::on_try_execute`].
+    pub fn on_try_execute<F, E>(self, callback: F) -> Self
+    where
+        F: Fn(StatusHandle) -> Result<(), E> + Send + 'env,
+        E: Display,
+    {
+        self.on_execute(move |status| {
+            let result = callback(status.clone());
+            status.finish_with(result);
+        })
+    }
+
+    /// See [`ExecutionUnit::id`].
+    pub fn id(self, id: impl Into<String>) -> Self {
+        self.update(|unit| unit.id(id))
+    }
+
+    /// See [`ExecutionUnit::depends_on`].
+    pub fn depends_on<I>(self, ids: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.update(|unit| unit.depends_on(ids))
+    }
+
+    /// See [`ExecutionUnit::retries`].
+    pub fn retries(self, retries: u32) -> Self {
+        self.update(|unit| unit.retries(retries))
+    }
+
+    /// See [`ExecutionUnit::timeout`].
+    pub fn timeout(self, timeout: Duration) -> Self {
+        self.update(|unit| unit.timeout(timeout))
+    }
+
+    /// See [`ExecutionUnit::allowed_to_fail`].
+    pub fn allowed_to_fail(self, allowed: bool) -> Self {
+        self.update(|unit| unit.allowed_to_fail(allowed))
+    }
+
+    fn update(self, update: impl FnOnce(ExecutionUnit) -> ExecutionUnit) -> Self {
+        {
+            let mut groups = self.group.scope.groups.borrow_mut();
+            let unit = &mut groups[self.group.index].units[self.index];
+            let taken = mem::replace(unit, ExecutionUnit::new(String::new()));
+            *unit = update(taken);
+        }
+        self
+    }
+}
@@ -0,0 +1,35 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::CancellationToken;
+
+/// Stops the run of a [`ProgressManager`](crate::ProgressManager) from
+/// another thread, returned by [`ProgressManager::shutdown_handle`](crate::ProgressManager::shutdown_handle).
+///
+/// Cheap to clone. Requesting a shutdown only stores two flags, so it may be
+/// done any number of times, from any thread and from a signal handler.
+#[derive(Clone)]
+pub struct ShutdownHandle {
+    requested: Arc<AtomicBool>,
+    cancellation: CancellationToken,
+}
+
+impl ShutdownHandle {
+    pub(crate) fn new(cancellation: CancellationToken) -> Self {
+        Self {
+            requested: Arc::new(AtomicBool::new(false)),
+            cancellation,
+        }
+    }
+
+    /// Cancels the running units and skips the ones left, see
+    /// [`ProgressManager::shutdown_handle`](crate::ProgressManager::shutdown_handle).
+    pub fn request_shutdown(&self) {
+        self.requested.store(true, Ordering::SeqCst);
+        self.cancellation.cancel();
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.requested.load(Ordering::SeqCst)
+    }
+}
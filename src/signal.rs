@@ -0,0 +1,152 @@
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Exit code of a process stopped by Ctrl+C, 128 plus `SIGINT`.
+pub(crate) const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// Number of Ctrl+C received since [`install`] was last called.
+static INTERRUPTS: AtomicUsize = AtomicUsize::new(0);
+
+/// Runs catching Ctrl+C, with the handler found before the first of them.
+static INSTALLED: Mutex<(usize, Option<sys::Previous>)> = Mutex::new((0, None));
+
+/// Catches Ctrl+C until the returned guard is dropped instead of letting it
+/// kill the process, and forgets the ones received before.
+///
+/// The first Ctrl+C is only recorded, see [`interrupted`]; a second one
+/// exits at once, for when cleaning up hangs. Once the last guard is
+/// dropped the handler found before is put back and the count is reset.
+pub(crate) fn install() -> Installed {
+    let mut installed = INSTALLED.lock().unwrap_or_else(|err| err.into_inner());
+    if installed.0 == 0 {
+        installed.1 = Some(sys::install());
+    }
+    installed.0 += 1;
+    INTERRUPTS.store(0, Ordering::SeqCst);
+    Installed(())
+}
+
+/// Restores the previous Ctrl+C handler when dropped, see [`install`].
+pub(crate) struct Installed(());
+
+impl Drop for Installed {
+    fn drop(&mut self) {
+        let mut installed = INSTALLED.lock().unwrap_or_else(|err| err.into_inner());
+        installed.0 -= 1;
+        if installed.0 == 0 {
+            if let Some(previous) = installed.1.take() {
+                sys::restore(previous);
+            }
+            INTERRUPTS.store(0, Ordering::SeqCst);
+        }
+    }
+}
+
+/// Whether Ctrl+C was pressed since [`install`].
+pub(crate) fn interrupted() -> bool {
+    INTERRUPTS.load(Ordering::SeqCst) > 0
+}
+
+/// Called from the signal handler, so it only touches an atomic.
+fn on_interrupt() {
+    if INTERRUPTS.fetch_add(1, Ordering::SeqCst) > 0 {
+        sys::force_exit();
+    }
+}
+
+#[cfg(unix)]
+mod sys {
+    use std::os::raw::c_int;
+
+    const SIGINT: c_int = 2;
+
+    /// Returned by `signal` when it fails.
+    const SIG_ERR: usize = usize::MAX;
+
+    /// The `sighandler_t` in place before [`install`].
+    pub(super) type Previous = usize;
+
+    unsafe extern "C" {
+        fn signal(signum: c_int, handler: usize) -> usize;
+        fn _exit(status: c_int) -> !;
+    }
+
+    extern "C" fn handler(_signum: c_int) {
+        super::on_interrupt();
+    }
+
+    pub(super) fn install() -> Previous {
+        // SAFETY: the handler only updates an atomic and may call `_exit`,
+        // both of which are async-signal-safe.
+        unsafe { signal(SIGINT, handler as extern "C" fn(c_int) as usize) }
+    }
+
+    pub(super) fn restore(previous: Previous) {
+        if previous != SIG_ERR {
+            // SAFETY: `previous` was the disposition of SIGINT until `install`.
+            unsafe {
+                signal(SIGINT, previous);
+            }
+        }
+    }
+
+    pub(super) fn force_exit() {
+        // SAFETY: `_exit` is async-signal-safe, unlike `std::process::exit`.
+        unsafe { _exit(super::INTERRUPTED_EXIT_CODE) }
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    const CTRL_C_EVENT: u32 = 0;
+    const CTRL_BREAK_EVENT: u32 = 1;
+
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        fn SetConsoleCtrlHandler(handler: Option<unsafe extern "system" fn(u32) -> i32>, add: i32) -> i32;
+    }
+
+    unsafe extern "system" fn handler(event: u32) -> i32 {
+        if event == CTRL_C_EVENT || event == CTRL_BREAK_EVENT {
+            super::on_interrupt();
+            1
+        } else {
+            0
+        }
+    }
+
+    /// Handlers are chained, so the ones before need not be saved.
+    pub(super) type Previous = ();
+
+    pub(super) fn install() -> Previous {
+        // SAFETY: the handler is a plain function that lives as long as the process.
+        unsafe {
+            SetConsoleCtrlHandler(Some(handler), 1);
+        }
+    }
+
+    pub(super) fn restore(_previous: Previous) {
+        // SAFETY: removes the handler added by `install`.
+        unsafe {
+            SetConsoleCtrlHandler(Some(handler), 0);
+        }
+    }
+
+    pub(super) fn force_exit() {
+        // Console control handlers run on a thread of their own.
+        std::process::exit(super::INTERRUPTED_EXIT_CODE);
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod sys {
+    pub(super) type Previous = ();
+
+    pub(super) fn install() -> Previous {}
+
+    pub(super) fn restore(_previous: Previous) {}
+
+    pub(super) fn force_exit() {
+        std::process::exit(super::INTERRUPTED_EXIT_CODE);
+    }
+}
@@ -0,0 +1,100 @@
+use std::time::Duration;
+
+/// Frames of the spinner shown while a unit runs, and how long each one stays on screen.
+///
+/// Set for a whole run with [`ProgressManager::spinner`](crate::ProgressManager::spinner)
+/// or for a single unit with [`ExecutionUnit::with_spinner`](crate::ExecutionUnit::with_spinner).
+/// Besides custom frames, presets such as [`braille`](Self::braille),
+/// [`bounce`](Self::bounce), [`arrows`](Self::arrows) and [`clock`](Self::clock)
+/// come with an interval that suits them. Frames may differ in width, the
+/// line is cleared before every frame.
+///
+/// ```rust
+/// # use hflow::{ProgressManager, SpinnerStyle};
+/// let mut manager = ProgressManager::new();
+/// manager.spinner(SpinnerStyle::bounce());
+/// assert_eq!(SpinnerStyle::ascii_line(), SpinnerStyle::ascii());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpinnerStyle {
+    pub frames: Vec<String>,
+    pub interval: Duration,
+}
+
+impl SpinnerStyle {
+    pub fn new<I, S>(frames: I, interval: Duration) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            frames: frames.into_iter().map(Into::into).collect(),
+            interval,
+        }
+    }
+
+    /// Braille dots, `⠋ ⠙ ⠹ ...` at 80ms.
+    pub fn dots() -> Self {
+        Self::new(["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"], Duration::from_millis(80))
+    }
+
+    /// The default spinner, `— \ | /` at 100ms.
+    pub fn line() -> Self {
+        Self::new(["—", "\\", "|", "/"], Duration::from_millis(100))
+    }
+
+    /// Plain ASCII variant of [`line`](Self::line), `- \ | /` at 100ms.
+    pub fn ascii() -> Self {
+        Self::ascii_line()
+    }
+
+    /// `- \ | /` at 100ms, the default spinner when only ASCII is printed.
+    pub fn ascii_line() -> Self {
+        Self::new(["-", "\\", "|", "/"], Duration::from_millis(100))
+    }
+
+    /// Rotating block of braille dots, `⣾ ⣽ ⣻ ...` at 80ms.
+    pub fn braille() -> Self {
+        Self::new(["⣾", "⣽", "⣻", "⢿", "⡿", "⣟", "⣯", "⣷"], Duration::from_millis(80))
+    }
+
+    /// A bar bouncing between brackets, `[=   ] [==  ] ...` at 80ms.
+    pub fn bounce() -> Self {
+        Self::new(
+            [
+                "[    ]", "[=   ]", "[==  ]", "[=== ]", "[ ===]", "[  ==]", "[   =]", "[    ]", "[   =]", "[  ==]",
+                "[ ===]", "[====]", "[=== ]", "[==  ]", "[=   ]",
+            ],
+            Duration::from_millis(80),
+        )
+    }
+
+    /// Arrows turning clockwise, `← ↖ ↑ ↗ ...` at 100ms.
+    pub fn arrows() -> Self {
+        Self::new(["←", "↖", "↑", "↗", "→", "↘", "↓", "↙"], Duration::from_millis(100))
+    }
+
+    /// Clock faces, `🕛 🕐 🕑 ...` at 100ms. Emoji take two columns on most terminals.
+    pub fn clock() -> Self {
+        Self::new(
+            ["🕛", "🕐", "🕑", "🕒", "🕓", "🕔", "🕕", "🕖", "🕗", "🕘", "🕙", "🕚"],
+            Duration::from_millis(100),
+        )
+    }
+
+    /// Frame to show after the spinner has been running for `elapsed`.
+    pub(crate) fn frame(&self, elapsed: Duration) -> &str {
+        if self.frames.is_empty() {
+            return "";
+        }
+        let interval = self.interval.as_millis().max(1);
+        let idx = (elapsed.as_millis() / interval) as usize % self.frames.len();
+        &self.frames[idx]
+    }
+}
+
+impl Default for SpinnerStyle {
+    fn default() -> Self {
+        Self::line()
+    }
+}
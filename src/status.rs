@@ -0,0 +1,223 @@
+use std::any::Any;
+use std::ops::{Deref, DerefMut};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, LockResult, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::ExecutionStatus;
+use crate::observer::{Observers, UnitId};
+use crate::progress::Progress;
+
+/// Details recorded about the failure of a unit.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Failure {
+    pub(crate) reason: Option<String>,
+    pub(crate) exit_code: Option<u8>,
+    /// Error returned by the closure, see [`ExecutionUnit::on_failure`](crate::ExecutionUnit::on_failure).
+    pub(crate) error: Option<Arc<dyn Any + Send + Sync>>,
+}
+
+/// Callback invoked with the old and new status of every transition.
+pub(crate) type StatusHook = Arc<dyn Fn(ExecutionStatus, ExecutionStatus) + Send + Sync + 'static>;
+
+/// Reason recorded when the status lock was found poisoned.
+pub(crate) const POISONED_REASON: &str = "worker panicked";
+
+/// The status of a unit together with the observers of its transitions.
+///
+/// Every write goes through this type so each transition is reported
+/// exactly once, after the lock has been released.
+///
+/// A poisoned status lock never panics: the value is recovered and, unless
+/// the unit had already finished, it is marked as failed.
+pub(crate) struct StatusCell {
+    value: Mutex<ExecutionStatus>,
+    /// Signalled after every transition.
+    changed: Condvar,
+    failure: Mutex<Failure>,
+    progress: Mutex<Option<Progress>>,
+    message: Mutex<Option<String>>,
+    /// Last time the closure set a message or progress.
+    updated: Mutex<Option<Instant>>,
+    unit: UnitId,
+    observers: Mutex<Observers>,
+    hook: Mutex<Option<StatusHook>>,
+    /// The closure saw the unit cancelled, so a failure it reports may be
+    /// due to the cancellation.
+    cancel_seen: AtomicBool,
+}
+
+impl StatusCell {
+    pub(crate) fn new(unit: UnitId) -> Self {
+        Self {
+            value: Mutex::new(ExecutionStatus::Pending),
+            changed: Condvar::new(),
+            failure: Mutex::new(Failure::default()),
+            progress: Mutex::new(None),
+            message: Mutex::new(None),
+            updated: Mutex::new(None),
+            unit,
+            observers: Mutex::new(Observers::default()),
+            hook: Mutex::new(None),
+            cancel_seen: AtomicBool::new(false),
+        }
+    }
+
+    pub(crate) fn unit(&self) -> UnitId {
+        self.unit
+    }
+
+    pub(crate) fn set_observers(&self, observers: Observers) {
+        *self.observers.lock().unwrap() = observers;
+    }
+
+    pub(crate) fn set_hook(&self, hook: StatusHook) {
+        *self.hook.lock().unwrap() = Some(hook);
+    }
+
+    pub(crate) fn cancel_seen(&self) -> bool {
+        self.cancel_seen.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn set_cancel_seen(&self, seen: bool) {
+        self.cancel_seen.store(seen, Ordering::SeqCst);
+    }
+
+    pub(crate) fn get(&self) -> ExecutionStatus {
+        *self.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub(crate) fn failure(&self) -> Failure {
+        self.failure.lock().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    pub(crate) fn update_failure(&self, update: impl FnOnce(&mut Failure)) {
+        update(&mut self.failure.lock().unwrap_or_else(PoisonError::into_inner));
+    }
+
+    pub(crate) fn progress(&self) -> Option<Progress> {
+        *self.progress.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub(crate) fn update_progress(&self, update: impl FnOnce(&mut Option<Progress>)) {
+        update(&mut self.progress.lock().unwrap_or_else(PoisonError::into_inner));
+        self.touch();
+    }
+
+    pub(crate) fn message(&self) -> Option<String> {
+        self.message.lock().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    pub(crate) fn set_message(&self, message: Option<String>) {
+        *self.message.lock().unwrap_or_else(PoisonError::into_inner) = message;
+        self.touch();
+    }
+
+    fn touch(&self) {
+        *self.updated.lock().unwrap_or_else(PoisonError::into_inner) = Some(Instant::now());
+    }
+
+    pub(crate) fn updated(&self) -> Option<Instant> {
+        *self.updated.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub(crate) fn set(&self, status: ExecutionStatus) {
+        self.transition(|_| Some(status));
+    }
+
+    /// Atomically replaces the status when `change` returns a new one,
+    /// returning the status left in the cell.
+    pub(crate) fn transition<F>(&self, change: F) -> ExecutionStatus
+    where
+        F: FnOnce(ExecutionStatus) -> Option<ExecutionStatus>,
+    {
+        let mut guard = self.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(status) = change(*guard) {
+            *guard = status;
+        }
+        *guard
+    }
+
+    /// Blocks until the status is no longer `current` or `timeout` elapsed.
+    pub(crate) fn wait_change(&self, current: ExecutionStatus, timeout: Duration) {
+        let guard = self.value.lock().unwrap_or_else(PoisonError::into_inner);
+        let _ = self.changed.wait_timeout_while(guard, timeout, |status| *status == current);
+    }
+
+    /// Locks the status for a manual update, the transition is reported when the guard is dropped.
+    ///
+    /// The first lock after a poisoning returns the error, with the status
+    /// already moved to failed; the poison is cleared for later callers.
+    pub(crate) fn lock(&self) -> LockResult<StatusGuard<'_>> {
+        match self.value.lock() {
+            Ok(guard) => Ok(StatusGuard::new(self, guard)),
+            Err(poisoned) => {
+                self.value.clear_poison();
+                let mut guard = StatusGuard::new(self, poisoned.into_inner());
+                if !guard.is_finished() {
+                    *guard = ExecutionStatus::Failed;
+                    self.update_failure(|failure| {
+                        failure.reason.get_or_insert_with(|| POISONED_REASON.to_string());
+                    });
+                }
+                Err(PoisonError::new(guard))
+            }
+        }
+    }
+
+    fn notify(&self, from: ExecutionStatus, to: ExecutionStatus) {
+        self.changed.notify_all();
+        let hook = self.hook.lock().unwrap_or_else(PoisonError::into_inner).clone();
+        // A panicking hook or observer must not unwind through the worker or the display loop.
+        if let Some(hook) = hook {
+            let _ = panic::catch_unwind(AssertUnwindSafe(|| hook(from, to)));
+        }
+        let observers = self.observers.lock().unwrap_or_else(PoisonError::into_inner).clone();
+        let at = SystemTime::now();
+        for observer in observers.iter() {
+            let _ = panic::catch_unwind(AssertUnwindSafe(|| observer.on_transition(self.unit, from, to, at)));
+        }
+    }
+}
+
+/// Exclusive access to the status of a unit, obtained from [`StatusHandle::lock`](crate::StatusHandle::lock).
+///
+/// Dereferences to the [`ExecutionStatus`]; observers are notified of the
+/// change once the guard is dropped and the lock released.
+pub struct StatusGuard<'a> {
+    cell: &'a StatusCell,
+    guard: Option<MutexGuard<'a, ExecutionStatus>>,
+    initial: ExecutionStatus,
+}
+
+impl<'a> StatusGuard<'a> {
+    fn new(cell: &'a StatusCell, guard: MutexGuard<'a, ExecutionStatus>) -> Self {
+        let initial = *guard;
+        Self { cell, guard: Some(guard), initial }
+    }
+}
+
+impl Deref for StatusGuard<'_> {
+    type Target = ExecutionStatus;
+
+    fn deref(&self) -> &ExecutionStatus {
+        self.guard.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for StatusGuard<'_> {
+    fn deref_mut(&mut self) -> &mut ExecutionStatus {
+        self.guard.as_mut().unwrap()
+    }
+}
+
+impl Drop for StatusGuard<'_> {
+    fn drop(&mut self) {
+        let current = **self;
+        drop(self.guard.take());
+        if current != self.initial {
+            self.cell.notify(self.initial, current);
+        }
+    }
+}
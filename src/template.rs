@@ -0,0 +1,201 @@
+use std::str::FromStr;
+
+use crate::error::ParseTemplateError;
+use crate::term;
+
+/// Layout of a unit line, such as `"{spinner} {description:<40} [{idx}/{total}] {elapsed}"`.
+///
+/// Placeholders are written `{name}`, optionally followed by a width:
+/// `{name:<40}` pads the value on the right to 40 characters, `{name:>8}`
+/// pads it on the left. `{{` and `}}` print literal braces. The supported
+/// names are:
+///
+/// - `description`: the description of the unit.
+/// - `idx` and `total`: the position of the unit, as in the default `[1/3]` counter.
+/// - `spinner`: the spinner frame, the progress bar once progress is
+///   reported, or the retry countdown. Empty on final lines.
+/// - `status`: the marker of the status, e.g. `✔` or `[ok]`. Empty while the unit runs.
+/// - `elapsed`: the time the unit has been running, or took.
+/// - `message`: the last message set by the closure, if any.
+/// - `percent`: the progress as `52%` once progress is reported.
+///
+/// Set on [`ProgressManager::line_template`](crate::ProgressManager::line_template).
+///
+/// ```rust
+/// # use hflow::LineTemplate;
+/// assert!(LineTemplate::new("{status} {description:<20} {elapsed:>6}").is_ok());
+/// assert!("{spinner} {name}".parse::<LineTemplate>().is_err());
+/// assert!(LineTemplate::new("{description:^20}").is_err());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LineTemplate {
+    pieces: Vec<Piece>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Piece {
+    Text(String),
+    Placeholder { field: Field, padding: Padding },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Field {
+    Description,
+    Idx,
+    Total,
+    Spinner,
+    Status,
+    Elapsed,
+    Message,
+    Percent,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Padding {
+    None,
+    Left(usize),
+    Right(usize),
+}
+
+/// Values substituted for the placeholders of a [`LineTemplate`].
+pub(crate) struct LineValues<'a> {
+    pub(crate) description: &'a str,
+    pub(crate) idx: &'a str,
+    pub(crate) total: &'a str,
+    pub(crate) spinner: &'a str,
+    pub(crate) status: &'a str,
+    pub(crate) elapsed: &'a str,
+    pub(crate) message: &'a str,
+    pub(crate) percent: &'a str,
+}
+
+impl LineTemplate {
+    /// Parses `template`, failing on unknown placeholders, widths that are
+    /// not numbers and unbalanced braces.
+    pub fn new(template: &str) -> Result<Self, ParseTemplateError> {
+        let error = |reason: String| ParseTemplateError {
+            template: template.to_string(),
+            reason,
+        };
+        let mut pieces = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '}' => return Err(error("unmatched `}`".to_string())),
+                '{' => {
+                    let mut placeholder = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => placeholder.push(c),
+                            None => return Err(error("unclosed `{`".to_string())),
+                        }
+                    }
+                    if !text.is_empty() {
+                        pieces.push(Piece::Text(std::mem::take(&mut text)));
+                    }
+                    pieces.push(parse_placeholder(&placeholder).map_err(error)?);
+                }
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            pieces.push(Piece::Text(text));
+        }
+        Ok(Self { pieces })
+    }
+
+    pub(crate) fn render(&self, values: &LineValues) -> String {
+        let mut line = String::new();
+        for piece in &self.pieces {
+            match piece {
+                Piece::Text(text) => line.push_str(text),
+                Piece::Placeholder { field, padding } => {
+                    let value = match field {
+                        Field::Description => values.description,
+                        Field::Idx => values.idx,
+                        Field::Total => values.total,
+                        Field::Spinner => values.spinner,
+                        Field::Status => values.status,
+                        Field::Elapsed => values.elapsed,
+                        Field::Message => values.message,
+                        Field::Percent => values.percent,
+                    };
+                    let fill = match padding {
+                        Padding::None => 0,
+                        Padding::Left(width) | Padding::Right(width) => {
+                            width.saturating_sub(term::width(value))
+                        }
+                    };
+                    if let Padding::Right(_) = padding {
+                        line.extend(std::iter::repeat_n(' ', fill));
+                        line.push_str(value);
+                    } else {
+                        line.push_str(value);
+                        line.extend(std::iter::repeat_n(' ', fill));
+                    }
+                }
+            }
+        }
+        line
+    }
+}
+
+/// Parses the inside of `{...}`, the name and an optional `:<width` or `:>width`.
+fn parse_placeholder(placeholder: &str) -> Result<Piece, String> {
+    let (name, spec) = match placeholder.split_once(':') {
+        Some((name, spec)) => (name, Some(spec)),
+        None => (placeholder, None),
+    };
+    let field = match name {
+        "description" => Field::Description,
+        "idx" => Field::Idx,
+        "total" => Field::Total,
+        "spinner" => Field::Spinner,
+        "status" => Field::Status,
+        "elapsed" => Field::Elapsed,
+        "message" => Field::Message,
+        "percent" => Field::Percent,
+        other => return Err(format!("unknown placeholder `{{{}}}`", other)),
+    };
+    let padding = match spec {
+        None => Padding::None,
+        Some(spec) => {
+            let (align, width) = spec.split_at(spec.chars().next().map_or(0, char::len_utf8));
+            let width = width
+                .parse()
+                .map_err(|_| format!("invalid width in `{{{}}}`, expected `:<N` or `:>N`", placeholder))?;
+            match align {
+                "<" => Padding::Left(width),
+                ">" => Padding::Right(width),
+                _ => return Err(format!("invalid alignment in `{{{}}}`, expected `<` or `>`", placeholder)),
+            }
+        }
+    };
+    Ok(Piece::Placeholder { field, padding })
+}
+
+impl FromStr for LineTemplate {
+    type Err = ParseTemplateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+/// Templates of the lines of a unit, `None` for the default layout.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct LineTemplates {
+    pub(crate) running: Option<LineTemplate>,
+    pub(crate) success: Option<LineTemplate>,
+    pub(crate) failure: Option<LineTemplate>,
+}
@@ -0,0 +1,191 @@
+use std::env;
+
+/// Width used when the terminal size cannot be determined.
+const DEFAULT_WIDTH: usize = 80;
+
+/// Height used when the terminal size cannot be determined.
+const DEFAULT_HEIGHT: usize = 24;
+
+/// Number of columns of the terminal, queried on every call so a resize is
+/// picked up on the next frame. Falls back to `COLUMNS`, then 80.
+pub(crate) fn terminal_width() -> usize {
+    known_width().unwrap_or(DEFAULT_WIDTH)
+}
+
+/// Number of columns of the terminal, `None` when neither the terminal nor `COLUMNS` tell.
+pub(crate) fn known_width() -> Option<usize> {
+    sys::window_size()
+        .map(|(columns, _)| columns)
+        .or_else(|| from_env("COLUMNS"))
+}
+
+/// Number of rows of the terminal. Falls back to `LINES`, then 24.
+pub(crate) fn terminal_height() -> usize {
+    sys::window_size()
+        .map(|(_, rows)| rows)
+        .filter(|&rows| rows > 0)
+        .or_else(|| from_env("LINES"))
+        .unwrap_or(DEFAULT_HEIGHT)
+}
+
+fn from_env(name: &str) -> Option<usize> {
+    env::var(name)
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+        .filter(|&value| value > 0)
+}
+
+/// Number of columns `c` takes on a terminal: 2 for wide characters such as
+/// CJK ideographs and most emoji, 0 for combining marks and other zero-width
+/// characters, 1 otherwise.
+pub(crate) fn char_width(c: char) -> usize {
+    match c as u32 {
+        0x0300..=0x036F | 0x200B..=0x200F | 0x20D0..=0x20FF | 0xFE00..=0xFE0F => 0,
+        _ if c.is_control() => 0,
+        0x1100..=0x115F
+        | 0x231A..=0x231B
+        | 0x23E9..=0x23EC
+        | 0x23F0..=0x23F3
+        | 0x25FD..=0x25FE
+        | 0x2614..=0x2615
+        | 0x2648..=0x2653
+        | 0x26AA..=0x26AB
+        | 0x26BD..=0x26BE
+        | 0x26C4..=0x26C5
+        | 0x2E80..=0x303E
+        | 0x3041..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x1F300..=0x1F64F
+        | 0x1F680..=0x1F6FF
+        | 0x1F900..=0x1F9FF
+        | 0x20000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
+
+/// Number of columns `text` takes on a terminal, see [`char_width`].
+pub(crate) fn width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
+/// Cuts `text` to at most `width` columns, ending it with `ellipsis` when it was cut.
+pub(crate) fn truncate(text: &str, width: usize, ellipsis: &str) -> String {
+    if self::width(text) <= width {
+        return text.to_string();
+    }
+    let ellipsis_width = self::width(ellipsis);
+    if width < ellipsis_width {
+        return take_columns(text, width);
+    }
+    let mut truncated = take_columns(text, width - ellipsis_width);
+    truncated.push_str(ellipsis);
+    truncated
+}
+
+/// The longest prefix of `text` that fits in `columns`.
+fn take_columns(text: &str, columns: usize) -> String {
+    let mut used = 0;
+    text.chars()
+        .take_while(|&c| {
+            used += char_width(c);
+            used <= columns
+        })
+        .collect()
+}
+
+/// Splits `text` into lines of at most `width` columns, breaking on
+/// whitespace and cutting words that do not fit on a line of their own.
+pub(crate) fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let mut word = word.to_string();
+            let line_width = self::width(&line);
+            if line_width > 0 && line_width + 1 + self::width(&word) > width {
+                lines.push(std::mem::take(&mut line));
+            }
+            while self::width(&word) > width {
+                if !line.is_empty() {
+                    lines.push(std::mem::take(&mut line));
+                }
+                // A character wider than the whole line still goes on a line of its own.
+                let head = match take_columns(&word, width) {
+                    head if head.is_empty() => word.chars().take(1).collect(),
+                    head => head,
+                };
+                word.drain(..head.len());
+                lines.push(head);
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&word);
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "dragonfly"
+))]
+mod sys {
+    use std::os::raw::{c_int, c_ulong};
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct Winsize {
+        ws_row: u16,
+        ws_col: u16,
+        ws_xpixel: u16,
+        ws_ypixel: u16,
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    const TIOCGWINSZ: c_ulong = 0x5413;
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    const TIOCGWINSZ: c_ulong = 0x4008_7468;
+
+    unsafe extern "C" {
+        fn ioctl(fd: c_int, request: c_ulong, ...) -> c_int;
+    }
+
+    /// Columns and rows of the terminal attached to stdout or stderr.
+    pub(super) fn window_size() -> Option<(usize, usize)> {
+        [1, 2].into_iter().find_map(|fd| {
+            let mut size = Winsize::default();
+            // SAFETY: TIOCGWINSZ only writes a `winsize` through the pointer, which outlives the call.
+            let result = unsafe { ioctl(fd, TIOCGWINSZ, &mut size as *mut Winsize) };
+            (result == 0 && size.ws_col > 0).then_some((size.ws_col as usize, size.ws_row as usize))
+        })
+    }
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "dragonfly"
+)))]
+mod sys {
+    pub(super) fn window_size() -> Option<(usize, usize)> {
+        None
+    }
+}
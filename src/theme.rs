@@ -0,0 +1,106 @@
+use colored::Color;
+
+/// Color of the lines of running units.
+const GRAY: Color = Color::TrueColor { r: 121, g: 115, b: 118 };
+/// Color of the line of a unit that ran out of time.
+const ORANGE: Color = Color::TrueColor { r: 255, g: 140, b: 0 };
+
+/// How a piece of text is painted when colors are enabled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextStyle {
+    Plain,
+    Color(Color),
+    Dimmed,
+    Bold,
+}
+
+/// Symbol ending the line of a unit in some status, and the style of that line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Marker {
+    pub symbol: String,
+    pub style: TextStyle,
+}
+
+impl Marker {
+    pub fn new(symbol: impl Into<String>, style: TextStyle) -> Self {
+        Self {
+            symbol: symbol.into(),
+            style,
+        }
+    }
+}
+
+/// Symbols and colors of every line printed for a unit.
+///
+/// Set for a whole run with [`ProgressManager::theme`](crate::ProgressManager::theme)
+/// or for a single unit with [`ExecutionUnit::with_theme`](crate::ExecutionUnit::with_theme).
+/// When colors are disabled, see [`ColorMode`](crate::ColorMode), the
+/// symbols are replaced by textual markers such as `[ok]`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Theme {
+    /// Style of the line while the unit runs, retries or is paused.
+    pub in_progress: TextStyle,
+    pub pending: Marker,
+    pub success: Marker,
+    pub failure: Marker,
+    pub warning: Marker,
+    pub skipped: Marker,
+    pub cancelled: Marker,
+    pub timed_out: Marker,
+    pub paused: Marker,
+    /// Style of the `[1/3]` counter, `None` to use the style of the rest of the line.
+    pub counter: Option<TextStyle>,
+}
+
+impl Theme {
+    /// The symbols of the default theme, without any color.
+    pub fn monochrome() -> Self {
+        let plain = |marker: Marker| Marker::new(marker.symbol, TextStyle::Plain);
+        let default = Self::default();
+        Self {
+            in_progress: TextStyle::Plain,
+            pending: plain(default.pending),
+            success: plain(default.success),
+            failure: plain(default.failure),
+            warning: plain(default.warning),
+            skipped: plain(default.skipped),
+            cancelled: plain(default.cancelled),
+            timed_out: plain(default.timed_out),
+            paused: plain(default.paused),
+            counter: None,
+        }
+    }
+
+    /// The colors of the default theme with ASCII symbols only, for terminals
+    /// and log viewers that cannot display `✔` and `✘`.
+    pub fn ascii() -> Self {
+        Self {
+            pending: Marker::new(".", TextStyle::Dimmed),
+            success: Marker::new("OK", TextStyle::Color(Color::Green)),
+            failure: Marker::new("x", TextStyle::Color(Color::Red)),
+            warning: Marker::new("!", TextStyle::Color(Color::Yellow)),
+            skipped: Marker::new("- skipped", TextStyle::Dimmed),
+            cancelled: Marker::new("x cancelled", TextStyle::Color(Color::Magenta)),
+            timed_out: Marker::new("timed out", TextStyle::Color(ORANGE)),
+            paused: Marker::new("|| paused", TextStyle::Color(GRAY)),
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            in_progress: TextStyle::Color(GRAY),
+            pending: Marker::new("·", TextStyle::Dimmed),
+            success: Marker::new("✔", TextStyle::Color(Color::Green)),
+            failure: Marker::new("✘", TextStyle::Color(Color::Red)),
+            warning: Marker::new("⚠", TextStyle::Color(Color::Yellow)),
+            skipped: Marker::new("↷ skipped", TextStyle::Dimmed),
+            cancelled: Marker::new("✗ cancelled", TextStyle::Color(Color::Magenta)),
+            timed_out: Marker::new("⏱ timed out", TextStyle::Color(ORANGE)),
+            paused: Marker::new("‖ paused", TextStyle::Color(GRAY)),
+            counter: None,
+        }
+    }
+}
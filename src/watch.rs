@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// How often the watched paths are scanned for changes.
+pub(crate) const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How long the watched paths must stay unchanged before the plan runs
+/// again, as editors and builds often write several files in a row.
+pub(crate) const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Size and modification time of every file under the watched paths.
+///
+/// Directories are walked recursively, hidden entries and `target`
+/// directories inside them are left out so that a build writing there does
+/// not trigger the next run.
+#[derive(Default, PartialEq)]
+pub(crate) struct Snapshot {
+    files: HashMap<PathBuf, (u64, Option<SystemTime>)>,
+}
+
+impl Snapshot {
+    pub(crate) fn take(paths: &[PathBuf]) -> Self {
+        let mut snapshot = Self::default();
+        for path in paths {
+            snapshot.scan(path);
+        }
+        snapshot
+    }
+
+    fn scan(&mut self, path: &Path) {
+        let Ok(metadata) = fs::symlink_metadata(path) else {
+            return;
+        };
+        if !metadata.is_dir() {
+            self.files.insert(path.to_path_buf(), (metadata.len(), metadata.modified().ok()));
+            return;
+        }
+        let Ok(entries) = fs::read_dir(path) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name.starts_with('.') || (name == "target" && entry.path().is_dir()) {
+                continue;
+            }
+            self.scan(&entry.path());
+        }
+    }
+
+    /// A file created, modified or removed since `earlier` was taken.
+    pub(crate) fn changed_since(&self, earlier: &Snapshot) -> Option<PathBuf> {
+        let changed = self.files.iter().find(|(path, file)| earlier.files.get(*path) != Some(file));
+        let removed = || earlier.files.keys().find(|path| !self.files.contains_key(*path));
+        changed.map(|(path, _)| path).or_else(removed).cloned()
+    }
+}